@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ome_ngff_rs::v0_4::Transform;
+use ome_ngff_rs::{parse_any, AnyNgffMetadata, ToCanonicalJson};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(meta) = parse_any(data) else {
+        return;
+    };
+    match meta {
+        AnyNgffMetadata::V0_4(mut meta) => {
+            let _ = meta.validate();
+            for ms in meta.multiscales().unwrap_or_default() {
+                let mut coord = vec![1.0; 5];
+                for idx in 0..4 {
+                    let _ = (ms, idx).transform(&mut coord);
+                    let _ = (ms, idx).rev_transform(&mut coord);
+                }
+            }
+            meta.normalize();
+            let _ = meta.to_canonical_json();
+        }
+        _ => (),
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ome_ngff_rs::v0_4::PlateReader;
+
+fuzz_target!(|data: &[u8]| {
+    let mut count = 0;
+    if let Ok(plate) = PlateReader::from_attributes(data).for_each_well(|_| count += 1) {
+        let _ = plate.validate();
+    }
+});
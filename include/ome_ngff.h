@@ -0,0 +1,41 @@
+/* C API for ome-ngff-rs, built with the `ffi` feature:
+ * cargo rustc --release --lib --features ffi --crate-type cdylib */
+#ifndef OME_NGFF_H
+#define OME_NGFF_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Opaque parsed v0.4 group attributes. */
+typedef struct NgffMetadata NgffMetadata;
+
+/* Functions which would otherwise panic fail instead, with an error message. */
+
+/* Message of the most recent error on this thread, or NULL.
+ * Owned by the library; valid until the next failing call on this thread. */
+const char *ngff_last_error(void);
+
+/* Parse len bytes of UTF-8 JSON. Returns NULL on failure; free with ngff_free. */
+NgffMetadata *ngff_parse(const unsigned char *json, size_t len);
+
+/* Returns 0 if the metadata is valid, -1 otherwise. */
+int ngff_validate(const NgffMetadata *meta);
+
+/* Write the voxel size of a multiscale's dataset into out.
+ * Returns the dimensionality, or -1 on failure.
+ * Nothing is written if out_len is less than the dimensionality. */
+int64_t ngff_voxel_size(const NgffMetadata *meta, size_t multiscale, size_t dataset,
+                        double *out, size_t out_len);
+
+/* Free metadata returned by ngff_parse. NULL is ignored. */
+void ngff_free(NgffMetadata *meta);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* OME_NGFF_H */
@@ -0,0 +1,948 @@
+use std::collections::HashSet;
+
+use crate::util::{edit_distance, impl_spec_diagnostic, variant_from_data, Name, SpecError};
+use serde::{Deserialize, Serialize};
+use serde_enum_str::{Deserialize_enum_str, Serialize_enum_str};
+use thiserror::Error;
+
+// #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+// pub struct SpaceAxis {
+//     name: String,
+//     unit: Option<SpaceUnit>,
+// }
+
+// #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+// pub struct TimeAxis {
+//     name: String,
+//     unit: Option<TimeUnit>,
+// }
+
+// #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+// pub struct ChannelAxis {
+//     name: String,
+//     unit: Option<String>,
+// }
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CoreAxis {
+    // may need to un-pack these if we want to add distinct functionality to axes, e.g. impl traits
+    Space { name: Name, unit: Option<SpaceUnit> },
+    Time { name: Name, unit: Option<TimeUnit> },
+    Channel { name: Name, unit: Option<String> },
+}
+
+// variant_from_data!(KnownAxis, Space, SpaceAxis);
+// variant_from_data!(KnownAxis, Time, TimeAxis);
+// variant_from_data!(KnownAxis, Channel, ChannelAxis);
+
+// #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+// pub struct UnknownAxis {
+//     name: String,
+//     #[serde(rename = "type")]
+//     axis_type: Option<String>,
+//     unit: Option<String>,
+// }
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Axis {
+    Core(CoreAxis),
+    Custom {
+        name: Name,
+        #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+        axis_type: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
+    },
+}
+
+impl Axis {
+    /// An axis typed by its conventional name:
+    /// `t` is time, `c` is channel, `x`, `y`, and `z` are space, and anything else is untyped.
+    pub fn from_name(name: &str) -> Self {
+        let name = Name::from(name);
+        match name.as_str() {
+            "t" => CoreAxis::Time { name, unit: None }.into(),
+            "c" => CoreAxis::Channel { name, unit: None }.into(),
+            "x" | "y" | "z" => CoreAxis::Space { name, unit: None }.into(),
+            _ => Axis::Custom {
+                name,
+                axis_type: None,
+                unit: None,
+            },
+        }
+    }
+
+    pub fn axis_type(&self) -> AxisType<'_> {
+        match self {
+            Axis::Core(CoreAxis::Space { .. }) => AxisType::Space,
+            Axis::Core(CoreAxis::Time { .. }) => AxisType::Time,
+            Axis::Core(CoreAxis::Channel { .. }) => AxisType::Channel,
+            Axis::Custom { axis_type, .. } => AxisType::Custom(axis_type.as_deref()),
+        }
+    }
+
+    pub fn unit(&self) -> Option<AxisUnit<'_>> {
+        match self {
+            Axis::Core(CoreAxis::Space { unit, .. }) => unit.as_ref().map(AxisUnit::Space),
+            Axis::Core(CoreAxis::Time { unit, .. }) => unit.as_ref().map(AxisUnit::Time),
+            Axis::Core(CoreAxis::Channel { unit, .. }) | Axis::Custom { unit, .. } => {
+                unit.as_deref().map(AxisUnit::Other)
+            }
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Axis::Core(k) => match k {
+                CoreAxis::Space { name, .. } => name.as_str(),
+                CoreAxis::Time { name, .. } => name.as_str(),
+                CoreAxis::Channel { name, .. } => name.as_str(),
+            },
+            Axis::Custom { name, .. } => name.as_str(),
+        }
+    }
+
+    /// Convert any space or time unit which differs from a known unit only by case
+    /// to that unit; other units are left untouched.
+    pub fn normalize(&mut self) {
+        match self {
+            Axis::Core(CoreAxis::Space { unit: Some(u), .. }) => u.normalize(),
+            Axis::Core(CoreAxis::Time { unit: Some(u), .. }) => u.normalize(),
+            _ => (),
+        }
+    }
+}
+
+variant_from_data!(Axis, Core, CoreAxis);
+
+/// The type of an [Axis].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisType<'a> {
+    Space,
+    Time,
+    Channel,
+    /// A type not defined by the spec, if any is given.
+    Custom(Option<&'a str>),
+}
+
+/// The unit of an [Axis] of any type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisUnit<'a> {
+    Space(&'a SpaceUnit),
+    Time(&'a TimeUnit),
+    /// The free-form unit of a channel or custom axis.
+    Other(&'a str),
+}
+// variant_from_data!(Axis, Unknown, UnknownAxis);
+// transitive_into!(Axis, KnownAxis);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize_enum_str, Deserialize_enum_str)]
+#[serde(rename_all = "lowercase")]
+pub enum SpaceUnit {
+    Angstrom,
+    Attometer,
+    Centimeter,
+    Decimeter,
+    Exameter,
+    Femtometer,
+    Foot,
+    Gigameter,
+    Hectometer,
+    Inch,
+    Kilometer,
+    Megameter,
+    Meter,
+    Micrometer,
+    Mile,
+    Millimeter,
+    Nanometer,
+    Parsec,
+    Petameter,
+    Picometer,
+    Terameter,
+    Yard,
+    Yoctometer,
+    Yottameter,
+    Zeptometer,
+    Zettameter,
+    #[serde(other)]
+    Other(String),
+}
+
+impl SpaceUnit {
+    /// Serialized names of all units known to the spec.
+    pub const NAMES: &'static [&'static str] = &[
+        "angstrom",
+        "attometer",
+        "centimeter",
+        "decimeter",
+        "exameter",
+        "femtometer",
+        "foot",
+        "gigameter",
+        "hectometer",
+        "inch",
+        "kilometer",
+        "megameter",
+        "meter",
+        "micrometer",
+        "mile",
+        "millimeter",
+        "nanometer",
+        "parsec",
+        "petameter",
+        "picometer",
+        "terameter",
+        "yard",
+        "yoctometer",
+        "yottameter",
+        "zeptometer",
+        "zettameter",
+    ];
+
+    const SYMBOLS: &'static [(&'static str, &'static str)] = &[
+        ("Em", "exameter"),
+        ("Gm", "gigameter"),
+        ("Mm", "megameter"),
+        ("Pm", "petameter"),
+        ("Tm", "terameter"),
+        ("a", "angstrom"),
+        ("cm", "centimeter"),
+        ("dm", "decimeter"),
+        ("fm", "femtometer"),
+        ("ft", "foot"),
+        ("in", "inch"),
+        ("km", "kilometer"),
+        ("m", "meter"),
+        ("mm", "millimeter"),
+        ("nm", "nanometer"),
+        ("pm", "picometer"),
+        ("um", "micrometer"),
+        ("Å", "angstrom"),
+        ("å", "angstrom"),
+        ("µm", "micrometer"),
+        ("μm", "micrometer"),
+    ];
+
+    const ALIASES: &'static [(&'static str, &'static str)] =
+        &[("micron", "micrometer"), ("microns", "micrometer")];
+
+    /// Convert an unrecognised unit which differs from a known unit only by case
+    /// (e.g. "MicroMeter") to that unit; other units are left untouched.
+    pub fn normalize(&mut self) {
+        if let Self::Other(s) = self {
+            match s.to_lowercase().parse() {
+                Ok(Self::Other(_)) | Err(_) => (),
+                Ok(u) => *self = u,
+            }
+        }
+    }
+
+    /// For an unrecognised unit, the known unit it most likely means, e.g. "micron" → micrometer.
+    pub fn suggest(&self) -> Option<Self> {
+        let Self::Other(s) = self else {
+            return None;
+        };
+        suggest_unit(s, Self::NAMES, Self::SYMBOLS, Self::ALIASES).and_then(|n| n.parse().ok())
+    }
+
+    /// The length of this unit in meters, if it is known.
+    pub fn si_factor(&self) -> Option<f64> {
+        use SpaceUnit::*;
+        Some(match self {
+            Angstrom => 1e-10,
+            Attometer => 1e-18,
+            Centimeter => 1e-2,
+            Decimeter => 1e-1,
+            Exameter => 1e18,
+            Femtometer => 1e-15,
+            Foot => 0.3048,
+            Gigameter => 1e9,
+            Hectometer => 1e2,
+            Inch => 0.0254,
+            Kilometer => 1e3,
+            Megameter => 1e6,
+            Meter => 1.0,
+            Micrometer => 1e-6,
+            Mile => 1609.344,
+            Millimeter => 1e-3,
+            Nanometer => 1e-9,
+            Parsec => 3.085_677_581_491_367e16,
+            Petameter => 1e15,
+            Picometer => 1e-12,
+            Terameter => 1e12,
+            Yard => 0.9144,
+            Yoctometer => 1e-24,
+            Yottameter => 1e24,
+            Zeptometer => 1e-21,
+            Zettameter => 1e21,
+            Other(_) => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize_enum_str, Deserialize_enum_str)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeUnit {
+    Attosecond,
+    Centisecond,
+    Day,
+    Decisecond,
+    Exasecond,
+    Femtosecond,
+    Gigasecond,
+    Hectosecond,
+    Hour,
+    Kilosecond,
+    Megasecond,
+    Microsecond,
+    Millisecond,
+    Minute,
+    Nanosecond,
+    Parsec,
+    Petasecond,
+    Picosecond,
+    Second,
+    Terasecond,
+    Yoctosecond,
+    Yottasecond,
+    Zeptosecond,
+    Zettasecond,
+    #[serde(other)]
+    Other(String),
+}
+
+impl TimeUnit {
+    /// Serialized names of all units known to the spec.
+    pub const NAMES: &'static [&'static str] = &[
+        "attosecond",
+        "centisecond",
+        "day",
+        "decisecond",
+        "exasecond",
+        "femtosecond",
+        "gigasecond",
+        "hectosecond",
+        "hour",
+        "kilosecond",
+        "megasecond",
+        "microsecond",
+        "millisecond",
+        "minute",
+        "nanosecond",
+        "parsec",
+        "petasecond",
+        "picosecond",
+        "second",
+        "terasecond",
+        "yoctosecond",
+        "yottasecond",
+        "zeptosecond",
+        "zettasecond",
+    ];
+
+    const SYMBOLS: &'static [(&'static str, &'static str)] = &[
+        ("Gs", "gigasecond"),
+        ("Ms", "megasecond"),
+        ("d", "day"),
+        ("fs", "femtosecond"),
+        ("h", "hour"),
+        ("ks", "kilosecond"),
+        ("ms", "millisecond"),
+        ("ns", "nanosecond"),
+        ("ps", "picosecond"),
+        ("s", "second"),
+        ("us", "microsecond"),
+        ("µs", "microsecond"),
+        ("μs", "microsecond"),
+    ];
+
+    const ALIASES: &'static [(&'static str, &'static str)] =
+        &[("hr", "hour"), ("min", "minute"), ("sec", "second")];
+
+    /// Convert an unrecognised unit which differs from a known unit only by case
+    /// (e.g. "MicroMeter") to that unit; other units are left untouched.
+    pub fn normalize(&mut self) {
+        if let Self::Other(s) = self {
+            match s.to_lowercase().parse() {
+                Ok(Self::Other(_)) | Err(_) => (),
+                Ok(u) => *self = u,
+            }
+        }
+    }
+
+    /// For an unrecognised unit, the known unit it most likely means, e.g. "sec" → second.
+    pub fn suggest(&self) -> Option<Self> {
+        let Self::Other(s) = self else {
+            return None;
+        };
+        suggest_unit(s, Self::NAMES, Self::SYMBOLS, Self::ALIASES).and_then(|n| n.parse().ok())
+    }
+
+    /// The duration of this unit in seconds, if it is known.
+    ///
+    /// The spec's "parsec" is not a unit of time, so has no factor.
+    pub fn si_factor(&self) -> Option<f64> {
+        use TimeUnit::*;
+        Some(match self {
+            Attosecond => 1e-18,
+            Centisecond => 1e-2,
+            Day => 86400.0,
+            Decisecond => 1e-1,
+            Exasecond => 1e18,
+            Femtosecond => 1e-15,
+            Gigasecond => 1e9,
+            Hectosecond => 1e2,
+            Hour => 3600.0,
+            Kilosecond => 1e3,
+            Megasecond => 1e6,
+            Microsecond => 1e-6,
+            Millisecond => 1e-3,
+            Minute => 60.0,
+            Nanosecond => 1e-9,
+            Petasecond => 1e15,
+            Picosecond => 1e-12,
+            Second => 1.0,
+            Terasecond => 1e12,
+            Yoctosecond => 1e-24,
+            Yottasecond => 1e24,
+            Zeptosecond => 1e-21,
+            Zettasecond => 1e21,
+            Parsec | Other(_) => return None,
+        })
+    }
+}
+
+/// The physical dimension measured by a [Unit].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    Length,
+    Time,
+}
+
+/// A unit of either space or time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Space(SpaceUnit),
+    Time(TimeUnit),
+}
+
+variant_from_data!(Unit, Space, SpaceUnit);
+variant_from_data!(Unit, Time, TimeUnit);
+
+impl Unit {
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Unit::Space(_) => Dimension::Length,
+            Unit::Time(_) => Dimension::Time,
+        }
+    }
+
+    /// The size of this unit in meters or seconds, if it is known.
+    pub fn si_factor(&self) -> Option<f64> {
+        match self {
+            Unit::Space(u) => u.si_factor(),
+            Unit::Time(u) => u.si_factor(),
+        }
+    }
+
+    /// The factor by which to multiply a value in this unit to express it in `other`,
+    /// if both are known and of the same dimension.
+    pub fn conversion_factor(&self, other: &Unit) -> Option<f64> {
+        if self.dimension() != other.dimension() {
+            return None;
+        }
+        Some(self.si_factor()? / other.si_factor()?)
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unit::Space(u) => u.fmt(f),
+            Unit::Time(u) => u.fmt(f),
+        }
+    }
+}
+
+impl AxisUnit<'_> {
+    /// The unit as a [Unit], unless it belongs to a channel or custom axis.
+    pub fn to_unit(&self) -> Option<Unit> {
+        match self {
+            AxisUnit::Space(u) => Some(Unit::Space((*u).clone())),
+            AxisUnit::Time(u) => Some(Unit::Time((*u).clone())),
+            AxisUnit::Other(_) => None,
+        }
+    }
+}
+
+/// Find a known unit name matching an unrecognised one,
+/// by a case-sensitive table of symbols (so "Mm" is not "mm"),
+/// case-insensitive comparison with names and other aliases,
+/// then a small edit distance (allowing for plurals and typos).
+fn suggest_unit(
+    unit: &str,
+    names: &[&'static str],
+    symbols: &[(&'static str, &'static str)],
+    aliases: &[(&'static str, &'static str)],
+) -> Option<&'static str> {
+    let unit = unit.trim();
+    if let Some((_, n)) = symbols.iter().find(|(s, _)| *s == unit) {
+        return Some(n);
+    }
+    let lower = unit.to_lowercase();
+    if let Some(n) = names.iter().find(|n| **n == lower) {
+        return Some(n);
+    }
+    if let Some((_, n)) = aliases.iter().find(|(a, _)| *a == lower) {
+        return Some(n);
+    }
+    let singular = lower.strip_suffix('s').unwrap_or(&lower);
+    if let Some(n) = names.iter().find(|n| **n == singular) {
+        return Some(n);
+    }
+    if let Some((_, n)) = aliases.iter().find(|(a, _)| *a == singular) {
+        return Some(n);
+    }
+    names
+        .iter()
+        .map(|n| (edit_distance(&lower, n), n))
+        .filter(|(d, _)| *d <= 2 && *d * 4 <= lower.len())
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, n)| *n)
+}
+
+/// How strictly to apply the axis rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AxisStrictness {
+    /// Every axis rule is an error.
+    #[default]
+    Strict,
+    /// Extra channel/custom axes ([InvalidAxes::NOther]) and non-standard ordering
+    /// ([InvalidAxes::Order]) are warnings; all other axis rules are still errors.
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidAxes {
+    #[error("Expected 2-5 axes, got {0}")]
+    Count(usize),
+    #[error("Expected 2-3 space axes, got {0}")]
+    NSpace(usize),
+    #[error("Got >1 time axes: {0:?} is extra")]
+    NTime(String),
+    #[error("Got >1 channel/null/custom axes: {0:?} is extra")]
+    NOther(String),
+    #[error(
+        "Invalid order at axis {0:?}: expected [time], [channel/custom], space, space, [space]"
+    )]
+    Order(String),
+    #[error("Axis name {0:?} is not unique")]
+    NonUniqueName(String),
+}
+
+impl SpecError for InvalidAxes {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Count(_) => "ngff::axes::count",
+            Self::NSpace(_) => "ngff::axes::space_count",
+            Self::NTime(_) => "ngff::axes::time_count",
+            Self::NOther(_) => "ngff::axes::other_count",
+            Self::Order(_) => "ngff::axes::order",
+            Self::NonUniqueName(_) => "ngff::axes::non_unique_name",
+        }
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        match self {
+            Self::NonUniqueName(_) => super::spec_url("axes-md"),
+            _ => super::spec_url("multiscale-md"),
+        }
+    }
+}
+
+impl_spec_diagnostic!(InvalidAxes);
+
+impl InvalidAxes {
+    pub fn validate(axes: &[Axis]) -> Result<(), InvalidAxes> {
+        Self::validate_with(axes, AxisStrictness::Strict).map(|_| ())
+    }
+
+    /// Validate axes, returning any rule violations downgraded to warnings by the strictness.
+    pub fn validate_with(
+        axes: &[Axis],
+        strictness: AxisStrictness,
+    ) -> Result<Vec<InvalidAxes>, InvalidAxes> {
+        use InvalidAxes::*;
+
+        let mut warnings = Vec::default();
+        let mut soft = |e: InvalidAxes| match strictness {
+            AxisStrictness::Strict => Err(e),
+            AxisStrictness::Lenient => {
+                warnings.push(e);
+                Ok(())
+            }
+        };
+
+        if axes.len() < 2 || axes.len() > 5 {
+            return Err(Count(axes.len()));
+        }
+        let mut space_count = 0;
+        let mut has_time = false;
+        let mut has_other = false;
+        let mut names = HashSet::with_capacity(axes.len());
+
+        for a in axes.iter() {
+            let n = a.name();
+            if names.contains(n) {
+                return Err(NonUniqueName(n.to_owned()));
+            }
+            names.insert(n);
+            match a {
+                Axis::Core(ak) => match ak {
+                    CoreAxis::Space { .. } => {
+                        space_count += 1;
+                    }
+                    CoreAxis::Time { .. } => {
+                        if space_count > 0 || has_other {
+                            soft(Order(n.to_owned()))?;
+                        }
+                        if has_time {
+                            return Err(NTime(n.to_owned()));
+                        }
+                        has_time = true;
+                    }
+                    CoreAxis::Channel { .. } => {
+                        if space_count > 0 {
+                            soft(Order(n.to_owned()))?;
+                        }
+                        if has_other {
+                            soft(NOther(n.to_owned()))?;
+                        }
+                        has_other = true;
+                    }
+                },
+                Axis::Custom { .. } => {
+                    if space_count > 0 {
+                        soft(Order(n.to_owned()))?;
+                    }
+                    if has_other {
+                        soft(NOther(n.to_owned()))?;
+                    }
+                    has_other = true;
+                }
+            }
+        }
+        if !(2..=3).contains(&space_count) {
+            return Err(NSpace(space_count));
+        }
+        Ok(warnings)
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod arbitrary {
+    use super::*;
+    use crate::util::Validity;
+    use proptest::prelude::*;
+
+    impl Arbitrary for SpaceUnit {
+        type Parameters = Validity;
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(validity: Validity) -> Self::Strategy {
+            let known = prop::sample::select(Self::NAMES).prop_map(|n| n.parse().unwrap());
+            match validity {
+                Validity::Valid => known.boxed(),
+                Validity::Any => prop_oneof![known, "[a-z]{1,10}".prop_map(Self::Other)].boxed(),
+            }
+        }
+    }
+
+    impl Arbitrary for TimeUnit {
+        type Parameters = Validity;
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(validity: Validity) -> Self::Strategy {
+            let known = prop::sample::select(Self::NAMES).prop_map(|n| n.parse().unwrap());
+            match validity {
+                Validity::Valid => known.boxed(),
+                Validity::Any => prop_oneof![known, "[a-z]{1,10}".prop_map(Self::Other)].boxed(),
+            }
+        }
+    }
+
+    impl Arbitrary for Axis {
+        type Parameters = Validity;
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(validity: Validity) -> Self::Strategy {
+            let name = "[a-z]{1,3}";
+            let core = prop_oneof![
+                (name, prop::option::of(any_with::<SpaceUnit>(validity))).prop_map(|(n, unit)| {
+                    CoreAxis::Space {
+                        name: n.into(),
+                        unit,
+                    }
+                }),
+                (name, prop::option::of(any_with::<TimeUnit>(validity))).prop_map(|(n, unit)| {
+                    CoreAxis::Time {
+                        name: n.into(),
+                        unit,
+                    }
+                }),
+                (name, prop::option::of(name)).prop_map(|(n, unit)| CoreAxis::Channel {
+                    name: n.into(),
+                    unit
+                }),
+            ]
+            .prop_map(Axis::Core);
+            match validity {
+                Validity::Valid => core.boxed(),
+                Validity::Any => prop_oneof![
+                    core,
+                    (name, prop::option::of(name), prop::option::of(name)).prop_map(
+                        |(n, axis_type, unit)| Axis::Custom {
+                            name: n.into(),
+                            axis_type,
+                            unit,
+                        }
+                    ),
+                ]
+                .boxed(),
+            }
+        }
+    }
+
+    /// Generate a list of axes for an image.
+    ///
+    /// Valid lists are an optional time axis, an optional channel axis,
+    /// and 2 or 3 space axes, with the conventional names.
+    pub fn arb_axes(validity: Validity) -> BoxedStrategy<Vec<Axis>> {
+        match validity {
+            Validity::Valid => (
+                prop::option::of(any_with::<TimeUnit>(validity)),
+                any::<bool>(),
+                prop::option::of(any_with::<SpaceUnit>(validity)),
+                2usize..=3,
+            )
+                .prop_map(|(time_unit, has_channel, space_unit, n_space)| {
+                    let mut axes = Vec::with_capacity(5);
+                    if let Some(unit) = time_unit {
+                        axes.push(Axis::Core(CoreAxis::Time {
+                            name: "t".into(),
+                            unit: Some(unit),
+                        }));
+                    }
+                    if has_channel {
+                        axes.push(Axis::Core(CoreAxis::Channel {
+                            name: "c".into(),
+                            unit: None,
+                        }));
+                    }
+                    for name in ["z", "y", "x"][3 - n_space..].iter() {
+                        axes.push(Axis::Core(CoreAxis::Space {
+                            name: (*name).into(),
+                            unit: space_unit.clone(),
+                        }));
+                    }
+                    axes
+                })
+                .boxed(),
+            Validity::Any => prop::collection::vec(any_with::<Axis>(validity), 0..7).boxed(),
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+pub use arbitrary::arb_axes;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn str2ax(s: &str) -> Axis {
+        serde_json::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_unit() {
+        assert_eq!(
+            str2ax(r#"{"name": "a", "type": "space", "unit": "foot"}"#),
+            Axis::Core(CoreAxis::Space {
+                name: "a".into(),
+                unit: Some(SpaceUnit::Foot)
+            })
+        );
+
+        assert_eq!(
+            str2ax(r#"{"name": "a", "type": "time", "unit": "second"}"#),
+            Axis::Core(CoreAxis::Time {
+                name: "a".into(),
+                unit: Some(TimeUnit::Second)
+            })
+        );
+
+        assert_eq!(
+            str2ax(r#"{"name": "a", "type": "time", "unit": "foot"}"#),
+            Axis::Core(CoreAxis::Time {
+                name: "a".into(),
+                unit: Some(TimeUnit::Other("foot".to_owned()))
+            })
+        );
+
+        assert_eq!(
+            str2ax(r#"{"name": "a", "type": "channel"}"#),
+            Axis::Core(CoreAxis::Channel {
+                name: "a".into(),
+                unit: None
+            })
+        );
+
+        assert_eq!(
+            str2ax(r#"{"name": "a", "type": "something", "unit": "somethingelse"}"#),
+            Axis::Custom {
+                name: "a".into(),
+                axis_type: Some("something".to_owned()),
+                unit: Some("somethingelse".to_owned()),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_axes_context() {
+        let axes = |s| serde_json::from_str::<Vec<Axis>>(s).unwrap();
+        assert_eq!(
+            InvalidAxes::validate(&axes(
+                r#"[{"name": "y", "type": "space"}, {"name": "c", "type": "channel"}, {"name": "x", "type": "space"}]"#
+            )),
+            Err(InvalidAxes::Order("c".to_owned()))
+        );
+        assert_eq!(
+            InvalidAxes::validate(&axes(
+                r#"[{"name": "y", "type": "space"}, {"name": "y", "type": "space"}]"#
+            )),
+            Err(InvalidAxes::NonUniqueName("y".to_owned()))
+        );
+    }
+
+    #[test]
+    fn lenient_axes() {
+        let axes = serde_json::from_str::<Vec<Axis>>(
+            r#"[{"name": "c", "type": "channel"}, {"name": "d"}, {"name": "y", "type": "space"}, {"name": "d", "type": "space"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            InvalidAxes::validate_with(&axes[..3], AxisStrictness::Lenient),
+            Err(InvalidAxes::NSpace(1))
+        );
+        assert_eq!(
+            InvalidAxes::validate_with(&axes, AxisStrictness::Lenient),
+            Err(InvalidAxes::NonUniqueName("d".to_owned()))
+        );
+        let mut ok = axes.clone();
+        ok[3] = serde_json::from_str(r#"{"name": "x", "type": "space"}"#).unwrap();
+        assert_eq!(
+            InvalidAxes::validate_with(&ok, AxisStrictness::Lenient),
+            Ok(vec![InvalidAxes::NOther("d".to_owned())])
+        );
+        assert_eq!(
+            InvalidAxes::validate(&ok),
+            Err(InvalidAxes::NOther("d".to_owned()))
+        );
+    }
+
+    #[test]
+    fn getters() {
+        let axes = serde_json::from_str::<Vec<Axis>>(
+            r#"[
+                {"name": "t", "type": "time", "unit": "second"},
+                {"name": "c", "type": "channel", "unit": "nm"},
+                {"name": "x", "type": "space", "unit": "micrometer"},
+                {"name": "d", "type": "depth"},
+                {"name": "e"}
+            ]"#,
+        )
+        .unwrap();
+        let types: Vec<_> = axes.iter().map(Axis::axis_type).collect();
+        assert_eq!(
+            types,
+            [
+                AxisType::Time,
+                AxisType::Channel,
+                AxisType::Space,
+                AxisType::Custom(Some("depth")),
+                AxisType::Custom(None)
+            ]
+        );
+        let units: Vec<_> = axes.iter().map(Axis::unit).collect();
+        assert_eq!(
+            units,
+            [
+                Some(AxisUnit::Time(&TimeUnit::Second)),
+                Some(AxisUnit::Other("nm")),
+                Some(AxisUnit::Space(&SpaceUnit::Micrometer)),
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(SpaceUnit::Micrometer.to_string(), "micrometer");
+        assert_eq!(
+            TimeUnit::Other("fortnight".to_owned()).to_string(),
+            "fortnight"
+        );
+        assert_eq!(TimeUnit::Minute.si_factor(), Some(60.0));
+        assert_eq!(TimeUnit::Parsec.si_factor(), None);
+
+        let um = Unit::from(SpaceUnit::Micrometer);
+        assert_eq!(um.dimension(), Dimension::Length);
+        assert_eq!(um.to_string(), "micrometer");
+        let factor = um.conversion_factor(&SpaceUnit::Nanometer.into()).unwrap();
+        assert!((factor - 1000.0).abs() < 1e-9);
+        assert_eq!(um.conversion_factor(&TimeUnit::Second.into()), None);
+        assert_eq!(
+            um.conversion_factor(&SpaceUnit::Other("furlong".to_owned()).into()),
+            None
+        );
+    }
+
+    #[test]
+    fn normalize_units() {
+        let normalize = |s: &str| {
+            let mut u = SpaceUnit::Other(s.to_owned());
+            u.normalize();
+            u
+        };
+        assert_eq!(normalize("MicroMeter"), SpaceUnit::Micrometer);
+        assert_eq!(normalize("Furlong"), SpaceUnit::Other("Furlong".to_owned()));
+        let mut u = TimeUnit::Other("Fortnight".to_owned());
+        u.normalize();
+        assert_eq!(u, TimeUnit::Other("Fortnight".to_owned()));
+    }
+
+    #[test]
+    fn unit_suggestions() {
+        let suggest = |s: &str| SpaceUnit::Other(s.to_owned()).suggest();
+        assert_eq!(suggest("micron"), Some(SpaceUnit::Micrometer));
+        assert_eq!(suggest("Microns"), Some(SpaceUnit::Micrometer));
+        assert_eq!(suggest("nanometers"), Some(SpaceUnit::Nanometer));
+        assert_eq!(suggest("micrometr"), Some(SpaceUnit::Micrometer));
+        assert_eq!(suggest("furlong"), None);
+        assert_eq!(suggest("xy"), None);
+        assert_eq!(suggest("mm"), Some(SpaceUnit::Millimeter));
+        assert_eq!(suggest("Mm"), Some(SpaceUnit::Megameter));
+        assert_eq!(suggest("pm"), Some(SpaceUnit::Picometer));
+        assert_eq!(suggest("Pm"), Some(SpaceUnit::Petameter));
+        let suggest = |s: &str| TimeUnit::Other(s.to_owned()).suggest();
+        assert_eq!(suggest("sec"), Some(TimeUnit::Second));
+        assert_eq!(suggest("Secs"), Some(TimeUnit::Second));
+        assert_eq!(suggest("ms"), Some(TimeUnit::Millisecond));
+        assert_eq!(suggest("Ms"), Some(TimeUnit::Megasecond));
+    }
+}
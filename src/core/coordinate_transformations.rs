@@ -0,0 +1,853 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::util::{impl_spec_diagnostic, InconsistentDimensionality, MaybeNdim, SpecError};
+use crate::RealCoord;
+use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TransformError {
+    #[error(transparent)]
+    Dimensions(#[from] InconsistentDimensionality),
+    #[error("Transform values stored at path {0} must be loaded before use")]
+    UnresolvedPath(String),
+    #[error("No dataset at index {0}")]
+    NoDataset(usize),
+    #[error("No dataset with path {0:?}")]
+    NoPath(String),
+}
+
+impl SpecError for TransformError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Dimensions(e) => e.code(),
+            Self::UnresolvedPath(_) => "ngff::transform::unresolved_path",
+            Self::NoDataset(_) => "ngff::transform::no_dataset",
+            Self::NoPath(_) => "ngff::transform::no_path",
+        }
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        match self {
+            Self::UnresolvedPath(_) => super::spec_url("trafo-md"),
+            _ => None,
+        }
+    }
+}
+
+impl_spec_diagnostic!(TransformError);
+
+/// A transformation of coordinates, in place.
+///
+/// The trait is object-safe, and is implemented for references, boxes, and sequences of
+/// transforms (applied in order), so heterogeneous pipelines can be built from
+/// e.g. `Vec<Box<dyn Transform>>`.
+pub trait Transform {
+    fn transform(&self, coord: &mut [f64]) -> Result<(), TransformError>;
+
+    fn rev_transform(&self, coord: &mut [f64]) -> Result<(), TransformError>;
+
+    /// Transform a [RealCoord], e.g. one created from a slice with [RealCoord::try_from].
+    fn transform_realcoord(&self, coord: &mut RealCoord) -> Result<(), TransformError> {
+        self.transform(coord.as_mut_slice())
+    }
+
+    fn rev_transform_realcoord(&self, coord: &mut RealCoord) -> Result<(), TransformError> {
+        self.rev_transform(coord.as_mut_slice())
+    }
+
+    /// Transform single-precision coordinates.
+    ///
+    /// By default, the coordinates are copied into a new `Vec<f64>`, transformed with
+    /// [Transform::transform], and rounded back to single precision.
+    /// The transforms in this crate override this to work on the coordinates in place,
+    /// with their (double-precision) values rounded to single precision.
+    fn transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        let mut wide: Vec<f64> = coord.iter().map(|c| f64::from(*c)).collect();
+        self.transform(&mut wide)?;
+        coord.iter_mut().zip(wide).for_each(|(c, w)| *c = w as f32);
+        Ok(())
+    }
+
+    /// Reverse-transform single-precision coordinates; see [Transform::transform_f32].
+    fn rev_transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        let mut wide: Vec<f64> = coord.iter().map(|c| f64::from(*c)).collect();
+        self.rev_transform(&mut wide)?;
+        coord.iter_mut().zip(wide).for_each(|(c, w)| *c = w as f32);
+        Ok(())
+    }
+
+    /// The world coordinate of the voxel at `index` (i.e. of its corner nearest the origin).
+    fn voxel_to_world(&self, index: &[u64]) -> Result<Vec<f64>, TransformError> {
+        let mut coord: Vec<f64> = index.iter().map(|i| *i as f64).collect();
+        self.transform(&mut coord)?;
+        Ok(coord)
+    }
+
+    /// The index of the voxel at a world coordinate, rounding each axis as given
+    /// ([Rounding::Floor] finds the voxel containing the coordinate).
+    ///
+    /// Returns `None` if the index would be negative or the coordinate is not finite.
+    fn world_to_voxel(
+        &self,
+        coord: &[f64],
+        rounding: Rounding,
+    ) -> Result<Option<Vec<u64>>, TransformError> {
+        let mut voxel = coord.to_vec();
+        self.rev_transform(&mut voxel)?;
+        Ok(voxel
+            .into_iter()
+            .map(|v| {
+                let rounded = rounding.round(v);
+                (rounded >= 0.0 && rounded.is_finite()).then_some(rounded as u64)
+            })
+            .collect())
+    }
+}
+
+/// How to round a continuous voxel coordinate to an integer index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Rounding {
+    /// Towards negative infinity, i.e. the voxel containing the coordinate.
+    #[default]
+    Floor,
+    /// Towards positive infinity.
+    Ceil,
+    /// To the nearest index, with halves away from zero.
+    Nearest,
+}
+
+impl Rounding {
+    pub fn round(self, value: f64) -> f64 {
+        match self {
+            Rounding::Floor => value.floor(),
+            Rounding::Ceil => value.ceil(),
+            Rounding::Nearest => value.round(),
+        }
+    }
+}
+
+/// A coordinate type in which transforms can be calculated.
+trait Scalar: Copy + AddAssign + SubAssign + MulAssign + DivAssign {
+    fn from_f64(value: f64) -> Self;
+}
+
+impl Scalar for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl Scalar for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslationOrPath {
+    Path(String),
+    #[cfg_attr(feature = "strict_numbers", serde(deserialize_with = "finite_values"))]
+    Translation(Vec<f64>),
+}
+
+impl MaybeNdim for TranslationOrPath {
+    fn maybe_ndim(&self) -> Option<usize> {
+        match self {
+            Self::Translation(v) => Some(v.len()),
+            _ => None,
+        }
+    }
+}
+
+impl TranslationOrPath {
+    fn apply<T: Scalar>(&self, coord: &mut [T], rev: bool) -> Result<(), TransformError> {
+        InconsistentDimensionality::check_dim_opts(self.maybe_ndim(), Some(coord.len()))?;
+        match self {
+            Self::Path(p) => return Err(TransformError::UnresolvedPath(p.clone())),
+            Self::Translation(v) => {
+                for (c, t) in coord.iter_mut().zip(v.iter()) {
+                    if rev {
+                        *c -= T::from_f64(*t);
+                    } else {
+                        *c += T::from_f64(*t);
+                    }
+                }
+            }
+        };
+        Ok(())
+    }
+}
+
+impl Transform for TranslationOrPath {
+    fn transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        self.apply(coord, false)
+    }
+
+    fn rev_transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        self.apply(coord, true)
+    }
+
+    fn transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        self.apply(coord, false)
+    }
+
+    fn rev_transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        self.apply(coord, true)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScaleOrPath {
+    Path(String),
+    #[cfg_attr(feature = "strict_numbers", serde(deserialize_with = "finite_values"))]
+    Scale(Vec<f64>),
+}
+
+/// Deserialize transform values, failing on NaN or infinite values
+/// (which some formats and number parsers produce from out-of-range or non-numeric input).
+#[cfg(feature = "strict_numbers")]
+fn finite_values<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<f64>, D::Error> {
+    let values = Vec::<f64>::deserialize(deserializer)?;
+    match values.iter().position(|v| !v.is_finite()) {
+        Some(idx) => Err(serde::de::Error::custom(format!(
+            "transform value {} at index {idx} is not finite",
+            values[idx]
+        ))),
+        None => Ok(values),
+    }
+}
+
+impl MaybeNdim for ScaleOrPath {
+    fn maybe_ndim(&self) -> Option<usize> {
+        match self {
+            Self::Scale(v) => Some(v.len()),
+            _ => None,
+        }
+    }
+}
+
+impl ScaleOrPath {
+    fn apply<T: Scalar>(&self, coord: &mut [T], rev: bool) -> Result<(), TransformError> {
+        InconsistentDimensionality::check_dim_opts(self.maybe_ndim(), Some(coord.len()))?;
+        match self {
+            Self::Path(p) => return Err(TransformError::UnresolvedPath(p.clone())),
+            Self::Scale(v) => {
+                for (c, t) in coord.iter_mut().zip(v.iter()) {
+                    if rev {
+                        *c /= T::from_f64(*t);
+                    } else {
+                        *c *= T::from_f64(*t);
+                    }
+                }
+            }
+        };
+        Ok(())
+    }
+}
+
+impl Transform for ScaleOrPath {
+    fn transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        self.apply(coord, false)
+    }
+
+    fn rev_transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        self.apply(coord, true)
+    }
+
+    fn transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        self.apply(coord, false)
+    }
+
+    fn rev_transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        self.apply(coord, true)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CoordinateTransformation {
+    Identity,
+    Translation(TranslationOrPath),
+    Scale(ScaleOrPath),
+}
+
+impl CoordinateTransformation {
+    pub fn scale(scale: Vec<f64>) -> Self {
+        Self::Scale(ScaleOrPath::Scale(scale))
+    }
+
+    pub fn translation(translation: Vec<f64>) -> Self {
+        Self::Translation(TranslationOrPath::Translation(translation))
+    }
+
+    pub fn identity() -> Self {
+        Self::Identity
+    }
+
+    /// The scale factors, if this is a scale with inline values.
+    pub fn scale_values(&self) -> Option<&[f64]> {
+        match self {
+            Self::Scale(ScaleOrPath::Scale(s)) => Some(s.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// The offsets, if this is a translation with inline values.
+    pub fn translation_values(&self) -> Option<&[f64]> {
+        match self {
+            Self::Translation(TranslationOrPath::Translation(t)) => Some(t.as_slice()),
+            _ => None,
+        }
+    }
+}
+
+impl Default for CoordinateTransformation {
+    fn default() -> Self {
+        Self::Identity
+    }
+}
+
+impl MaybeNdim for CoordinateTransformation {
+    fn maybe_ndim(&self) -> Option<usize> {
+        match self {
+            Self::Translation(t) => t.maybe_ndim(),
+            Self::Scale(t) => t.maybe_ndim(),
+            _ => None,
+        }
+    }
+}
+
+impl Transform for CoordinateTransformation {
+    fn transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        match self {
+            Self::Identity => Ok(()),
+            Self::Translation(t) => t.transform(coord),
+            Self::Scale(t) => t.transform(coord),
+        }
+    }
+
+    fn rev_transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        match self {
+            Self::Identity => Ok(()),
+            Self::Translation(t) => t.rev_transform(coord),
+            Self::Scale(t) => t.rev_transform(coord),
+        }
+    }
+
+    fn transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        match self {
+            Self::Identity => Ok(()),
+            Self::Translation(t) => t.transform_f32(coord),
+            Self::Scale(t) => t.transform_f32(coord),
+        }
+    }
+
+    fn rev_transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        match self {
+            Self::Identity => Ok(()),
+            Self::Translation(t) => t.rev_transform_f32(coord),
+            Self::Scale(t) => t.rev_transform_f32(coord),
+        }
+    }
+}
+
+impl<T: Transform> Transform for [T] {
+    fn transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        self.iter().try_for_each(|t| t.transform(coord))
+    }
+
+    fn rev_transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        self.iter().rev().try_for_each(|t| t.rev_transform(coord))
+    }
+
+    fn transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        self.iter().try_for_each(|t| t.transform_f32(coord))
+    }
+
+    fn rev_transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        self.iter()
+            .rev()
+            .try_for_each(|t| t.rev_transform_f32(coord))
+    }
+}
+
+impl<T: Transform> Transform for Vec<T> {
+    fn transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        self.as_slice().transform(coord)
+    }
+
+    fn rev_transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        self.as_slice().rev_transform(coord)
+    }
+
+    fn transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        self.as_slice().transform_f32(coord)
+    }
+
+    fn rev_transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        self.as_slice().rev_transform_f32(coord)
+    }
+}
+
+impl<T: Transform + ?Sized> Transform for &T {
+    fn transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        (**self).transform(coord)
+    }
+
+    fn rev_transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        (**self).rev_transform(coord)
+    }
+
+    fn transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        (**self).transform_f32(coord)
+    }
+
+    fn rev_transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        (**self).rev_transform_f32(coord)
+    }
+}
+
+impl<T: Transform + ?Sized> Transform for Box<T> {
+    fn transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        (**self).transform(coord)
+    }
+
+    fn rev_transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        (**self).rev_transform(coord)
+    }
+
+    fn transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        (**self).transform_f32(coord)
+    }
+
+    fn rev_transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        (**self).rev_transform_f32(coord)
+    }
+}
+
+/// An equivalent sequence of at most one scale followed by at most one translation,
+/// with identities removed and consecutive scales and translations fused.
+///
+/// If any transform's values are stored at a path, or their dimensionalities differ,
+/// the transforms cannot be fused, and only the identities are removed.
+pub fn simplify(transforms: &[CoordinateTransformation]) -> Vec<CoordinateTransformation> {
+    let non_identity = || {
+        transforms
+            .iter()
+            .filter(|t| !matches!(t, CoordinateTransformation::Identity))
+    };
+    let fusable = non_identity().try_fold(None, |ndim, t| {
+        let n = t.maybe_ndim()?;
+        ndim.is_none_or(|d| d == n).then_some(Some(n))
+    });
+    let Some(Some(ndim)) = fusable else {
+        return non_identity().cloned().collect();
+    };
+
+    let mut scale: Option<Vec<f64>> = None;
+    let mut translation: Option<Vec<f64>> = None;
+    for t in non_identity() {
+        if let Some(s) = t.scale_values() {
+            let acc = scale.get_or_insert_with(|| vec![1.0; ndim]);
+            acc.iter_mut().zip(s).for_each(|(a, s)| *a *= s);
+            if let Some(acc) = translation.as_mut() {
+                acc.iter_mut().zip(s).for_each(|(a, s)| *a *= s);
+            }
+        } else if let Some(t) = t.translation_values() {
+            let acc = translation.get_or_insert_with(|| vec![0.0; ndim]);
+            acc.iter_mut().zip(t).for_each(|(a, t)| *a += t);
+        }
+    }
+    scale
+        .map(CoordinateTransformation::scale)
+        .into_iter()
+        .chain(translation.map(CoordinateTransformation::translation))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidCoordinateTransforms {
+    #[error("Missing scale transform")]
+    MissingScale,
+    #[error("Transformation {0} is ordered incorrectly")]
+    Order(usize),
+    #[error("Unsupported transformation {1} at index {0}")]
+    Unsupported(usize, String),
+    #[error("Invalid count at transformation {0}: {1}")]
+    Count(usize, String),
+    #[error("Transformation {0} has a non-finite value")]
+    NonFinite(usize),
+    #[error("Transformation {0} has a zero scale factor")]
+    ZeroScale(usize),
+    #[error(transparent)]
+    Dimensions(#[from] InconsistentDimensionality),
+}
+
+impl SpecError for InvalidCoordinateTransforms {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::MissingScale => "ngff::transforms::missing_scale",
+            Self::Order(_) => "ngff::transforms::order",
+            Self::Unsupported(..) => "ngff::transforms::unsupported",
+            Self::Count(..) => "ngff::transforms::count",
+            Self::NonFinite(_) => "ngff::transforms::non_finite",
+            Self::ZeroScale(_) => "ngff::transforms::zero_scale",
+            Self::Dimensions(e) => e.code(),
+        }
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        match self {
+            Self::Unsupported(..) => super::spec_url("trafo-md"),
+            _ => super::spec_url("multiscale-md"),
+        }
+    }
+}
+
+impl_spec_diagnostic!(InvalidCoordinateTransforms);
+
+impl InvalidCoordinateTransforms {
+    pub fn validate(
+        cs: &[CoordinateTransformation],
+        require_scale: bool,
+        mut ndim: Option<usize>,
+    ) -> Result<Option<usize>, Self> {
+        if require_scale && cs.is_empty() {
+            return Err(InvalidCoordinateTransforms::MissingScale);
+        }
+        let mut has_scale = false;
+        let mut has_transl = false;
+
+        for (idx, c) in cs.iter().enumerate() {
+            ndim = InconsistentDimensionality::check_dim_opts(ndim, c.maybe_ndim())?;
+            let values = c
+                .scale_values()
+                .or(c.translation_values())
+                .unwrap_or_default();
+            if !values.iter().all(|v| v.is_finite()) {
+                return Err(InvalidCoordinateTransforms::NonFinite(idx));
+            }
+            if c.scale_values().is_some_and(|s| s.contains(&0.0)) {
+                return Err(InvalidCoordinateTransforms::ZeroScale(idx));
+            }
+            match c {
+                CoordinateTransformation::Identity => {
+                    return Err(InvalidCoordinateTransforms::Unsupported(
+                        idx,
+                        "identity".to_owned(),
+                    ))
+                }
+                CoordinateTransformation::Translation(_) => {
+                    if !has_scale {
+                        return Err(InvalidCoordinateTransforms::Order(idx));
+                    }
+                    if has_transl {
+                        return Err(InvalidCoordinateTransforms::Count(
+                            idx,
+                            "Multiple translations found".to_owned(),
+                        ));
+                    } else {
+                        has_transl = true;
+                    }
+                }
+                CoordinateTransformation::Scale(_) => {
+                    if has_scale {
+                        return Err(InvalidCoordinateTransforms::Count(
+                            idx,
+                            "Multiple scales found".to_owned(),
+                        ));
+                    } else {
+                        has_scale = true;
+                    }
+                }
+            }
+        }
+        Ok(ndim)
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod arbitrary {
+    use super::*;
+    use crate::util::Validity;
+    use proptest::prelude::*;
+
+    fn arb_values(validity: Validity, ndim: usize, positive: bool) -> BoxedStrategy<Vec<f64>> {
+        match (validity, positive) {
+            (Validity::Valid, true) => prop::collection::vec(0.01f64..1000.0, ndim).boxed(),
+            (Validity::Valid, false) => prop::collection::vec(-1000.0f64..1000.0, ndim).boxed(),
+            (Validity::Any, _) => prop::collection::vec(any::<f64>(), ndim).boxed(),
+        }
+    }
+
+    impl Arbitrary for CoordinateTransformation {
+        type Parameters = Validity;
+        type Strategy = BoxedStrategy<Self>;
+
+        /// Valid transformations are scales and translations with finite values.
+        /// Others may also be identities, paths, or contain non-finite or zero values.
+        fn arbitrary_with(validity: Validity) -> Self::Strategy {
+            let values = (2usize..=5).prop_flat_map(move |ndim| {
+                (
+                    arb_values(validity, ndim, true),
+                    arb_values(validity, ndim, false),
+                )
+            });
+            let valued = prop_oneof![
+                values.clone().prop_map(|(s, _)| Self::scale(s)),
+                values.prop_map(|(_, t)| Self::translation(t)),
+            ];
+            match validity {
+                Validity::Valid => valued.boxed(),
+                Validity::Any => prop_oneof![
+                    valued,
+                    Just(Self::Identity),
+                    "[a-z]{1,8}".prop_map(|p| Self::Scale(ScaleOrPath::Path(p))),
+                    "[a-z]{1,8}".prop_map(|p| Self::Translation(TranslationOrPath::Path(p))),
+                ]
+                .boxed(),
+            }
+        }
+    }
+
+    /// Generate a list of transformations for a dataset with the given dimensionality.
+    ///
+    /// Valid lists are a scale, optionally followed by a translation.
+    pub fn arb_transforms(
+        validity: Validity,
+        ndim: usize,
+    ) -> BoxedStrategy<Vec<CoordinateTransformation>> {
+        match validity {
+            Validity::Valid => (
+                arb_values(validity, ndim, true),
+                prop::option::of(arb_values(validity, ndim, false)),
+            )
+                .prop_map(|(s, t)| {
+                    let mut cs = vec![CoordinateTransformation::scale(s)];
+                    cs.extend(t.map(CoordinateTransformation::translation));
+                    cs
+                })
+                .boxed(),
+            Validity::Any => {
+                prop::collection::vec(any_with::<CoordinateTransformation>(validity), 0..4).boxed()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+pub use arbitrary::arb_transforms;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn str2ct(s: &str) -> CoordinateTransformation {
+        serde_json::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_transforms() {
+        assert_eq!(
+            str2ct(r#"{"type": "identity"}"#),
+            CoordinateTransformation::Identity,
+        );
+        assert_eq!(
+            str2ct(r#"{"type": "translation", "path": "path/to/whatever"}"#),
+            CoordinateTransformation::Translation(TranslationOrPath::Path(
+                "path/to/whatever".to_owned()
+            )),
+        );
+        assert_eq!(
+            str2ct(r#"{"type": "scale", "scale": [1,2,3]}"#),
+            CoordinateTransformation::scale(vec![1.0, 2.0, 3.0]),
+        );
+        assert_eq!(
+            str2ct(r#"{"type": "translation", "translation": [1,2,3]}"#),
+            CoordinateTransformation::translation(vec![1.0, 2.0, 3.0]),
+        );
+        let scale = CoordinateTransformation::scale(vec![1.0, 2.0]);
+        assert_eq!(scale.scale_values(), Some([1.0, 2.0].as_slice()));
+        assert_eq!(scale.translation_values(), None);
+        assert_eq!(
+            str2ct(r#"{"type": "translation", "path": "t"}"#).translation_values(),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_values() {
+        use CoordinateTransformation as CT;
+        let validate = |cs: &[CT]| InvalidCoordinateTransforms::validate(cs, true, None);
+        assert_eq!(
+            validate(&[CT::scale(vec![1.0, 2.0]), CT::translation(vec![0.0, -1.0])]),
+            Ok(Some(2))
+        );
+        assert_eq!(
+            validate(&[CT::scale(vec![1.0, 0.0])]),
+            Err(InvalidCoordinateTransforms::ZeroScale(0))
+        );
+        assert_eq!(
+            validate(&[CT::scale(vec![1.0, f64::NAN])]),
+            Err(InvalidCoordinateTransforms::NonFinite(0))
+        );
+        assert_eq!(
+            validate(&[
+                CT::scale(vec![1.0, 1.0]),
+                CT::translation(vec![f64::INFINITY, 0.0])
+            ]),
+            Err(InvalidCoordinateTransforms::NonFinite(1))
+        );
+    }
+
+    #[cfg(feature = "strict_numbers")]
+    #[test]
+    fn strict_numbers() {
+        use serde::de::{value::Error, IntoDeserializer};
+        let parse =
+            |v: Vec<f64>| -> Result<Vec<f64>, Error> { finite_values(v.into_deserializer()) };
+        assert_eq!(parse(vec![1.0, -2.0]).unwrap(), [1.0, -2.0]);
+        assert_eq!(
+            parse(vec![1.0, f64::INFINITY]).unwrap_err().to_string(),
+            "transform value inf at index 1 is not finite"
+        );
+    }
+
+    #[test]
+    fn voxel_indices() {
+        let transforms = vec![
+            CoordinateTransformation::scale(vec![0.5, 2.0]),
+            CoordinateTransformation::translation(vec![1.0, -1.0]),
+        ];
+        assert_eq!(transforms.voxel_to_world(&[2, 3]).unwrap(), [2.0, 5.0]);
+        let world = [2.2, 5.9];
+        assert_eq!(
+            transforms.world_to_voxel(&world, Rounding::Floor).unwrap(),
+            Some(vec![2, 3])
+        );
+        assert_eq!(
+            transforms.world_to_voxel(&world, Rounding::Ceil).unwrap(),
+            Some(vec![3, 4])
+        );
+        assert_eq!(
+            transforms
+                .world_to_voxel(&world, Rounding::Nearest)
+                .unwrap(),
+            Some(vec![2, 3])
+        );
+        assert_eq!(
+            transforms
+                .world_to_voxel(&[0.0, 0.0], Rounding::Floor)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn simplify_transforms() {
+        use CoordinateTransformation as CT;
+        let transforms = [
+            CT::translation(vec![1.0, 1.0]),
+            CT::identity(),
+            CT::scale(vec![2.0, 3.0]),
+            CT::scale(vec![2.0, 1.0]),
+            CT::translation(vec![0.5, 0.5]),
+        ];
+        let simple = simplify(&transforms);
+        assert_eq!(
+            simple,
+            [CT::scale(vec![4.0, 3.0]), CT::translation(vec![4.5, 3.5])]
+        );
+        let mut a = [1.0, 2.0];
+        let mut b = a;
+        transforms.transform(&mut a).unwrap();
+        simple.transform(&mut b).unwrap();
+        assert_eq!(a, b);
+
+        let with_path = [
+            CT::scale(vec![2.0, 2.0]),
+            CT::identity(),
+            CT::Translation(TranslationOrPath::Path("t".to_owned())),
+        ];
+        assert_eq!(
+            simplify(&with_path),
+            [with_path[0].clone(), with_path[2].clone()]
+        );
+    }
+
+    #[test]
+    fn transform_f32() {
+        let transforms = vec![
+            CoordinateTransformation::scale(vec![2.0, 0.5]),
+            CoordinateTransformation::translation(vec![1.0, -1.0]),
+        ];
+        let mut coord = [1.5f32, 3.0];
+        transforms.transform_f32(&mut coord).unwrap();
+        assert_eq!(coord, [4.0, 0.5]);
+        transforms.rev_transform_f32(&mut coord).unwrap();
+        assert_eq!(coord, [1.5, 3.0]);
+        assert!(matches!(
+            transforms.transform_f32(&mut [1.0]),
+            Err(TransformError::Dimensions(_))
+        ));
+
+        /// Only implements the double-precision methods.
+        struct Double;
+
+        impl Transform for Double {
+            fn transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+                coord.iter_mut().for_each(|c| *c *= 2.0);
+                Ok(())
+            }
+
+            fn rev_transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+                coord.iter_mut().for_each(|c| *c /= 2.0);
+                Ok(())
+            }
+        }
+
+        let mut coord = [1.5f32, 3.0];
+        Double.transform_f32(&mut coord).unwrap();
+        assert_eq!(coord, [3.0, 6.0]);
+        Double.rev_transform_f32(&mut coord).unwrap();
+        assert_eq!(coord, [1.5, 3.0]);
+    }
+
+    #[test]
+    fn dyn_pipeline() {
+        let scale = CoordinateTransformation::scale(vec![2.0, 4.0]);
+        let pipeline: Vec<Box<dyn Transform>> = vec![
+            Box::new(scale.clone()),
+            Box::new(vec![CoordinateTransformation::translation(vec![1.0, 1.0])]),
+            Box::new(&scale),
+        ];
+        let mut coord = [1.0, 1.0];
+        pipeline.transform(&mut coord).unwrap();
+        assert_eq!(coord, [6.0, 20.0]);
+        pipeline.rev_transform(&mut coord).unwrap();
+        assert_eq!(coord, [1.0, 1.0]);
+    }
+
+    #[test]
+    fn transform_realcoord() {
+        let transforms = vec![
+            CoordinateTransformation::scale(vec![2.0, 4.0]),
+            CoordinateTransformation::translation(vec![1.0, 1.0]),
+        ];
+        let mut coord = RealCoord::try_from([1.0, 1.0].as_slice()).unwrap();
+        transforms.transform_realcoord(&mut coord).unwrap();
+        assert_eq!(coord.as_slice(), [3.0, 5.0]);
+        transforms.rev_transform_realcoord(&mut coord).unwrap();
+        assert_eq!(coord.as_slice(), [1.0, 1.0]);
+
+        let mut short = RealCoord::try_from([1.0].as_slice()).unwrap();
+        assert!(matches!(
+            transforms.transform_realcoord(&mut short),
+            Err(TransformError::Dimensions(_))
+        ));
+        assert!(RealCoord::try_from([0.0; 6].as_slice()).is_err());
+    }
+}
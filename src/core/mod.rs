@@ -0,0 +1,46 @@
+//! Types which are identical in every supported version of the spec:
+//! axes and their units, scale and translation transforms, and multiscale datasets.
+//!
+//! They are re-exported by each version's module.
+use crate::util::{ToCanonicalJson, Validate};
+use crate::version::NgffVersion;
+
+mod axes;
+mod coordinate_transformations;
+mod multiscale;
+
+#[cfg(feature = "proptest")]
+pub use axes::arb_axes;
+pub use axes::{
+    Axis, AxisStrictness, AxisType, AxisUnit, CoreAxis, Dimension, InvalidAxes, SpaceUnit,
+    TimeUnit, Unit,
+};
+#[cfg(feature = "proptest")]
+pub use coordinate_transformations::arb_transforms;
+pub use coordinate_transformations::{
+    simplify, CoordinateTransformation, InvalidCoordinateTransforms, Rounding, ScaleOrPath,
+    Transform, TransformError, TranslationOrPath,
+};
+pub use multiscale::MultiscaleDataset;
+
+impl ToCanonicalJson for Axis {}
+impl ToCanonicalJson for CoordinateTransformation {}
+impl ToCanonicalJson for MultiscaleDataset {}
+
+impl Validate for MultiscaleDataset {
+    type Error = InvalidCoordinateTransforms;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        MultiscaleDataset::validate(self, None).map(|_| ())
+    }
+}
+
+/// A link to a section of the spec, for errors in the shared types.
+///
+/// These rules were introduced in v0.4, so the link is to that version.
+pub(crate) fn spec_url(fragment: &str) -> Option<String> {
+    Some(format!(
+        "https://ngff.openmicroscopy.org/{}/#{fragment}",
+        NgffVersion::V0_4
+    ))
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+use crate::util::ZPath;
+
+use super::{CoordinateTransformation, InvalidCoordinateTransforms};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiscaleDataset {
+    pub(crate) path: ZPath,
+    pub(crate) coordinate_transformations: Vec<CoordinateTransformation>,
+}
+
+impl MultiscaleDataset {
+    pub fn new<S: Into<ZPath>>(
+        path: S,
+        coordinate_transformations: Vec<CoordinateTransformation>,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            coordinate_transformations,
+        }
+    }
+
+    pub fn validate(
+        &self,
+        ndim: Option<usize>,
+    ) -> Result<Option<usize>, InvalidCoordinateTransforms> {
+        InvalidCoordinateTransforms::validate(
+            self.coordinate_transformations.as_slice(),
+            true,
+            ndim,
+        )
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    /// The dataset's scale factors, if it has a scale transform with inline values.
+    pub fn scale(&self) -> Option<&[f64]> {
+        self.coordinate_transformations
+            .iter()
+            .find_map(CoordinateTransformation::scale_values)
+    }
+}
@@ -0,0 +1,173 @@
+//! Pretty diagnostics pointing into the JSON text which produced an error.
+use miette::{Diagnostic, NamedSource, SourceSpan};
+use thiserror::Error;
+
+/// An error located in the JSON source text it was produced from.
+#[derive(Debug, Error, Diagnostic)]
+#[error("{error}")]
+pub struct SourceDiagnostic {
+    #[source_code]
+    src: NamedSource<String>,
+    #[label("{}", self.label)]
+    span: Option<SourceSpan>,
+    label: String,
+    #[diagnostic_source]
+    error: Box<dyn Diagnostic + Send + Sync>,
+}
+
+impl SourceDiagnostic {
+    /// Label the value at the given JSON pointer, or the whole document if it cannot be found.
+    pub fn at_pointer<E>(name: &str, json: &str, pointer: &str, error: E) -> Self
+    where
+        E: Diagnostic + Send + Sync + 'static,
+    {
+        let span = pointer_span(json, pointer).map(|(start, end)| (start, end - start).into());
+        Self {
+            src: NamedSource::new(name, json.to_owned()),
+            span,
+            label: format!("in {}", if pointer.is_empty() { "/" } else { pointer }),
+            error: Box::new(error),
+        }
+    }
+
+    /// Label a 1-based line and column, as reported by `serde_json`.
+    pub fn at_line_column<E>(name: &str, json: &str, line: usize, column: usize, error: E) -> Self
+    where
+        E: Diagnostic + Send + Sync + 'static,
+    {
+        let offset = json
+            .split_inclusive('\n')
+            .take(line.saturating_sub(1))
+            .map(str::len)
+            .sum::<usize>()
+            + column.saturating_sub(1);
+        Self {
+            src: NamedSource::new(name, json.to_owned()),
+            span: Some(offset.min(json.len()).into()),
+            label: "here".to_owned(),
+            error: Box::new(error),
+        }
+    }
+}
+
+/// Byte range of the value at a JSON pointer (RFC 6901) in valid JSON text.
+pub(crate) fn pointer_span(json: &str, pointer: &str) -> Option<(usize, usize)> {
+    let bytes = json.as_bytes();
+    let mut pos = skip_ws(bytes, 0);
+    let tokens = pointer
+        .split('/')
+        .skip(1)
+        .map(|t| t.replace("~1", "/").replace("~0", "~"));
+    for token in tokens {
+        match bytes.get(pos)? {
+            b'{' => {
+                pos = skip_ws(bytes, pos + 1);
+                loop {
+                    if bytes.get(pos)? != &b'"' {
+                        return None;
+                    }
+                    let key_end = skip_value(bytes, pos)?;
+                    let key: String = serde_json::from_slice(&bytes[pos..key_end]).ok()?;
+                    pos = skip_ws(bytes, key_end);
+                    if bytes.get(pos)? != &b':' {
+                        return None;
+                    }
+                    pos = skip_ws(bytes, pos + 1);
+                    if key == token {
+                        break;
+                    }
+                    pos = skip_ws(bytes, skip_value(bytes, pos)?);
+                    if bytes.get(pos)? != &b',' {
+                        return None;
+                    }
+                    pos = skip_ws(bytes, pos + 1);
+                }
+            }
+            b'[' => {
+                let idx: usize = token.parse().ok()?;
+                pos = skip_ws(bytes, pos + 1);
+                for _ in 0..idx {
+                    pos = skip_ws(bytes, skip_value(bytes, pos)?);
+                    if bytes.get(pos)? != &b',' {
+                        return None;
+                    }
+                    pos = skip_ws(bytes, pos + 1);
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some((pos, skip_value(bytes, pos)?))
+}
+
+fn skip_ws(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Position after the value starting at `pos`.
+fn skip_value(bytes: &[u8], mut pos: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    loop {
+        let b = *bytes.get(pos)?;
+        pos += 1;
+        if in_string {
+            match b {
+                b'\\' => pos += 1,
+                b'"' => {
+                    in_string = false;
+                    if depth == 0 {
+                        return Some(pos);
+                    }
+                }
+                _ => (),
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            }
+            _ if depth == 0 => {
+                // scalar: run until a delimiter
+                while bytes
+                    .get(pos)
+                    .is_some_and(|c| !matches!(c, b',' | b'}' | b']') && !c.is_ascii_whitespace())
+                {
+                    pos += 1;
+                }
+                return Some(pos);
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pointers() {
+        let json = r#" {"a": [1, {"b/c": "x\"y"}, true], "d": null} "#;
+        let span = |p| pointer_span(json, p).map(|(s, e)| &json[s..e]);
+        assert_eq!(
+            span(""),
+            Some(r#"{"a": [1, {"b/c": "x\"y"}, true], "d": null}"#)
+        );
+        assert_eq!(span("/a/0"), Some("1"));
+        assert_eq!(span("/a/1/b~1c"), Some(r#""x\"y""#));
+        assert_eq!(span("/a/2"), Some("true"));
+        assert_eq!(span("/d"), Some("null"));
+        assert_eq!(span("/a/3"), None);
+        assert_eq!(span("/e"), None);
+    }
+}
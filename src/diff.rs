@@ -0,0 +1,120 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::util::canonicalize;
+
+/// A single difference between two documents.
+///
+/// `path` is a JSON pointer (RFC 6901) into the serialized documents.
+/// `old` is `None` for additions, `new` is `None` for removals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub path: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// Compare the serialized forms of two objects,
+/// returning every leaf-level change in document order.
+///
+/// Numbers are compared after canonicalization, so `1.0` and `1` are equal.
+pub fn diff<T: Serialize>(old: &T, new: &T) -> serde_json::Result<Vec<Change>> {
+    let old = canonicalize(serde_json::to_value(old)?);
+    let new = canonicalize(serde_json::to_value(new)?);
+    let mut changes = Vec::default();
+    diff_values(String::new(), &old, &new, &mut changes);
+    Ok(changes)
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn diff_values(path: String, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Object(o), Value::Object(n)) => {
+            for (k, ov) in o.iter() {
+                let p = format!("{path}/{}", escape(k));
+                match n.get(k) {
+                    Some(nv) => diff_values(p, ov, nv, changes),
+                    None => changes.push(Change {
+                        path: p,
+                        old: Some(ov.clone()),
+                        new: None,
+                    }),
+                }
+            }
+            for (k, nv) in n.iter().filter(|(k, _)| !o.contains_key(*k)) {
+                changes.push(Change {
+                    path: format!("{path}/{}", escape(k)),
+                    old: None,
+                    new: Some(nv.clone()),
+                });
+            }
+        }
+        (Value::Array(o), Value::Array(n)) => {
+            for idx in 0..o.len().max(n.len()) {
+                let p = format!("{path}/{idx}");
+                match (o.get(idx), n.get(idx)) {
+                    (Some(ov), Some(nv)) => diff_values(p, ov, nv, changes),
+                    (ov, nv) => changes.push(Change {
+                        path: p,
+                        old: ov.cloned(),
+                        new: nv.cloned(),
+                    }),
+                }
+            }
+        }
+        (o, n) => {
+            if o != n {
+                changes.push(Change {
+                    path,
+                    old: Some(o.clone()),
+                    new: Some(n.clone()),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn nested_changes() {
+        let old = json!({"a": [1.0, 2], "b": {"c/d": "x"}, "gone": true});
+        let new = json!({"a": [1, 3, 4], "b": {"c/d": "y"}, "added": null});
+        assert_eq!(
+            diff(&old, &new).unwrap(),
+            vec![
+                Change {
+                    path: "/a/1".to_owned(),
+                    old: Some(json!(2)),
+                    new: Some(json!(3))
+                },
+                Change {
+                    path: "/a/2".to_owned(),
+                    old: None,
+                    new: Some(json!(4))
+                },
+                Change {
+                    path: "/b/c~1d".to_owned(),
+                    old: Some(json!("x")),
+                    new: Some(json!("y"))
+                },
+                Change {
+                    path: "/gone".to_owned(),
+                    old: Some(json!(true)),
+                    new: None
+                },
+                Change {
+                    path: "/added".to_owned(),
+                    old: None,
+                    new: Some(Value::Null)
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! A C API over v0.4 metadata.
+//!
+//! See `include/ome_ngff.h` for the declarations.
+//! Functions which fail set a thread-local error message, retrievable with [ngff_last_error].
+//! Panics are caught at the boundary and reported as failures.
+//!
+//! Build the shared library with `cargo rustc --release --lib --features ffi --crate-type cdylib`.
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::v0_4::NgffMetadata;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error<E: ToString>(e: E) {
+    let msg = CString::new(e.to_string().replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|le| *le.borrow_mut() = Some(msg));
+}
+
+/// Run `f`, returning `failed` (and setting the error) if it panics,
+/// as unwinding into foreign code is undefined behaviour.
+fn catch<T, F: FnOnce() -> T>(failed: T, f: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        set_error(format!("Panicked: {}", panic_message(payload.as_ref())));
+        failed
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "unknown cause"
+    }
+}
+
+/// The message of the most recent error on this thread, or null if there has been none.
+///
+/// The string is owned by the library and valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn ngff_last_error() -> *const c_char {
+    LAST_ERROR.with(|le| le.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Parse `len` bytes of UTF-8 JSON, returning null on failure.
+///
+/// The result must be freed with [ngff_free].
+///
+/// # Safety
+///
+/// `json` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ngff_parse(json: *const u8, len: usize) -> *mut NgffMetadata {
+    if json.is_null() {
+        set_error("Null pointer");
+        return ptr::null_mut();
+    }
+    let bytes = std::slice::from_raw_parts(json, len);
+    catch(ptr::null_mut(), || match NgffMetadata::from_slice(bytes) {
+        Ok(meta) => Box::into_raw(Box::new(meta)),
+        Err(e) => {
+            set_error(e);
+            ptr::null_mut()
+        }
+    })
+}
+
+/// Validate parsed metadata, returning 0 if valid and -1 otherwise.
+///
+/// # Safety
+///
+/// `meta` must be null or a pointer returned by [ngff_parse] which has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn ngff_validate(meta: *const NgffMetadata) -> c_int {
+    let Some(meta) = meta.as_ref() else {
+        set_error("Null pointer");
+        return -1;
+    };
+    catch(-1, || match meta.validate() {
+        Ok(()) => 0,
+        Err(e) => {
+            set_error(e);
+            -1
+        }
+    })
+}
+
+/// Write the voxel size of a multiscale's dataset into `out`,
+/// returning the image's dimensionality, or -1 on failure.
+///
+/// If `out_len` is smaller than the dimensionality, nothing is written;
+/// call with `out_len = 0` to query the dimensionality.
+///
+/// # Safety
+///
+/// `meta` must be null or a pointer returned by [ngff_parse] which has not been freed.
+/// `out` must be valid for writes of `out_len` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn ngff_voxel_size(
+    meta: *const NgffMetadata,
+    multiscale: usize,
+    dataset: usize,
+    out: *mut f64,
+    out_len: usize,
+) -> i64 {
+    let Some(meta) = meta.as_ref() else {
+        set_error("Null pointer");
+        return -1;
+    };
+    let Some(ms) = meta.multiscales().and_then(|mss| mss.get(multiscale)) else {
+        set_error(format!("No multiscale at index {multiscale}"));
+        return -1;
+    };
+    catch(-1, || {
+        let size = match ms.voxel_size(dataset) {
+            Ok(s) => s,
+            Err(e) => {
+                set_error(e);
+                return -1;
+            }
+        };
+        if !out.is_null() && out_len >= size.len() {
+            ptr::copy_nonoverlapping(size.as_ptr(), out, size.len());
+        }
+        size.len() as i64
+    })
+}
+
+/// Free metadata returned by [ngff_parse]. Null is ignored.
+///
+/// # Safety
+///
+/// `meta` must be null or a pointer returned by [ngff_parse] which has not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn ngff_free(meta: *mut NgffMetadata) {
+    if !meta.is_null() {
+        let meta = Box::from_raw(meta);
+        catch((), || drop(meta));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn roundtrip() {
+        let json = br#"{"multiscales": [{
+            "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+            "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [2, 3]}]}]
+        }]}"#;
+        unsafe {
+            let meta = ngff_parse(json.as_ptr(), json.len());
+            assert!(!meta.is_null());
+            assert_eq!(ngff_validate(meta), 0);
+
+            let mut out = [0.0; 2];
+            assert_eq!(ngff_voxel_size(meta, 0, 0, out.as_mut_ptr(), 0), 2);
+            assert_eq!(out, [0.0, 0.0]);
+            assert_eq!(ngff_voxel_size(meta, 0, 0, out.as_mut_ptr(), 2), 2);
+            assert_eq!(out, [2.0, 3.0]);
+
+            assert_eq!(ngff_voxel_size(meta, 0, 1, out.as_mut_ptr(), 2), -1);
+            let msg = CStr::from_ptr(ngff_last_error()).to_str().unwrap();
+            assert_eq!(msg, "No dataset at index 1");
+            ngff_free(meta);
+
+            assert!(ngff_parse(b"{".as_ptr(), 1).is_null());
+        }
+    }
+
+    #[test]
+    fn catch_panics() {
+        assert_eq!(catch(-1, || 1), 1);
+        assert_eq!(catch(-1, || panic!("oh no {}", 1)), -1);
+        let msg = unsafe { CStr::from_ptr(ngff_last_error()) };
+        assert_eq!(msg.to_str().unwrap(), "Panicked: oh no 1");
+        assert_eq!(catch(-1, || panic!("static")), -1);
+        let msg = unsafe { CStr::from_ptr(ngff_last_error()) };
+        assert_eq!(msg.to_str().unwrap(), "Panicked: static");
+    }
+}
@@ -4,9 +4,40 @@ const MAX_DIMS: usize = 5;
 pub type DimVec<T> = ArrayVec<T, MAX_DIMS>;
 pub type RealCoord = DimVec<f64>;
 
+mod version;
+pub use version::{detect_version, NgffVersion};
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+pub use version::{Spec, SpecType};
+
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+mod core;
+#[cfg(all(feature = "miette", any(feature = "v0_4", feature = "v0_5")))]
+pub mod diagnostic;
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+pub mod diff;
+#[cfg(feature = "v0_4")]
+mod migrate;
+#[cfg(feature = "v0_4")]
+pub use migrate::{migrate_store, migrate_store_with_progress, MigrateError, Migration};
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+mod parse;
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+pub use parse::{parse_any, AnyNgffMetadata, ParseError};
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+pub mod store;
 #[cfg(any(feature = "v0_4", feature = "v0_5"))]
 mod util;
 
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(all(feature = "proptest", any(feature = "v0_4", feature = "v0_5")))]
+pub use util::Validity;
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+pub use util::{JsonError, Name, SpecError, ToCanonicalJson, Validate, Validated, ValidatedError};
+
 #[cfg(feature = "v0_4")]
 pub mod v0_4;
 
@@ -0,0 +1,583 @@
+use std::collections::VecDeque;
+
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::diff::{diff, Change};
+use crate::store::{join_key, LoadError, Progress, ReadableStore, StoreError, WritableStore};
+use crate::v0_4::{
+    InvalidNgffMetadata, NgffMetadata, Transaction, WriteError, ARRAY_KEY, ATTRS_KEY, GROUP_KEY,
+};
+use crate::version::NgffVersion;
+
+/// The key of a zarr v3 node's metadata, relative to the node.
+const ZARR_JSON_KEY: &str = "zarr.json";
+
+/// Keys of the attributes which the v0.5 spec moves under `ome`.
+const NGFF_KEYS: &[&str] = &[
+    "multiscales",
+    "omero",
+    "labels",
+    "image-label",
+    "plate",
+    "well",
+    "bioformats2raw.layout",
+];
+
+/// Failure to migrate a store between spec versions.
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum MigrateError {
+    #[error("Cannot migrate from version {0} to {1}")]
+    Unsupported(NgffVersion, NgffVersion),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error(transparent)]
+    Load(#[from] LoadError),
+    #[error("Could not read metadata: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid metadata at {0}: {1}")]
+    Invalid(String, InvalidNgffMetadata),
+    #[error("Cannot convert array {0} to zarr v3: {1}")]
+    UnsupportedArray(String, String),
+    #[error(transparent)]
+    Write(#[from] WriteError),
+}
+
+/// The changes made to one group's attributes by [migrate_store].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub group: String,
+    pub changes: Vec<Change>,
+}
+
+/// Upgrade the metadata of every group in the hierarchy at `group`
+/// (images, their labels, plates, and wells), reporting what changed.
+///
+/// Only v0.4 to v0.5 is supported.
+/// Each group's attributes are validated as v0.4, moved under `ome` with the version,
+/// and merged into the group's `zarr.json`, alongside any existing zarr v3 metadata.
+/// The zarr v2 metadata of the multiscales' arrays, and of any groups between them and their image
+/// (or between a plate and its wells), is converted to zarr v3,
+/// keeping the v2 chunk key encoding so that the chunks themselves need not move.
+/// The zarr v2 metadata keys are then removed.
+/// Nothing is written unless every group and array can be migrated.
+///
+/// To preview the migration without writing anything, pass a [DryRun](crate::v0_4::DryRun) of the store.
+pub fn migrate_store<S: ReadableStore + WritableStore + ?Sized>(
+    store: &S,
+    group: &str,
+    from: NgffVersion,
+    to: NgffVersion,
+) -> Result<Vec<Migration>, MigrateError> {
+    migrate_store_with_progress(store, group, from, to, |_| ())
+}
+
+/// As [migrate_store], calling `progress` after each group is migrated
+/// (before anything is written).
+pub fn migrate_store_with_progress<S, F>(
+    store: &S,
+    group: &str,
+    from: NgffVersion,
+    to: NgffVersion,
+    mut progress: F,
+) -> Result<Vec<Migration>, MigrateError>
+where
+    S: ReadableStore + WritableStore + ?Sized,
+    F: FnMut(Progress),
+{
+    if (from, to) != (NgffVersion::V0_4, NgffVersion::V0_5) {
+        return Err(MigrateError::Unsupported(from, to));
+    }
+    let tx = Transaction::new(store);
+    let mut migrations = Vec::default();
+    let mut queue = VecDeque::from([join_key([group])]);
+    while let Some(group) = queue.pop_front() {
+        let key = join_key([group.as_str(), ATTRS_KEY]);
+        let bytes = store.get(&key)?.ok_or(LoadError::NotFound(key))?;
+        let meta = NgffMetadata::from_slice(&bytes).map_err(LoadError::from)?;
+        meta.validate()
+            .map_err(|e| MigrateError::Invalid(group.clone(), e))?;
+
+        let child = |path: &str| join_key([group.as_str(), path]);
+        for ms in meta.multiscales().into_iter().flatten() {
+            let names: Vec<_> = ms.axes().iter().map(|a| a.name()).collect();
+            for ds in ms.datasets() {
+                upgrade_intermediate_groups(&tx, &group, ds.path())?;
+                upgrade_array(&tx, &child(ds.path()), &names)?;
+            }
+        }
+        for pw in meta.plate().into_iter().flat_map(|p| p.wells()) {
+            upgrade_intermediate_groups(&tx, &group, pw.path())?;
+        }
+        if meta.multiscales().is_some() {
+            let labels = child("labels");
+            if store.contains(&join_key([labels.as_str(), ATTRS_KEY]))? {
+                queue.push_back(labels);
+            }
+        }
+        queue.extend(meta.labels().into_iter().flatten().map(|l| child(l)));
+        queue.extend(
+            meta.plate()
+                .into_iter()
+                .flat_map(|p| p.wells())
+                .map(|w| child(w.path())),
+        );
+        queue.extend(
+            meta.well()
+                .into_iter()
+                .flat_map(|w| w.images())
+                .map(|fov| child(fov.path())),
+        );
+
+        let attrs: Value = serde_json::from_slice(&bytes)?;
+        let upgraded = upgrade_attributes(attrs.clone());
+        let changes = diff(&attrs, &upgraded)?;
+
+        let node_key = join_key([group.as_str(), ZARR_JSON_KEY]);
+        let mut node: Value = match store.get(&node_key)? {
+            Some(b) => serde_json::from_slice(&b)?,
+            None => json!({"zarr_format": 3, "node_type": "group"}),
+        };
+        let mut merged = node
+            .get_mut("attributes")
+            .map(Value::take)
+            .and_then(|a| match a {
+                Value::Object(m) => Some(m),
+                _ => None,
+            })
+            .unwrap_or_default();
+        if let Value::Object(m) = upgraded {
+            merged.extend(m);
+        }
+        node["attributes"] = Value::Object(merged);
+        tx.set(&node_key, &serde_json::to_vec_pretty(&node)?)?;
+        tx.delete(&join_key([group.as_str(), ATTRS_KEY]))?;
+        tx.delete(&join_key([group.as_str(), GROUP_KEY]))?;
+
+        progress(Progress {
+            group: &group,
+            done: migrations.len() + 1,
+            total: migrations.len() + 1 + queue.len(),
+        });
+        migrations.push(Migration { group, changes });
+    }
+    // each group's metadata was validated as it was read,
+    // and the v0.5 documents cannot be validated as such
+    tx.apply()?;
+    Ok(migrations)
+}
+
+/// Replace the zarr v2 metadata of the groups between `group` and its descendant `path`
+/// (exclusive) with zarr v3 metadata.
+fn upgrade_intermediate_groups<S>(
+    tx: &Transaction<S>,
+    group: &str,
+    path: &str,
+) -> Result<(), MigrateError>
+where
+    S: ReadableStore + WritableStore + ?Sized,
+{
+    let path = join_key([path]);
+    let Some((parents, _)) = path.rsplit_once('/') else {
+        return Ok(());
+    };
+    let mut node = join_key([group]);
+    for seg in parents.split('/') {
+        node = join_key([node.as_str(), seg]);
+        let group_key = join_key([node.as_str(), GROUP_KEY]);
+        if !tx.contains(&group_key)? {
+            continue;
+        }
+        let attrs_key = join_key([node.as_str(), ATTRS_KEY]);
+        let attrs = match tx.get(&attrs_key)? {
+            Some(b) => serde_json::from_slice(&b)?,
+            None => json!({}),
+        };
+        let meta = json!({"zarr_format": 3, "node_type": "group", "attributes": attrs});
+        tx.set(
+            &join_key([node.as_str(), ZARR_JSON_KEY]),
+            &serde_json::to_vec_pretty(&meta)?,
+        )?;
+        tx.delete(&group_key)?;
+        tx.delete(&attrs_key)?;
+    }
+    Ok(())
+}
+
+/// Replace the zarr v2 metadata of the array at `array`, if any, with zarr v3 metadata,
+/// naming its dimensions after the axes.
+fn upgrade_array<S>(tx: &Transaction<S>, array: &str, names: &[&str]) -> Result<(), MigrateError>
+where
+    S: ReadableStore + WritableStore + ?Sized,
+{
+    let array_key = join_key([array, ARRAY_KEY]);
+    let Some(bytes) = tx.get(&array_key)? else {
+        return Ok(());
+    };
+    let zarray: Value = serde_json::from_slice(&bytes)?;
+    let attrs_key = join_key([array, ATTRS_KEY]);
+    let attrs = match tx.get(&attrs_key)? {
+        Some(b) => serde_json::from_slice(&b)?,
+        None => json!({}),
+    };
+    let mut meta = zarray_to_v3(&zarray)
+        .map_err(|reason| MigrateError::UnsupportedArray(array.to_owned(), reason))?;
+    meta["attributes"] = attrs;
+    meta["dimension_names"] = json!(names);
+    tx.set(
+        &join_key([array, ZARR_JSON_KEY]),
+        &serde_json::to_vec_pretty(&meta)?,
+    )?;
+    tx.delete(&array_key)?;
+    tx.delete(&attrs_key)?;
+    Ok(())
+}
+
+/// Convert zarr v2 array metadata to zarr v3, with codecs that read the existing chunks.
+///
+/// Arrays with filters, or compressors without a zarr v3 equivalent, are not supported.
+fn zarray_to_v3(zarray: &Value) -> Result<Value, String> {
+    let field = |key: &str| zarray.get(key).filter(|v| !v.is_null());
+    let dtype = field("dtype")
+        .and_then(Value::as_str)
+        .ok_or("missing dtype")?;
+    let (endian, (data_type, size)) = match dtype.split_at_checked(1) {
+        Some((e @ ("<" | ">" | "|"), t)) => (
+            e,
+            match t {
+                "b1" => ("bool", 1),
+                "i1" => ("int8", 1),
+                "i2" => ("int16", 2),
+                "i4" => ("int32", 4),
+                "i8" => ("int64", 8),
+                "u1" => ("uint8", 1),
+                "u2" => ("uint16", 2),
+                "u4" => ("uint32", 4),
+                "u8" => ("uint64", 8),
+                "f2" => ("float16", 2),
+                "f4" => ("float32", 4),
+                "f8" => ("float64", 8),
+                "c8" => ("complex64", 8),
+                "c16" => ("complex128", 16),
+                _ => return Err(format!("unsupported dtype {dtype:?}")),
+            },
+        ),
+        _ => return Err(format!("unsupported dtype {dtype:?}")),
+    };
+    if field("filters")
+        .and_then(Value::as_array)
+        .is_some_and(|f| !f.is_empty())
+    {
+        return Err("filters are not supported".to_owned());
+    }
+    let shape = field("shape").ok_or("missing shape")?;
+    let ndim = shape.as_array().map_or(0, Vec::len);
+
+    let mut codecs = Vec::default();
+    if field("order").and_then(Value::as_str) == Some("F") {
+        let order: Vec<_> = (0..ndim).rev().collect();
+        codecs.push(json!({"name": "transpose", "configuration": {"order": order}}));
+    }
+    codecs.push(match endian {
+        "<" => json!({"name": "bytes", "configuration": {"endian": "little"}}),
+        ">" => json!({"name": "bytes", "configuration": {"endian": "big"}}),
+        _ => json!({"name": "bytes"}),
+    });
+    if let Some(compressor) = field("compressor") {
+        codecs.push(compressor_to_v3(compressor, size)?);
+    }
+
+    let fill_value = match field("fill_value") {
+        Some(v) => v.clone(),
+        None if data_type == "bool" => json!(false),
+        None => json!(0),
+    };
+    let separator = field("dimension_separator")
+        .and_then(Value::as_str)
+        .unwrap_or(".");
+    Ok(json!({
+        "zarr_format": 3,
+        "node_type": "array",
+        "shape": shape,
+        "data_type": data_type,
+        "chunk_grid": {
+            "name": "regular",
+            "configuration": {"chunk_shape": field("chunks").ok_or("missing chunks")?},
+        },
+        "chunk_key_encoding": {"name": "v2", "configuration": {"separator": separator}},
+        "fill_value": fill_value,
+        "codecs": codecs,
+    }))
+}
+
+/// The zarr v3 codec equivalent to a zarr v2 (numcodecs) compressor,
+/// for a data type of `size` bytes.
+fn compressor_to_v3(compressor: &Value, size: usize) -> Result<Value, String> {
+    let id = compressor
+        .get("id")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let get = |key: &str| compressor.get(key).cloned().unwrap_or(Value::Null);
+    match id {
+        "gzip" => Ok(json!({"name": "gzip", "configuration": {"level": get("level")}})),
+        "zstd" => Ok(json!({
+            "name": "zstd",
+            "configuration": {"level": get("level"), "checksum": get("checksum").as_bool().unwrap_or(false)},
+        })),
+        "blosc" => {
+            // numcodecs' -1 is "auto": bit-shuffle for single bytes, otherwise byte-shuffle
+            let shuffle = match get("shuffle").as_i64() {
+                Some(0) => "noshuffle",
+                Some(2) => "bitshuffle",
+                Some(-1) if size == 1 => "bitshuffle",
+                _ => "shuffle",
+            };
+            Ok(json!({
+                "name": "blosc",
+                "configuration": {
+                    "cname": get("cname"),
+                    "clevel": get("clevel"),
+                    "shuffle": shuffle,
+                    "typesize": size,
+                    "blocksize": get("blocksize").as_u64().unwrap_or(0),
+                },
+            }))
+        }
+        _ => Err(format!("unsupported compressor {compressor}")),
+    }
+}
+
+/// Move the spec's keys under `ome`, declaring the version there instead of on each object.
+fn upgrade_attributes(attrs: Value) -> Value {
+    let Value::Object(mut attrs) = attrs else {
+        return attrs;
+    };
+    let mut ome = Map::default();
+    ome.insert("version".to_owned(), NgffVersion::V0_5.as_str().into());
+    for key in NGFF_KEYS {
+        let Some(mut value) = attrs.remove(*key) else {
+            continue;
+        };
+        match &mut value {
+            Value::Array(items) if *key == "multiscales" => {
+                for item in items.iter_mut().filter_map(Value::as_object_mut) {
+                    item.remove("version");
+                }
+            }
+            Value::Object(obj) if matches!(*key, "image-label" | "plate" | "well") => {
+                obj.remove("version");
+            }
+            _ => (),
+        }
+        ome.insert((*key).to_owned(), value);
+    }
+    attrs.insert("ome".to_owned(), Value::Object(ome));
+    Value::Object(attrs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+
+    #[test]
+    fn image_with_labels() {
+        let store = MemoryStore::new();
+        store
+            .set(
+                "img/.zattrs",
+                br#"{
+                    "multiscales": [{
+                        "version": "0.4",
+                        "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                        "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}]
+                    }],
+                    "omero": {"channels": []},
+                    "custom": true
+                }"#,
+            )
+            .unwrap();
+        store
+            .set("img/labels/.zattrs", br#"{"labels": ["cells"]}"#)
+            .unwrap();
+        store
+            .set(
+                "img/labels/cells/.zattrs",
+                br#"{
+                    "multiscales": [{
+                        "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                        "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}]
+                    }],
+                    "image-label": {"version": "0.4", "colors": []}
+                }"#,
+            )
+            .unwrap();
+        store
+            .set(
+                "img/labels/cells/zarr.json",
+                br#"{"zarr_format": 3, "node_type": "group", "attributes": {"existing": 1}}"#,
+            )
+            .unwrap();
+        for group in ["img", "img/labels"] {
+            store
+                .set(&format!("{group}/.zgroup"), br#"{"zarr_format": 2}"#)
+                .unwrap();
+        }
+        store
+            .set(
+                "img/0/.zarray",
+                br#"{
+                    "zarr_format": 2, "shape": [64, 32], "chunks": [32, 32], "dtype": "<u2",
+                    "compressor": {"id": "blosc", "cname": "lz4", "clevel": 5, "shuffle": 1, "blocksize": 0},
+                    "fill_value": 0, "order": "F", "filters": null, "dimension_separator": "/"
+                }"#,
+            )
+            .unwrap();
+        store
+            .set(
+                "img/labels/cells/0/.zarray",
+                br#"{
+                    "zarr_format": 2, "shape": [64, 32], "chunks": [32, 32], "dtype": "|u1",
+                    "compressor": null, "fill_value": null, "order": "C", "filters": null
+                }"#,
+            )
+            .unwrap();
+        store
+            .set("img/labels/cells/0/.zattrs", br#"{"note": "kept"}"#)
+            .unwrap();
+
+        assert!(matches!(
+            migrate_store(&store, "img", NgffVersion::V0_5, NgffVersion::V0_4),
+            Err(MigrateError::Unsupported(..))
+        ));
+
+        let mut reports = Vec::default();
+        let migrations =
+            migrate_store_with_progress(&store, "img", NgffVersion::V0_4, NgffVersion::V0_5, |p| {
+                reports.push((p.group.to_owned(), p.done, p.total))
+            })
+            .unwrap();
+        let groups: Vec<_> = migrations.iter().map(|m| m.group.as_str()).collect();
+        assert_eq!(groups, ["img", "img/labels", "img/labels/cells"]);
+        assert_eq!(
+            reports,
+            [
+                ("img".to_owned(), 1, 2),
+                ("img/labels".to_owned(), 2, 3),
+                ("img/labels/cells".to_owned(), 3, 3)
+            ]
+        );
+        assert!(migrations.iter().all(|m| !m.changes.is_empty()));
+
+        let node: Value =
+            serde_json::from_slice(&store.get("img/zarr.json").unwrap().unwrap()).unwrap();
+        let attrs = &node["attributes"];
+        assert_eq!(attrs["custom"], true);
+        assert_eq!(attrs["ome"]["version"], "0.5");
+        assert_eq!(attrs["ome"]["omero"], json!({"channels": []}));
+        assert!(attrs["ome"]["multiscales"][0].get("version").is_none());
+
+        let node: Value =
+            serde_json::from_slice(&store.get("img/labels/cells/zarr.json").unwrap().unwrap())
+                .unwrap();
+        assert_eq!(node["attributes"]["existing"], 1);
+        assert!(node["attributes"]["ome"]["image-label"]
+            .get("version")
+            .is_none());
+
+        let array: Value =
+            serde_json::from_slice(&store.get("img/0/zarr.json").unwrap().unwrap()).unwrap();
+        assert_eq!(array["node_type"], "array");
+        assert_eq!(array["data_type"], "uint16");
+        assert_eq!(array["dimension_names"], json!(["y", "x"]));
+        assert_eq!(
+            array["chunk_key_encoding"]["configuration"]["separator"],
+            "/"
+        );
+        assert_eq!(array["codecs"][0]["configuration"]["order"], json!([1, 0]));
+        assert_eq!(array["codecs"][2]["configuration"]["shuffle"], "shuffle");
+        assert_eq!(array["codecs"][2]["configuration"]["typesize"], 2);
+
+        let array: Value =
+            serde_json::from_slice(&store.get("img/labels/cells/0/zarr.json").unwrap().unwrap())
+                .unwrap();
+        assert_eq!(array["data_type"], "uint8");
+        assert_eq!(array["fill_value"], 0);
+        assert_eq!(array["codecs"], json!([{"name": "bytes"}]));
+        assert_eq!(array["attributes"]["note"], "kept");
+
+        assert!(v2_keys(&store).is_empty());
+    }
+
+    fn v2_keys(store: &MemoryStore) -> Vec<String> {
+        store
+            .keys()
+            .into_iter()
+            .filter(|k| {
+                [ATTRS_KEY, GROUP_KEY, ARRAY_KEY]
+                    .iter()
+                    .any(|s| k.ends_with(s))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plate() {
+        let store = MemoryStore::new();
+        store
+            .set(
+                "p/.zattrs",
+                br#"{"plate": {
+                    "version": "0.4",
+                    "columns": [{"name": "1"}],
+                    "rows": [{"name": "A"}],
+                    "wells": [{"path": "A/1", "rowIndex": 0, "columnIndex": 0}]
+                }}"#,
+            )
+            .unwrap();
+        store
+            .set("p/A/1/.zattrs", br#"{"well": {"images": []}}"#)
+            .unwrap();
+        for group in ["p", "p/A", "p/A/1"] {
+            store
+                .set(&format!("{group}/.zgroup"), br#"{"zarr_format": 2}"#)
+                .unwrap();
+        }
+
+        migrate_store(&store, "p", NgffVersion::V0_4, NgffVersion::V0_5).unwrap();
+        assert!(v2_keys(&store).is_empty());
+        let row: Value =
+            serde_json::from_slice(&store.get("p/A/zarr.json").unwrap().unwrap()).unwrap();
+        assert_eq!(row["node_type"], "group");
+    }
+
+    #[test]
+    fn unsupported_array() {
+        let store = MemoryStore::new();
+        store
+            .set(
+                "img/.zattrs",
+                br#"{"multiscales": [{
+                    "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                    "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}]
+                }]}"#,
+            )
+            .unwrap();
+        store
+            .set(
+                "img/0/.zarray",
+                br#"{
+                    "zarr_format": 2, "shape": [1, 1], "chunks": [1, 1], "dtype": "<f4",
+                    "compressor": null, "fill_value": 0, "order": "C",
+                    "filters": [{"id": "delta", "dtype": "<f4"}]
+                }"#,
+            )
+            .unwrap();
+        assert!(matches!(
+            migrate_store(&store, "img", NgffVersion::V0_4, NgffVersion::V0_5),
+            Err(MigrateError::UnsupportedArray(a, _)) if a == "img/0"
+        ));
+        assert_eq!(store.keys(), ["img/.zattrs", "img/0/.zarray"]);
+    }
+}
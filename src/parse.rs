@@ -0,0 +1,61 @@
+use thiserror::Error;
+
+use crate::util::JsonError;
+use crate::version::{detect_version, NgffVersion};
+
+/// Metadata parsed with whichever supported spec version it declares.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum AnyNgffMetadata {
+    #[cfg(feature = "v0_4")]
+    V0_4(crate::v0_4::NgffMetadata),
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum ParseError {
+    #[error("No supported version found")]
+    NoVersion,
+    #[error("Unsupported version {0}")]
+    UnsupportedVersion(NgffVersion),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Json(#[from] JsonError),
+}
+
+/// Parse a group's attributes as whichever version they declare.
+///
+/// Documents with no recognised version (e.g. a bare `labels` list)
+/// are parsed as the oldest enabled version.
+/// Never panics, so is suitable for untrusted input.
+pub fn parse_any(bytes: &[u8]) -> Result<AnyNgffMetadata, ParseError> {
+    let version = detect_version(bytes);
+    #[cfg(feature = "v0_4")]
+    if matches!(version, Some(NgffVersion::V0_4) | None) {
+        let meta = crate::v0_4::NgffMetadata::from_slice(bytes)?;
+        return Ok(AnyNgffMetadata::V0_4(meta));
+    }
+    match version {
+        Some(v) => Err(ParseError::UnsupportedVersion(v)),
+        None => Err(ParseError::NoVersion),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dispatch() {
+        #[cfg(feature = "v0_4")]
+        assert!(matches!(
+            parse_any(br#"{"well": {"version": "0.4", "images": []}}"#),
+            Ok(AnyNgffMetadata::V0_4(_))
+        ));
+        assert!(matches!(
+            parse_any(br#"{"ome": {"version": "0.5"}}"#),
+            Err(ParseError::UnsupportedVersion(NgffVersion::V0_5))
+        ));
+        assert!(parse_any(b"\xff\x00").is_err());
+    }
+}
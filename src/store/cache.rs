@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use super::{key_path, ReadableStore, Revalidated, StoreError, Versioned};
+
+/// Caches reads from another store, e.g. so that repeated traversals of a remote plate
+/// don't refetch every small metadata document.
+///
+/// Values (and missing keys) are cached in memory and trusted for the lifetime of the cache.
+/// With [CachedStore::with_disk_cache], values which have a version tag are also persisted,
+/// and revalidated against the inner store (e.g. with `If-None-Match`) on first use.
+#[derive(Debug)]
+pub struct CachedStore<S> {
+    inner: S,
+    memory: RwLock<HashMap<String, Option<Vec<u8>>>>,
+    disk: Option<PathBuf>,
+}
+
+impl<S: ReadableStore> CachedStore<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            memory: RwLock::default(),
+            disk: None,
+        }
+    }
+
+    /// Also persist versioned values in the given directory.
+    pub fn with_disk_cache<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.disk = Some(dir.into());
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Forget everything cached in memory.
+    pub fn clear(&self) {
+        self.memory.write().unwrap().clear();
+    }
+
+    /// The paths of the value and version tag for a key, within the cache directory.
+    fn disk_paths(dir: &Path, key: &str) -> Result<(PathBuf, PathBuf), StoreError> {
+        Ok((
+            key_path(&dir.join("data"), key)?,
+            key_path(&dir.join("versions"), key)?,
+        ))
+    }
+
+    fn read_disk((data, version): &(PathBuf, PathBuf)) -> Option<Versioned> {
+        let version = std::fs::read_to_string(version).ok()?;
+        let value = std::fs::read(data).ok()?;
+        Some(Versioned {
+            value,
+            version: Some(version),
+        })
+    }
+
+    fn write_disk(
+        (data, version_path): &(PathBuf, PathBuf),
+        value: &[u8],
+        version: &str,
+    ) -> Result<(), StoreError> {
+        for p in [data, version_path] {
+            if let Some(parent) = p.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        std::fs::write(data, value)?;
+        std::fs::write(version_path, version)?;
+        Ok(())
+    }
+
+    fn remove_disk((data, version): &(PathBuf, PathBuf)) {
+        let _ = std::fs::remove_file(data);
+        let _ = std::fs::remove_file(version);
+    }
+
+    fn fetch(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        let Some(dir) = self.disk.as_deref() else {
+            return self.inner.get(key);
+        };
+        let paths = Self::disk_paths(dir, key)?;
+        let fetched = match Self::read_disk(&paths) {
+            Some(cached) => {
+                let version = cached.version.as_deref().unwrap_or_default();
+                match self.inner.get_if_changed(key, version)? {
+                    Revalidated::Unchanged => return Ok(Some(cached.value)),
+                    Revalidated::Changed(v) => v,
+                }
+            }
+            None => self.inner.get_versioned(key)?,
+        };
+        match &fetched {
+            Some(Versioned {
+                value,
+                version: Some(version),
+            }) => Self::write_disk(&paths, value, version)?,
+            _ => Self::remove_disk(&paths),
+        }
+        Ok(fetched.map(|v| v.value))
+    }
+}
+
+impl<S: ReadableStore> ReadableStore for CachedStore<S> {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        if let Some(cached) = self.memory.read().unwrap().get(key) {
+            return Ok(cached.clone());
+        }
+        let value = self.fetch(key)?;
+        self.memory
+            .write()
+            .unwrap()
+            .insert(key.to_owned(), value.clone());
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts reads, and versions values by their length.
+    #[derive(Default)]
+    struct Counting {
+        values: HashMap<String, Vec<u8>>,
+        reads: AtomicUsize,
+    }
+
+    impl ReadableStore for Counting {
+        fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            Ok(self.values.get(key).cloned())
+        }
+
+        fn get_versioned(&self, key: &str) -> Result<Option<Versioned>, StoreError> {
+            Ok(self.get(key)?.map(|value| Versioned {
+                version: Some(value.len().to_string()),
+                value,
+            }))
+        }
+
+        fn get_if_changed(&self, key: &str, version: &str) -> Result<Revalidated, StoreError> {
+            match self.values.get(key) {
+                Some(v) if v.len().to_string() == version => Ok(Revalidated::Unchanged),
+                _ => self.get_versioned(key).map(Revalidated::Changed),
+            }
+        }
+    }
+
+    #[test]
+    fn memory_and_disk() {
+        let mut inner = Counting::default();
+        inner.values.insert("a/.zattrs".to_owned(), b"{}".to_vec());
+        let dir = std::env::temp_dir().join(format!("ome-ngff-rs-cache-{}", std::process::id()));
+
+        let cached = CachedStore::new(inner).with_disk_cache(&dir);
+        for _ in 0..3 {
+            assert_eq!(cached.get("a/.zattrs").unwrap().unwrap(), b"{}");
+            assert!(cached.get("b/.zattrs").unwrap().is_none());
+        }
+        assert_eq!(cached.inner().reads.load(Ordering::SeqCst), 2);
+
+        // a fresh cache revalidates from disk without re-reading the value
+        let cached = CachedStore::new(cached.inner).with_disk_cache(&dir);
+        cached.inner().reads.store(0, Ordering::SeqCst);
+        assert_eq!(cached.get("a/.zattrs").unwrap().unwrap(), b"{}");
+        assert_eq!(cached.inner().reads.load(Ordering::SeqCst), 0);
+
+        assert!(matches!(
+            cached.get("a/../../.zattrs"),
+            Err(StoreError::InvalidKey(_))
+        ));
+        assert_eq!(cached.inner().reads.load(Ordering::SeqCst), 0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::io::Read;
+
+#[cfg(feature = "async")]
+use super::AsyncReadableStore;
+use super::{ReadableStore, RetryPolicy, Revalidated, StoreError, Versioned};
+
+/// A read-only store served over HTTP(S), e.g. `https://example.com/image.zarr`.
+///
+/// Keys are fetched with plain `GET` requests; 403 and 404 responses are treated as missing keys,
+/// as object stores often return 403 for keys which do not exist.
+/// With the `async` feature, it can also be read through `AsyncReadableStore`,
+/// which makes each request on its own thread so as not to block the executor.
+/// Requests failing with a 429 or 5xx status or a connection error are retried
+/// according to the [RetryPolicy] (by default, [RetryPolicy::default]).
+#[derive(Debug, Clone)]
+pub struct HttpStore {
+    base: String,
+    agent: ureq::Agent,
+    retry: RetryPolicy,
+}
+
+impl HttpStore {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_agent(base_url, ureq::Agent::new())
+    }
+
+    /// Use a configured agent, e.g. with timeouts or a proxy.
+    pub fn with_agent(base_url: &str, agent: ureq::Agent) -> Self {
+        Self {
+            base: base_url.trim_end_matches('/').to_owned(),
+            agent,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.base, key.trim_start_matches('/'))
+    }
+}
+
+impl HttpStore {
+    /// `GET` a key, optionally conditional on its ETag not matching.
+    fn request(&self, key: &str, if_none_match: Option<&str>) -> Result<Revalidated, StoreError> {
+        let url = self.url(key);
+        let mut attempt = 0;
+        let result = loop {
+            let mut request = self.agent.get(&url);
+            if let Some(etag) = if_none_match {
+                request = request.set("If-None-Match", etag);
+            }
+            let result = request.call();
+            let delay = match &result {
+                Err(e) if is_transient(e) => self.retry.backoff(attempt),
+                _ => None,
+            };
+            let Some(delay) = delay else { break result };
+            std::thread::sleep(delay);
+            attempt += 1;
+        };
+        match result {
+            Ok(response) if response.status() == 304 => Ok(Revalidated::Unchanged),
+            Ok(response) => {
+                let version = response.header("ETag").map(str::to_owned);
+                let mut value = Vec::default();
+                response.into_reader().read_to_end(&mut value)?;
+                Ok(Revalidated::Changed(Some(Versioned { value, version })))
+            }
+            Err(ureq::Error::Status(403 | 404, _)) => Ok(Revalidated::Changed(None)),
+            Err(e) => Err(StoreError::Other(format!("GET {url}: {e}"))),
+        }
+    }
+}
+
+/// Whether a request might succeed if repeated.
+fn is_transient(error: &ureq::Error) -> bool {
+    use ureq::ErrorKind;
+    match error {
+        ureq::Error::Status(status, _) => *status == 429 || (500..600).contains(status),
+        ureq::Error::Transport(t) => matches!(
+            t.kind(),
+            ErrorKind::Dns | ErrorKind::ConnectionFailed | ErrorKind::Io
+        ),
+    }
+}
+
+impl ReadableStore for HttpStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.get_versioned(key)?.map(|v| v.value))
+    }
+
+    fn get_versioned(&self, key: &str) -> Result<Option<Versioned>, StoreError> {
+        match self.request(key, None)? {
+            Revalidated::Changed(v) => Ok(v),
+            Revalidated::Unchanged => unreachable!("unconditional request"),
+        }
+    }
+
+    fn get_if_changed(&self, key: &str, version: &str) -> Result<Revalidated, StoreError> {
+        self.request(key, Some(version))
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncReadableStore for HttpStore {
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<Vec<u8>>, StoreError>> + Send {
+        let store = self.clone();
+        let key = key.to_owned();
+        on_thread(move || ReadableStore::get(&store, &key))
+    }
+}
+
+/// Run a blocking function on a new thread when first polled, resolving to its output.
+#[cfg(feature = "async")]
+fn on_thread<T, F>(f: F) -> impl Future<Output = T> + Send
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+    use std::sync::{Arc, Mutex};
+    use std::task::{Poll, Waker};
+
+    let shared = Arc::new(Mutex::new((None, None::<Waker>)));
+    let mut start = Some({
+        let shared = Arc::clone(&shared);
+        move || {
+            let output = catch_unwind(AssertUnwindSafe(f));
+            let mut state = shared.lock().unwrap();
+            state.0 = Some(output);
+            if let Some(waker) = state.1.take() {
+                waker.wake();
+            }
+        }
+    });
+    std::future::poll_fn(move |cx| {
+        if let Some(start) = start.take() {
+            std::thread::spawn(start);
+        }
+        let mut state = shared.lock().unwrap();
+        match state.0.take() {
+            Some(Ok(output)) => Poll::Ready(output),
+            Some(Err(panic)) => resume_unwind(panic),
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn get() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                BufReader::new(&stream)
+                    .read_line(&mut request_line)
+                    .unwrap();
+                let response = if request_line.starts_with("GET /image.zarr/.zattrs ") {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}"
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let store = HttpStore::new(&format!("http://{addr}/image.zarr/"));
+        assert_eq!(
+            ReadableStore::get(&store, ".zattrs").unwrap().unwrap(),
+            b"{}"
+        );
+        assert!(ReadableStore::get(&store, "0/.zarray").unwrap().is_none());
+        server.join().unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn get_async() {
+        use std::sync::mpsc;
+        use std::task::{Context, Poll, Wake, Waker};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let mut stream = listener.incoming().next().unwrap().unwrap();
+            let mut request_line = String::new();
+            BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .unwrap();
+            assert!(request_line.starts_with("GET /image.zarr/.zattrs "));
+            let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}";
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        struct Signal(std::sync::Mutex<mpsc::Sender<()>>);
+        impl Wake for Signal {
+            fn wake(self: std::sync::Arc<Self>) {
+                self.0.lock().unwrap().send(()).unwrap();
+            }
+        }
+        let (tx, rx) = mpsc::channel();
+        let waker = Waker::from(std::sync::Arc::new(Signal(std::sync::Mutex::new(tx))));
+        let store = HttpStore::new(&format!("http://{addr}/image.zarr"));
+        let mut get = std::pin::pin!(AsyncReadableStore::get(&store, ".zattrs"));
+        let mut cx = Context::from_waker(&waker);
+        let value = loop {
+            match get.as_mut().poll(&mut cx) {
+                Poll::Ready(value) => break value,
+                Poll::Pending => rx.recv().unwrap(),
+            }
+        };
+        assert_eq!(value.unwrap().unwrap(), b"{}");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn retry() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            for (idx, stream) in listener.incoming().take(4).enumerate() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                BufReader::new(&stream)
+                    .read_line(&mut request_line)
+                    .unwrap();
+                // the first key succeeds on the second attempt; the second never does
+                let response = if idx == 1 {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}"
+                } else {
+                    "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let retry = RetryPolicy {
+            max_retries: 1,
+            initial_backoff: std::time::Duration::from_millis(1),
+            ..Default::default()
+        };
+        let store = HttpStore::new(&format!("http://{addr}/image.zarr")).with_retry(retry);
+        assert_eq!(
+            ReadableStore::get(&store, ".zattrs").unwrap().unwrap(),
+            b"{}"
+        );
+        assert!(ReadableStore::get(&store, "0/.zarray").is_err());
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,340 @@
+//! Minimal key-value store abstraction for reading and writing NGFF hierarchies.
+use std::collections::HashMap;
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+
+use thiserror::Error;
+
+use crate::util::JsonError;
+
+mod cache;
+pub use cache::CachedStore;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "http")]
+pub use http::HttpStore;
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+mod object;
+#[cfg(any(feature = "s3", feature = "gcs", feature = "azure"))]
+pub use object::CloudStore;
+#[cfg(any(feature = "http", feature = "s3", feature = "gcs", feature = "azure"))]
+mod retry;
+#[cfg(any(feature = "http", feature = "s3", feature = "gcs", feature = "azure"))]
+pub use retry::RetryPolicy;
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum StoreError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Store error: {0}")]
+    Other(String),
+    #[error("Key {0:?} refers outside the store")]
+    InvalidKey(String),
+}
+
+/// A store whose values can be read by key.
+///
+/// Keys are `/`-separated, relative to the store root, e.g. `"plate.zarr/A/1/.zattrs"`.
+pub trait ReadableStore {
+    /// Get the value at a key, or `None` if there is no such key.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError>;
+
+    /// Whether the store has a value at a key.
+    fn contains(&self, key: &str) -> Result<bool, StoreError> {
+        self.get(key).map(|v| v.is_some())
+    }
+
+    /// Get the value at a key along with an opaque version tag (e.g. an HTTP ETag),
+    /// if the backend provides one.
+    fn get_versioned(&self, key: &str) -> Result<Option<Versioned>, StoreError> {
+        Ok(self.get(key)?.map(|value| Versioned {
+            value,
+            version: None,
+        }))
+    }
+
+    /// Get the value at a key only if its version differs from `version`.
+    ///
+    /// Backends without conditional reads always return [Revalidated::Changed].
+    fn get_if_changed(&self, key: &str, version: &str) -> Result<Revalidated, StoreError> {
+        let _ = version;
+        self.get_versioned(key).map(Revalidated::Changed)
+    }
+}
+
+/// A value with the backend's version tag for it, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Versioned {
+    pub value: Vec<u8>,
+    pub version: Option<String>,
+}
+
+/// The result of a conditional read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Revalidated {
+    /// The value has the given version.
+    Unchanged,
+    /// The value has changed (or been removed).
+    Changed(Option<Versioned>),
+}
+
+/// Progress through a hierarchy, reported after each group is processed
+/// by store-backed operations such as [crate::v0_4::NgffMetadata::validate_group_with_progress].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress<'a> {
+    /// The group which was just processed.
+    pub group: &'a str,
+    /// The number of groups processed so far, including this one.
+    pub done: usize,
+    /// The number of groups known to need processing so far,
+    /// which may grow as more of the hierarchy is discovered.
+    pub total: usize,
+}
+
+/// A store whose values can be read by key asynchronously.
+#[cfg(feature = "async")]
+pub trait AsyncReadableStore: Sync {
+    /// Get the value at a key, or `None` if there is no such key.
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<Vec<u8>>, StoreError>> + Send;
+
+    /// Whether the store has a value at a key.
+    fn contains(&self, key: &str) -> impl Future<Output = Result<bool, StoreError>> + Send {
+        async move { self.get(key).await.map(|v| v.is_some()) }
+    }
+}
+
+/// A store whose values can be written by key.
+pub trait WritableStore {
+    fn set(&self, key: &str, value: &[u8]) -> Result<(), StoreError>;
+
+    /// Remove the value at a key, if present.
+    ///
+    /// Stores which cannot remove values return an error.
+    fn delete(&self, key: &str) -> Result<(), StoreError> {
+        Err(StoreError::Other(format!(
+            "Cannot delete {key:?} from this store"
+        )))
+    }
+}
+
+/// Join store key components into a normalized key.
+///
+/// Empty and `.` segments are dropped, and `..` removes the preceding segment.
+pub fn join_key<'a, I: IntoIterator<Item = &'a str>>(parts: I) -> String {
+    let mut segments: Vec<&str> = Vec::default();
+    for seg in parts.into_iter().flat_map(|p| p.split('/')) {
+        match seg {
+            "" | "." => (),
+            ".." => {
+                segments.pop();
+            }
+            s => segments.push(s),
+        }
+    }
+    segments.join("/")
+}
+
+/// Open a read-only store from a URL or local path.
+///
+/// `http://` and `https://` URLs require the `http` feature;
+/// anything else is treated as a filesystem path, with an optional `file://` prefix.
+pub fn open(location: &str) -> Result<Box<dyn ReadableStore + Send + Sync>, StoreError> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        #[cfg(feature = "http")]
+        return Ok(Box::new(HttpStore::new(location)));
+        #[cfg(not(feature = "http"))]
+        return Err(StoreError::Other(format!(
+            "HTTP stores require the `http` feature: {location}"
+        )));
+    }
+    let path = location.strip_prefix("file://").unwrap_or(location);
+    Ok(Box::new(FilesystemStore::new(path)))
+}
+
+/// A store on the local filesystem.
+#[derive(Debug, Clone)]
+pub struct FilesystemStore {
+    root: PathBuf,
+}
+
+impl FilesystemStore {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StoreError> {
+        key_path(&self.root, key)
+    }
+}
+
+/// The path of `key` within the directory `root`,
+/// failing if a segment of the key (e.g. `..`, or an absolute path) would leave it.
+pub(crate) fn key_path(root: &Path, key: &str) -> Result<PathBuf, StoreError> {
+    let mut path = root.to_path_buf();
+    for seg in key.split('/').filter(|s| !s.is_empty()) {
+        let mut components = Path::new(seg).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(c)), None) => path.push(c),
+            (Some(Component::CurDir), None) => (),
+            _ => return Err(StoreError::InvalidKey(key.to_owned())),
+        }
+    }
+    Ok(path)
+}
+
+impl ReadableStore for FilesystemStore {
+    fn contains(&self, key: &str) -> Result<bool, StoreError> {
+        Ok(self.path(key)?.is_file())
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        match std::fs::read(self.path(key)?) {
+            Ok(v) => Ok(Some(v)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncReadableStore for FilesystemStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        ReadableStore::get(self, key)
+    }
+}
+
+impl WritableStore for FilesystemStore {
+    fn set(&self, key: &str, value: &[u8]) -> Result<(), StoreError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, value)?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), StoreError> {
+        match std::fs::remove_file(self.path(key)?) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An in-memory store, mainly for testing.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    values: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All keys currently in the store, sorted.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<_> = self.values.read().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+impl ReadableStore for MemoryStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.values.read().unwrap().get(key).cloned())
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncReadableStore for MemoryStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        ReadableStore::get(self, key)
+    }
+}
+
+impl WritableStore for MemoryStore {
+    fn set(&self, key: &str, value: &[u8]) -> Result<(), StoreError> {
+        self.values
+            .write()
+            .unwrap()
+            .insert(key.to_owned(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), StoreError> {
+        self.values.write().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// Failure to load a metadata document from a store.
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum LoadError {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error(transparent)]
+    Json(#[from] JsonError),
+    #[error("Key not found: {0}")]
+    NotFound(String),
+    #[error("Expected {1} metadata at {0}")]
+    MissingMetadata(String, &'static str),
+}
+
+/// A metadata document refers to nodes which are not present in the store.
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum InvalidHierarchy {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error("No array at {0}")]
+    MissingArray(String),
+    #[error("No group at {0}")]
+    MissingGroup(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        assert_eq!(
+            join_key(["/plate.zarr/", "", "A/1", ".zattrs"]),
+            "plate.zarr/A/1/.zattrs"
+        );
+        assert_eq!(join_key([""]), "");
+        assert_eq!(join_key(["a/./b", "../c//d"]), "a/c/d");
+    }
+
+    #[test]
+    fn filesystem() {
+        let dir = std::env::temp_dir().join(format!("ome-ngff-rs-store-{}", std::process::id()));
+        let store = FilesystemStore::new(&dir);
+        assert!(ReadableStore::get(&store, "a/.zattrs").unwrap().is_none());
+        store.set("a/.zattrs", b"{}").unwrap();
+        assert_eq!(
+            ReadableStore::get(&store, "a/.zattrs").unwrap().unwrap(),
+            b"{}"
+        );
+        for key in ["../a/.zattrs", "a/../../b", "/a/..//..", "a/.."] {
+            assert!(matches!(
+                ReadableStore::get(&store, key),
+                Err(StoreError::InvalidKey(k)) if k == key
+            ));
+            assert!(matches!(
+                store.set(key, b"{}"),
+                Err(StoreError::InvalidKey(_))
+            ));
+        }
+        #[cfg(windows)]
+        assert!(store.set("C:/a", b"{}").is_err());
+        assert!(ReadableStore::contains(&store, "./a//.zattrs").unwrap());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
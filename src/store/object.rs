@@ -0,0 +1,185 @@
+use std::sync::Arc;
+#[cfg(feature = "blocking")]
+use std::sync::OnceLock;
+
+use object_store::path::Path;
+use object_store::{GetOptions, ObjectStore};
+
+#[cfg(feature = "blocking")]
+use super::ReadableStore;
+use super::{AsyncReadableStore, RetryPolicy, Revalidated, StoreError, Versioned};
+
+/// A read-only store backed by a cloud object store, under an optional key prefix.
+///
+/// With the `blocking` feature, it can also be read through `ReadableStore`,
+/// which runs the request on an internal single-threaded runtime
+/// and so must not be done from within an async context.
+///
+/// The object store clients already retry transient errors (see `object_store::RetryConfig`),
+/// so by default no further retries are made; a [RetryPolicy] can add retries of
+/// the errors which remain, e.g. for a flaky connection.
+#[derive(Debug)]
+pub struct CloudStore {
+    inner: Arc<dyn ObjectStore>,
+    prefix: String,
+    retry: RetryPolicy,
+    #[cfg(feature = "blocking")]
+    runtime: OnceLock<tokio::runtime::Runtime>,
+}
+
+impl CloudStore {
+    pub fn new(inner: Arc<dyn ObjectStore>, prefix: &str) -> Self {
+        Self {
+            inner,
+            prefix: super::join_key([prefix]),
+            retry: RetryPolicy::NONE,
+            #[cfg(feature = "blocking")]
+            runtime: OnceLock::new(),
+        }
+    }
+
+    /// An S3 bucket, with region, credentials, and endpoint taken from
+    /// the standard `AWS_*` environment variables.
+    #[cfg(feature = "s3")]
+    pub fn s3_from_env(bucket: &str, prefix: &str) -> Result<Self, StoreError> {
+        let s3 = object_store::aws::AmazonS3Builder::from_env()
+            .with_bucket_name(bucket)
+            .build()
+            .map_err(to_store_error)?;
+        Ok(Self::new(Arc::new(s3), prefix))
+    }
+
+    /// A Google Cloud Storage bucket, with credentials taken from
+    /// the standard `GOOGLE_*` environment variables.
+    #[cfg(feature = "gcs")]
+    pub fn gcs_from_env(bucket: &str, prefix: &str) -> Result<Self, StoreError> {
+        let gcs = object_store::gcp::GoogleCloudStorageBuilder::from_env()
+            .with_bucket_name(bucket)
+            .build()
+            .map_err(to_store_error)?;
+        Ok(Self::new(Arc::new(gcs), prefix))
+    }
+
+    /// An Azure Blob Storage container, with account and credentials taken from
+    /// the standard `AZURE_*` environment variables.
+    #[cfg(feature = "azure")]
+    pub fn azure_from_env(container: &str, prefix: &str) -> Result<Self, StoreError> {
+        let azure = object_store::azure::MicrosoftAzureBuilder::from_env()
+            .with_container_name(container)
+            .build()
+            .map_err(to_store_error)?;
+        Ok(Self::new(Arc::new(azure), prefix))
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    fn path(&self, key: &str) -> Path {
+        Path::from(super::join_key([self.prefix.as_str(), key]))
+    }
+
+    #[cfg(feature = "blocking")]
+    fn runtime(&self) -> Result<&tokio::runtime::Runtime, StoreError> {
+        if let Some(rt) = self.runtime.get() {
+            return Ok(rt);
+        }
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(self.runtime.get_or_init(|| rt))
+    }
+}
+
+fn to_store_error(e: object_store::Error) -> StoreError {
+    StoreError::Other(e.to_string())
+}
+
+impl CloudStore {
+    /// Get a key, optionally conditional on its ETag not matching.
+    async fn request(
+        &self,
+        key: &str,
+        if_none_match: Option<&str>,
+    ) -> Result<Revalidated, StoreError> {
+        let path = self.path(key);
+        let mut attempt = 0;
+        let result = loop {
+            let options = GetOptions {
+                if_none_match: if_none_match.map(str::to_owned),
+                ..Default::default()
+            };
+            match self.inner.get_opts(&path, options).await {
+                Ok(r) => break r,
+                Err(object_store::Error::NotFound { .. }) => return Ok(Revalidated::Changed(None)),
+                Err(object_store::Error::NotModified { .. }) => return Ok(Revalidated::Unchanged),
+                // other variants are not transient, e.g. permissions or invalid paths
+                Err(e @ object_store::Error::Generic { .. }) => match self.retry.backoff(attempt) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(to_store_error(e)),
+                },
+                Err(e) => return Err(to_store_error(e)),
+            }
+            attempt += 1;
+        };
+        let version = result.meta.e_tag.clone();
+        let bytes = result.bytes().await.map_err(to_store_error)?;
+        Ok(Revalidated::Changed(Some(Versioned {
+            value: bytes.to_vec(),
+            version,
+        })))
+    }
+}
+
+impl AsyncReadableStore for CloudStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        match self.request(key, None).await? {
+            Revalidated::Changed(v) => Ok(v.map(|v| v.value)),
+            Revalidated::Unchanged => unreachable!("unconditional request"),
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl ReadableStore for CloudStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        self.runtime()?.block_on(AsyncReadableStore::get(self, key))
+    }
+
+    fn get_versioned(&self, key: &str) -> Result<Option<Versioned>, StoreError> {
+        match self.runtime()?.block_on(self.request(key, None))? {
+            Revalidated::Changed(v) => Ok(v),
+            Revalidated::Unchanged => unreachable!("unconditional request"),
+        }
+    }
+
+    fn get_if_changed(&self, key: &str, version: &str) -> Result<Revalidated, StoreError> {
+        self.runtime()?.block_on(self.request(key, Some(version)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+    use object_store::ObjectStoreExt;
+
+    #[test]
+    fn get() {
+        let inner = InMemory::new();
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(inner.put(
+            &Path::from("bucket/image.zarr/.zattrs"),
+            b"{}".to_vec().into(),
+        ))
+        .unwrap();
+
+        let store = CloudStore::new(Arc::new(inner), "/bucket/image.zarr/");
+        let get = |key| rt.block_on(AsyncReadableStore::get(&store, key)).unwrap();
+        assert_eq!(get(".zattrs").unwrap(), b"{}");
+        assert!(get("0/.zarray").is_none());
+    }
+}
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+/// How often, and after how long, a remote store retries a read which failed transiently
+/// (e.g. with a 5xx status, a timeout, or a dropped connection).
+///
+/// The delay before the `n`th retry is `initial_backoff * multiplier^n`, capped at `max_backoff`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+}
+
+impl RetryPolicy {
+    /// Fail on the first error.
+    pub const NONE: Self = Self {
+        max_retries: 0,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+        multiplier: 1.0,
+    };
+
+    /// The delay before the retry after the given number of failed attempts (from 0),
+    /// or `None` if there should be no more retries.
+    ///
+    /// A negative or NaN multiplier is treated as 0, and delays which overflow
+    /// are capped at `max_backoff`.
+    pub fn backoff(&self, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        if self.initial_backoff.is_zero() {
+            return Some(Duration::ZERO);
+        }
+        // f64::max ignores NaN
+        let factor = self
+            .multiplier
+            .max(0.0)
+            .powi(attempt.try_into().unwrap_or(i32::MAX));
+        let secs = self.initial_backoff.as_secs_f64() * factor;
+        let delay = if secs.is_finite() {
+            Duration::try_from_secs_f64(secs).unwrap_or(self.max_backoff)
+        } else {
+            self.max_backoff
+        };
+        Some(delay.min(self.max_backoff))
+    }
+}
+
+/// 3 retries, after 100ms, 200ms, and 400ms.
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delays(policy: RetryPolicy) -> Vec<Duration> {
+        (0..).map_while(|n| policy.backoff(n)).collect()
+    }
+
+    #[test]
+    fn backoff() {
+        let ms = Duration::from_millis;
+        assert_eq!(delays(RetryPolicy::default()), [ms(100), ms(200), ms(400)]);
+        assert!(delays(RetryPolicy::NONE).is_empty());
+
+        let policy = RetryPolicy {
+            max_retries: 5,
+            initial_backoff: ms(1000),
+            max_backoff: ms(5000),
+            multiplier: 3.0,
+        };
+        assert_eq!(
+            delays(policy),
+            [ms(1000), ms(3000), ms(5000), ms(5000), ms(5000)]
+        );
+
+        for multiplier in [-2.0, f64::NAN] {
+            let policy = RetryPolicy {
+                multiplier,
+                ..policy
+            };
+            assert_eq!(delays(policy), [ms(1000), ms(0), ms(0), ms(0), ms(0)]);
+        }
+
+        let policy = RetryPolicy {
+            max_retries: u32::MAX,
+            multiplier: f64::MAX,
+            ..policy
+        };
+        assert_eq!(policy.backoff(1), Some(ms(5000)));
+        assert_eq!(policy.backoff(u32::MAX - 1), Some(ms(5000)));
+        let policy = RetryPolicy {
+            multiplier: f64::INFINITY,
+            ..policy
+        };
+        assert_eq!(policy.backoff(0), Some(ms(1000)));
+        assert_eq!(policy.backoff(1), Some(ms(5000)));
+    }
+}
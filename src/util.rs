@@ -1,7 +1,96 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
 use thiserror::Error;
 
 pub type ZPath = String;
 
+#[cfg(feature = "smol_str")]
+type NameInner = smol_str::SmolStr;
+#[cfg(not(feature = "smol_str"))]
+type NameInner = String;
+
+/// A short, frequently-repeated name, such as an axis or plate row/column name.
+///
+/// With the `smol_str` feature, short names are stored inline without allocating;
+/// the API is the same either way.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Name(NameInner);
+
+impl Name {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::borrow::Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Name {
+    fn from(s: &str) -> Self {
+        Self(s.into())
+    }
+}
+
+impl From<String> for Name {
+    fn from(s: String) -> Self {
+        Self(s.into())
+    }
+}
+
+impl From<&String> for Name {
+    fn from(s: &String) -> Self {
+        Self(s.as_str().into())
+    }
+}
+
+impl From<Name> for String {
+    fn from(n: Name) -> Self {
+        n.0.into()
+    }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Name {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
 /// variant_from_data!(EnumType, VariantName, DataType)
 ///
 /// adds `From<D>` for an enum with a variant containing D
@@ -20,6 +109,292 @@ macro_rules! variant_from_data {
 
 pub(crate) use variant_from_data;
 
+/// Whether generated instances must be spec-valid.
+#[cfg(feature = "proptest")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Validity {
+    /// Only generate instances which pass validation.
+    #[default]
+    Valid,
+    /// Generate instances with little regard for the spec, which may or may not be valid.
+    Any,
+}
+
+/// Apply a fallible check to every item, returning the error from the earliest failing item.
+///
+/// Runs in parallel with the `rayon` feature.
+#[cfg(feature = "v0_4")]
+pub(crate) fn try_for_each_item<T, E, F>(items: &[T], f: F) -> Result<(), E>
+where
+    T: Sync,
+    E: Send,
+    F: Fn(&T) -> Result<(), E> + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        match items.par_iter().find_map_first(|item| f(item).err()) {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.iter().try_for_each(f)
+    }
+}
+
+/// Levenshtein distance between two strings, in characters.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = Vec::with_capacity(b.len() + 1);
+        row.push(i + 1);
+        for (j, cb) in b.iter().enumerate() {
+            let sub = prev[j] + usize::from(ca != *cb);
+            row.push(sub.min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// Uniform validation of NGFF metadata objects.
+///
+/// Types may also have inherent `validate` methods taking extra context
+/// (e.g. [crate::v0_4::Well::validate]); this trait validates without any.
+pub trait Validate {
+    type Error: std::error::Error;
+
+    fn validate(&self) -> Result<(), Self::Error>;
+
+    fn is_valid(&self) -> bool {
+        Validate::validate(self).is_ok()
+    }
+
+    /// Pass through the object if it is valid.
+    fn validated(self) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        Validate::validate(&self)?;
+        Ok(self)
+    }
+
+    /// Wrap the object as proof that it is valid.
+    fn into_validated(self) -> Result<Validated<Self>, Self::Error>
+    where
+        Self: Sized,
+    {
+        Validated::new(self)
+    }
+}
+
+/// An object which has passed validation: [Validate::validate],
+/// or a validation entry point such as [crate::v0_4::NgffMetadata::validate_group]
+/// (by the rules of its config).
+///
+/// It can only be constructed by validating, and cannot be mutated afterwards,
+/// so functions such as [crate::v0_4::write_plate] can take it to require valid input.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Validated<T>(T);
+
+impl<T: Validate> Validated<T> {
+    pub fn new(inner: T) -> Result<Self, T::Error> {
+        inner.validate()?;
+        Ok(Self(inner))
+    }
+}
+
+impl<T> Validated<T> {
+    /// Wrap an object which the caller has just validated by other means.
+    #[cfg(feature = "v0_4")]
+    pub(crate) fn assume_valid(inner: T) -> Self {
+        Self(inner)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for Validated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsRef<T> for Validated<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Validate> Validated<T> {
+    /// Deserialize the unchecked `T`, then validate it,
+    /// keeping the deserializer's and the validation error's types apart.
+    pub fn deserialize_checked<'de, D>(
+        deserializer: D,
+    ) -> Result<Self, ValidatedError<D::Error, T::Error>>
+    where
+        T: serde::Deserialize<'de>,
+        D: serde::Deserializer<'de>,
+    {
+        let inner = T::deserialize(deserializer).map_err(ValidatedError::Deserialize)?;
+        Self::new(inner).map_err(ValidatedError::Invalid)
+    }
+
+    /// Read and validate a JSON document, as [Validated::deserialize_checked].
+    pub fn from_json_slice(
+        bytes: &[u8],
+    ) -> Result<Self, ValidatedError<serde_json::Error, T::Error>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let inner = serde_json::from_slice(bytes).map_err(ValidatedError::Deserialize)?;
+        Self::new(inner).map_err(ValidatedError::Invalid)
+    }
+}
+
+/// Failure to deserialize a [Validated] object with [Validated::deserialize_checked].
+#[derive(Debug, Error)]
+pub enum ValidatedError<D, V> {
+    /// The data could not be deserialized as the unchecked type.
+    #[error(transparent)]
+    Deserialize(D),
+    /// The data was deserialized, but is not valid.
+    #[error(transparent)]
+    Invalid(V),
+}
+
+/// Deserializes the unchecked `T`, then validates it, failing with the validation error's message
+/// so that `Validated<T>` can be used wherever a deserializable type is expected.
+///
+/// For a typed validation error, use [Validated::deserialize_checked].
+impl<'de, T: serde::Deserialize<'de> + Validate> serde::Deserialize<'de> for Validated<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::new(T::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+impl<T: Validate> Validate for Option<T> {
+    type Error = T::Error;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        self.as_ref().map_or(Ok(()), Validate::validate)
+    }
+}
+
+/// Validates each item, returning the first error.
+impl<T: Validate> Validate for [T] {
+    type Error = T::Error;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        self.iter().try_for_each(Validate::validate)
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    type Error = T::Error;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        self.as_slice().validate()
+    }
+}
+
+/// A validation error which can be traced back to the specification.
+pub trait SpecError {
+    /// A stable, machine-readable identifier, e.g. `ngff::axes::order`.
+    fn code(&self) -> &'static str;
+
+    /// A link to the relevant section of the specification, if there is one.
+    fn spec_url(&self) -> Option<String>;
+}
+
+/// impl_spec_diagnostic!(Type)
+///
+/// with the `miette` feature, implements `miette::Diagnostic` using the type's [SpecError] code and URL
+macro_rules! impl_spec_diagnostic {
+    ($t:ty) => {
+        #[cfg(feature = "miette")]
+        impl miette::Diagnostic for $t {
+            fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+                Some(Box::new($crate::util::SpecError::code(self)))
+            }
+
+            fn url<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+                $crate::util::SpecError::spec_url(self)
+                    .map(|u| Box::new(u) as Box<dyn std::fmt::Display>)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_spec_diagnostic;
+
+/// Failure to read or write a metadata object as JSON.
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum JsonError {
+    #[error("Could not read {0}: {1}")]
+    Read(&'static str, #[source] serde_json::Error),
+    #[error("Could not write {0}: {1}")]
+    Write(&'static str, #[source] serde_json::Error),
+}
+
+impl JsonError {
+    /// 1-based line and column of the error in the JSON input, if known (otherwise 0).
+    pub fn line_column(&self) -> (usize, usize) {
+        let (Self::Read(_, e) | Self::Write(_, e)) = self;
+        (e.line(), e.column())
+    }
+}
+
+/// impl_json_io!(Type)
+///
+/// adds `from_reader`, `from_slice`, `to_writer`, and `to_string_pretty` to a serde type,
+/// naming the type in any error.
+/// The writers use the type's `Serialize` implementation,
+/// which fills in any missing versions.
+#[cfg(feature = "v0_4")]
+macro_rules! impl_json_io {
+    ($t:ty) => {
+        impl $t {
+            pub fn from_reader<R: std::io::Read>(
+                reader: R,
+            ) -> Result<Self, $crate::util::JsonError> {
+                serde_json::from_reader(reader)
+                    .map_err(|e| $crate::util::JsonError::Read(stringify!($t), e))
+            }
+
+            pub fn from_slice(bytes: &[u8]) -> Result<Self, $crate::util::JsonError> {
+                serde_json::from_slice(bytes)
+                    .map_err(|e| $crate::util::JsonError::Read(stringify!($t), e))
+            }
+
+            pub fn to_writer<W: std::io::Write>(
+                &self,
+                writer: W,
+            ) -> Result<(), $crate::util::JsonError> {
+                serde_json::to_writer(writer, self)
+                    .map_err(|e| $crate::util::JsonError::Write(stringify!($t), e))
+            }
+
+            pub fn to_string_pretty(&self) -> Result<String, $crate::util::JsonError> {
+                serde_json::to_string_pretty(self)
+                    .map_err(|e| $crate::util::JsonError::Write(stringify!($t), e))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "v0_4")]
+pub(crate) use impl_json_io;
+
 // macro_rules! transitive_into {
 //     ($target:ty, $intermediate:ty) => {
 //         impl<T> std::convert::From<T> for $target where T: std::convert::Into<$intermediate> {
@@ -36,7 +411,25 @@ pub(crate) use variant_from_data;
 #[error("Inconsistent dimensionalities: {0}, {1}")]
 pub struct InconsistentDimensionality(usize, usize);
 
+impl SpecError for InconsistentDimensionality {
+    fn code(&self) -> &'static str {
+        "ngff::dimensionality"
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        None
+    }
+}
+
+impl_spec_diagnostic!(InconsistentDimensionality);
+
 impl InconsistentDimensionality {
+    /// An error for two dimensionalities which are known to differ.
+    #[cfg(feature = "v0_4")]
+    pub(crate) fn new(dim1: usize, dim2: usize) -> Self {
+        Self(dim1, dim2)
+    }
+
     pub fn check_dims(dim1: usize, dim2: usize) -> Result<usize, Self> {
         if dim1 == dim2 {
             Ok(dim1)
@@ -90,3 +483,59 @@ impl<T: Ndim> MaybeNdim for T {
         Some(self.ndim())
     }
 }
+
+/// Serialization to a stable, byte-reproducible JSON string.
+pub trait ToCanonicalJson: Serialize {
+    /// Compact JSON with object keys sorted lexicographically
+    /// and integral floats written as integers (`1.0` becomes `1`, `-0.0` becomes `0`).
+    fn to_canonical_json(&self) -> serde_json::Result<String> {
+        let value = canonicalize(serde_json::to_value(self)?);
+        serde_json::to_string(&value)
+    }
+}
+
+pub(crate) fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(m) => {
+            let mut entries: Vec<_> = m.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, canonicalize(v)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(a) => Value::Array(a.into_iter().map(canonicalize).collect()),
+        Value::Number(n) => Value::Number(canonical_number(n)),
+        v => v,
+    }
+}
+
+fn canonical_number(n: Number) -> Number {
+    let Some(f) = n.as_f64().filter(|_| n.is_f64()) else {
+        return n;
+    };
+    if f.fract() == 0.0 && f.abs() < (1u64 << 53) as f64 {
+        Number::from(f as i64)
+    } else {
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name() {
+        let name = Name::from("A");
+        assert_eq!(name, Name::from("A".to_owned()));
+        assert_eq!(name, "A");
+        assert_eq!(name.len(), 1);
+        assert_eq!(serde_json::to_string(&name).unwrap(), r#""A""#);
+        assert_eq!(serde_json::from_str::<Name>(r#""A""#).unwrap(), name);
+        let names: std::collections::HashSet<Name> = [name].into_iter().collect();
+        assert!(names.contains("A"));
+    }
+}
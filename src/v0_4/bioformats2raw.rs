@@ -0,0 +1,143 @@
+//! The transitional `bioformats2raw.layout`, in which a converter (such as bioformats2raw)
+//! writes each series of the input as an image group `0`, `1`, ... under the root group,
+//! and the input's OME-XML metadata to `OME/METADATA.ome.xml`.
+use serde::Deserialize;
+
+use crate::store::{join_key, LoadError, ReadableStore};
+use crate::util::{JsonError, ZPath};
+
+use super::{ATTRS_KEY, GROUP_KEY};
+
+/// The root group attribute identifying the layout, whose value is the layout version.
+pub const LAYOUT_KEY: &str = "bioformats2raw.layout";
+/// The group holding the OME-XML metadata and the series list, relative to the root group.
+pub const OME_GROUP: &str = "OME";
+/// The OME-XML metadata of every series, relative to [OME_GROUP].
+pub const OME_XML_KEY: &str = "METADATA.ome.xml";
+
+#[derive(Deserialize)]
+struct RootAttributes {
+    #[serde(rename = "bioformats2raw.layout")]
+    layout: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct OmeAttributes {
+    series: Option<Vec<ZPath>>,
+}
+
+/// A root group in the bioformats2raw layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bioformats2Raw {
+    group: ZPath,
+    layout: u64,
+    series: Vec<ZPath>,
+}
+
+impl Bioformats2Raw {
+    /// Read the layout of the root group at `group`, or `None` if it is not in the layout.
+    ///
+    /// The series are listed by the `OME` group's `series` attribute if it has one;
+    /// otherwise they are the consecutively numbered groups present from `0`.
+    pub fn from_store<S: ReadableStore + ?Sized>(
+        store: &S,
+        group: &str,
+    ) -> Result<Option<Self>, LoadError> {
+        let key = join_key([group, ATTRS_KEY]);
+        let Some(bytes) = store.get(&key)? else {
+            return Ok(None);
+        };
+        let root: RootAttributes = serde_json::from_slice(&bytes)
+            .map_err(|e| JsonError::Read("bioformats2raw attributes", e))?;
+        let Some(layout) = root.layout else {
+            return Ok(None);
+        };
+
+        let ome_attrs = join_key([group, OME_GROUP, ATTRS_KEY]);
+        let listed = match store.get(&ome_attrs)? {
+            Some(bytes) => {
+                let ome: OmeAttributes = serde_json::from_slice(&bytes)
+                    .map_err(|e| JsonError::Read("OME attributes", e))?;
+                ome.series
+            }
+            None => None,
+        };
+        let series = match listed {
+            Some(series) => series,
+            None => {
+                let mut series = Vec::default();
+                for idx in 0.. {
+                    let path = idx.to_string();
+                    let base = join_key([group, path.as_str()]);
+                    if !store.contains(&join_key([base.as_str(), GROUP_KEY]))?
+                        && !store.contains(&join_key([base.as_str(), ATTRS_KEY]))?
+                    {
+                        break;
+                    }
+                    series.push(path);
+                }
+                series
+            }
+        };
+        Ok(Some(Self {
+            group: group.to_owned(),
+            layout,
+            series,
+        }))
+    }
+
+    /// The version of the layout.
+    pub fn layout(&self) -> u64 {
+        self.layout
+    }
+
+    /// Paths of the series' image groups, relative to the root group.
+    pub fn series(&self) -> &[ZPath] {
+        self.series.as_slice()
+    }
+
+    /// Keys of the series' image groups, relative to the store root.
+    pub fn series_groups(&self) -> impl Iterator<Item = ZPath> + '_ {
+        self.series
+            .iter()
+            .map(|s| join_key([self.group.as_str(), s.as_str()]))
+    }
+
+    /// Key of the OME-XML metadata, relative to the store root.
+    pub fn ome_xml_key(&self) -> ZPath {
+        join_key([self.group.as_str(), OME_GROUP, OME_XML_KEY])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{MemoryStore, WritableStore};
+
+    #[test]
+    fn series() {
+        let store = MemoryStore::new();
+        store
+            .set("root/.zattrs", br#"{"bioformats2raw.layout": 3}"#)
+            .unwrap();
+        for key in ["root/0/.zgroup", "root/1/.zattrs", "root/3/.zgroup"] {
+            store.set(key, b"{}").unwrap();
+        }
+        let bf = Bioformats2Raw::from_store(&store, "root").unwrap().unwrap();
+        assert_eq!(bf.layout(), 3);
+        assert_eq!(bf.series(), ["0", "1"]);
+        assert_eq!(bf.ome_xml_key(), "root/OME/METADATA.ome.xml");
+
+        store
+            .set("root/OME/.zattrs", br#"{"series": ["0", "3"]}"#)
+            .unwrap();
+        let bf = Bioformats2Raw::from_store(&store, "root").unwrap().unwrap();
+        let groups: Vec<_> = bf.series_groups().collect();
+        assert_eq!(groups, ["root/0", "root/3"]);
+
+        store.set("other/.zattrs", b"{}").unwrap();
+        assert!(Bioformats2Raw::from_store(&store, "other")
+            .unwrap()
+            .is_none());
+    }
+}
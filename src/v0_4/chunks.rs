@@ -0,0 +1,192 @@
+use thiserror::Error;
+
+use super::multiscale::Multiscale;
+use crate::core::{Rounding, Transform, TransformError};
+use crate::util::{InconsistentDimensionality, Ndim};
+
+/// Relative tolerance when checking that scaled chunk sizes are whole multiples.
+const TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidChunks {
+    #[error("Expected chunk shapes for {0} levels, got {1}")]
+    Count(usize, usize),
+    #[error("Chunk shape for level {0} has {1} dimensions, expected {2}")]
+    Dimensions(usize, usize, usize),
+    #[error("Level {0} has no inline scale")]
+    NoScale(usize),
+    #[error("Level {0} has zero or non-finite scale on axis {1}")]
+    InvalidScale(usize, usize),
+    #[error("Chunks of level {1} do not align with chunks of level {0} on axis {2}")]
+    Misaligned(usize, usize, usize),
+    #[error("Chunk shape for level {0} is zero on axis {1}")]
+    ZeroSize(usize, usize),
+    #[error(transparent)]
+    Transform(#[from] TransformError),
+}
+
+/// Where a voxel lies in a chunked array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkLocation {
+    /// Index of the chunk in the chunk grid.
+    pub chunk: Vec<u64>,
+    /// Index of the voxel within the chunk.
+    pub offset: Vec<u64>,
+}
+
+impl Multiscale {
+    /// Locate the voxel of the dataset at `level` containing a world coordinate,
+    /// given that dataset's chunk shape.
+    ///
+    /// Returns `None` if the coordinate is before the start of the array on any axis.
+    pub fn chunk_for(
+        &self,
+        world_coord: &[f64],
+        level: usize,
+        chunk_shape: &[u64],
+    ) -> Result<Option<ChunkLocation>, InvalidChunks> {
+        InconsistentDimensionality::check_dims(world_coord.len(), self.ndim())
+            .map_err(TransformError::from)?;
+        InconsistentDimensionality::check_dims(chunk_shape.len(), self.ndim())
+            .map_err(TransformError::from)?;
+        if let Some(axis) = chunk_shape.iter().position(|c| *c == 0) {
+            return Err(InvalidChunks::ZeroSize(level, axis));
+        }
+        let Some(voxel) = (self, level).world_to_voxel(world_coord, Rounding::Floor)? else {
+            return Ok(None);
+        };
+        let (chunk, offset) = voxel
+            .iter()
+            .zip(chunk_shape.iter())
+            .map(|(idx, c)| (idx / c, idx % c))
+            .unzip();
+        Ok(Some(ChunkLocation { chunk, offset }))
+    }
+
+    /// Check that every chunk boundary of each level falls on a chunk boundary
+    /// of the level before it, under the downsampling factors implied by the datasets' scales.
+    ///
+    /// `chunk_shapes` has one chunk shape per dataset, in dataset order.
+    pub fn validate_chunk_alignment(&self, chunk_shapes: &[Vec<u64>]) -> Result<(), InvalidChunks> {
+        let datasets = self.datasets();
+        if chunk_shapes.len() != datasets.len() {
+            return Err(InvalidChunks::Count(datasets.len(), chunk_shapes.len()));
+        }
+        let ndim = self.ndim();
+        for (level, shape) in chunk_shapes.iter().enumerate() {
+            if shape.len() != ndim {
+                return Err(InvalidChunks::Dimensions(level, shape.len(), ndim));
+            }
+            if let Some(axis) = shape.iter().position(|c| *c == 0) {
+                return Err(InvalidChunks::ZeroSize(level, axis));
+            }
+        }
+        let scale = |level: usize| {
+            let s = datasets[level]
+                .scale()
+                .ok_or(InvalidChunks::NoScale(level))?;
+            match s.iter().position(|v| *v == 0.0 || !v.is_finite()) {
+                Some(axis) => Err(InvalidChunks::InvalidScale(level, axis)),
+                None => Ok(s),
+            }
+        };
+        for level in 1..datasets.len() {
+            let s0 = scale(level - 1)?;
+            let s1 = scale(level)?;
+            let c0 = &chunk_shapes[level - 1];
+            let c1 = &chunk_shapes[level];
+            for axis in 0..ndim {
+                // size of a coarse chunk, in voxels of the finer level
+                let mapped = c1[axis] as f64 * s1[axis] / s0[axis];
+                let ratio = mapped / c0[axis] as f64;
+                let rounded = ratio.round();
+                if rounded < 1.0 || (ratio - rounded).abs() > TOLERANCE * ratio {
+                    return Err(InvalidChunks::Misaligned(level - 1, level, axis));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"{
+        "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+        "datasets": [
+            {"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]},
+            {"path": "1", "coordinateTransformations": [{"type": "scale", "scale": [2, 2]}]}
+        ]
+    }"#;
+
+    #[test]
+    fn chunk_alignment() {
+        let ms: Multiscale = serde_json::from_str(EXAMPLE).unwrap();
+        ms.validate_chunk_alignment(&[vec![64, 64], vec![64, 64]])
+            .unwrap();
+        ms.validate_chunk_alignment(&[vec![64, 64], vec![32, 64]])
+            .unwrap();
+        assert_eq!(
+            ms.validate_chunk_alignment(&[vec![64, 64], vec![48, 64]]),
+            Err(InvalidChunks::Misaligned(0, 1, 0))
+        );
+        assert_eq!(
+            ms.validate_chunk_alignment(&[vec![64, 64]]),
+            Err(InvalidChunks::Count(2, 1))
+        );
+    }
+
+    #[test]
+    fn chunk_alignment_degenerate() {
+        let ms: Multiscale = serde_json::from_str(EXAMPLE).unwrap();
+        assert_eq!(
+            ms.validate_chunk_alignment(&[vec![64, 64], vec![0, 64]]),
+            Err(InvalidChunks::ZeroSize(1, 0))
+        );
+        assert_eq!(
+            ms.validate_chunk_alignment(&[vec![64, 0], vec![64, 64]]),
+            Err(InvalidChunks::ZeroSize(0, 1))
+        );
+
+        let zero_scale: Multiscale =
+            serde_json::from_str(&EXAMPLE.replace("[2, 2]", "[0, 2]")).unwrap();
+        assert_eq!(
+            zero_scale.validate_chunk_alignment(&[vec![64, 64], vec![64, 64]]),
+            Err(InvalidChunks::InvalidScale(1, 0))
+        );
+        let zero_scale: Multiscale =
+            serde_json::from_str(&EXAMPLE.replace("[1, 1]", "[1, 0]")).unwrap();
+        assert_eq!(
+            zero_scale.validate_chunk_alignment(&[vec![64, 64], vec![64, 64]]),
+            Err(InvalidChunks::InvalidScale(0, 1))
+        );
+    }
+
+    #[test]
+    fn chunk_for() {
+        let ms: Multiscale = serde_json::from_str(EXAMPLE).unwrap();
+        assert_eq!(
+            ms.chunk_for(&[130.0, 20.5], 1, &[32, 16]).unwrap(),
+            Some(ChunkLocation {
+                chunk: vec![2, 0],
+                offset: vec![1, 10]
+            })
+        );
+        assert_eq!(ms.chunk_for(&[-1.0, 0.0], 0, &[32, 16]).unwrap(), None);
+    }
+
+    #[test]
+    fn chunk_for_zero_size() {
+        let ms: Multiscale = serde_json::from_str(EXAMPLE).unwrap();
+        assert_eq!(
+            ms.chunk_for(&[1.0, 1.0], 0, &[0, 64]),
+            Err(InvalidChunks::ZeroSize(0, 0))
+        );
+        assert!(matches!(
+            ms.chunk_for(&[1.0, 1.0], 2, &[64, 64]),
+            Err(InvalidChunks::Transform(TransformError::NoDataset(2)))
+        ));
+    }
+}
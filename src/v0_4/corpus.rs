@@ -0,0 +1,132 @@
+//! Example documents from the OME-NGFF v0.4 specification.
+
+use thiserror::Error;
+
+use crate::util::JsonError;
+
+use super::{InvalidNgffMetadata, NgffMetadata};
+
+/// Which metadata key a fixture demonstrates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FixtureKind {
+    Multiscales,
+    ImageLabel,
+    Plate,
+    Well,
+}
+
+/// A spec example document.
+///
+/// Examples show a single key, so may not be complete groups
+/// (e.g. `image-label` without `multiscales`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixture {
+    pub name: &'static str,
+    pub kind: FixtureKind,
+    pub json: &'static str,
+}
+
+macro_rules! fixture {
+    ($name:literal, $kind:ident) => {
+        Fixture {
+            name: $name,
+            kind: FixtureKind::$kind,
+            json: include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/corpus/v0_4/",
+                $name,
+                ".json"
+            )),
+        }
+    };
+}
+
+pub const FIXTURES: &[Fixture] = &[
+    fixture!("multiscales_example", Multiscales),
+    fixture!("multiscales_transformations", Multiscales),
+    fixture!("colors_properties", ImageLabel),
+    fixture!("plate_6wells", Plate),
+    fixture!("plate_2wells", Plate),
+    fixture!("well_4fields", Well),
+    fixture!("well_2fields", Well),
+];
+
+pub fn fixtures() -> impl Iterator<Item = &'static Fixture> {
+    FIXTURES.iter()
+}
+
+/// The fixture with the given name, if there is one.
+pub fn fixture(name: &str) -> Option<&'static Fixture> {
+    fixtures().find(|fix| fix.name == name)
+}
+
+impl Fixture {
+    pub fn parse(&self) -> Result<NgffMetadata, JsonError> {
+        NgffMetadata::from_slice(self.json.as_bytes())
+    }
+
+    /// Parse the fixture and validate each metadata object it contains.
+    pub fn check(&self) -> Result<NgffMetadata, CorpusFailure<InvalidNgffMetadata>> {
+        let meta = self.parse()?;
+        self.validate(&meta).map_err(CorpusFailure::Check)?;
+        Ok(meta)
+    }
+
+    /// Validate the parsed fixture.
+    ///
+    /// Fixtures demonstrating `image-label` are not complete groups,
+    /// so only their `image-label` object is validated.
+    pub fn validate(&self, meta: &NgffMetadata) -> Result<(), InvalidNgffMetadata> {
+        match (self.kind, meta.image_label()) {
+            (FixtureKind::ImageLabel, Some(il)) => Ok(il.validate()?),
+            _ => meta.validate(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CorpusFailure<E> {
+    #[error(transparent)]
+    Parse(#[from] JsonError),
+    #[error("Check failed: {0}")]
+    Check(E),
+}
+
+/// Parse every fixture and pass it to a caller-supplied check,
+/// returning the names of failing fixtures with their errors.
+pub fn run<E, F>(mut check: F) -> Vec<(&'static str, CorpusFailure<E>)>
+where
+    F: FnMut(&Fixture, NgffMetadata) -> Result<(), E>,
+{
+    fixtures()
+        .filter_map(|fix| {
+            let result = fix
+                .parse()
+                .map_err(CorpusFailure::Parse)
+                .and_then(|meta| check(fix, meta).map_err(CorpusFailure::Check));
+            result.err().map(|e| (fix.name, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corpus_is_valid() {
+        let failures = run(|fix, meta| {
+            let present = match fix.kind {
+                FixtureKind::Multiscales => meta.multiscales().is_some(),
+                FixtureKind::ImageLabel => meta.image_label().is_some(),
+                FixtureKind::Plate => meta.plate().is_some(),
+                FixtureKind::Well => meta.well().is_some(),
+            };
+            if !present {
+                return Err(InvalidNgffMetadata::Empty);
+            }
+            fix.validate(&meta)
+        });
+        assert!(failures.is_empty(), "{failures:?}");
+    }
+}
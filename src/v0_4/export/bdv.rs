@@ -0,0 +1,117 @@
+use super::{escape_xml, space_axes};
+use crate::core::AxisType;
+use crate::core::TransformError;
+use crate::util::InconsistentDimensionality;
+use crate::v0_4::Multiscale;
+
+impl Multiscale {
+    /// A minimal BigDataViewer/BigStitcher XML dataset description of the image
+    /// in the zarr group at `url`, whose highest-resolution array has the given shape.
+    ///
+    /// There is one view setup per channel and one timepoint per time index,
+    /// each reading its `[t, c]` slice of the highest-resolution array,
+    /// registered by that array's scale and translation.
+    /// Space axes are reversed into BDV's `x y z` order; 2D images have a single z-slice.
+    pub fn bdv_xml(&self, url: &str, shape: &[u64]) -> Result<String, TransformError> {
+        InconsistentDimensionality::check_dims(self.axes().len(), shape.len())?;
+        let (scale, translation) = self.absolute_affine(0)?;
+        let count = |t: AxisType| {
+            self.axes()
+                .iter()
+                .position(|a| a.axis_type() == t)
+                .map_or(1, |idx| shape[idx])
+        };
+        let n_timepoints = count(AxisType::Time);
+        let n_channels = count(AxisType::Channel);
+
+        // x, y, z order
+        let space = space_axes(self.axes());
+        let mut size = [1; 3];
+        let mut voxel = [1.0; 3];
+        let mut offset = [0.0; 3];
+        for (xyz, idx) in space.iter().rev().enumerate().take(3) {
+            size[xyz] = shape[*idx];
+            voxel[xyz] = scale[*idx];
+            offset[xyz] = translation[*idx];
+        }
+        let unit = space
+            .last()
+            .and_then(|idx| self.axes()[*idx].unit())
+            .and_then(|u| u.to_unit())
+            .map_or("pixel".to_owned(), |u| u.to_string());
+        let join = |vs: &[f64]| vs.iter().map(f64::to_string).collect::<Vec<_>>().join(" ");
+
+        let mut lines = Vec::default();
+        lines.push(r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_owned());
+        lines.push(r#"<SpimData version="0.2">"#.to_owned());
+        lines.push(r#"  <BasePath type="relative">.</BasePath>"#.to_owned());
+        lines.push("  <SequenceDescription>".to_owned());
+        lines.push(r#"    <ImageLoader format="bdv.multimg.zarr" version="3.0">"#.to_owned());
+        lines.push(format!(
+            r#"      <zarr type="absolute">{}</zarr>"#,
+            escape_xml(url)
+        ));
+        lines.push("      <zgroups>".to_owned());
+        for t in 0..n_timepoints {
+            for c in 0..n_channels {
+                lines.push(format!(
+                    r#"        <zgroup setup="{c}" timepoint="{t}"><path></path><indicies>[{t}, {c}]</indicies></zgroup>"#
+                ));
+            }
+        }
+        lines.push("      </zgroups>".to_owned());
+        lines.push("    </ImageLoader>".to_owned());
+        lines.push("    <ViewSetups>".to_owned());
+        for c in 0..n_channels {
+            lines.push(format!(
+                "      <ViewSetup><id>{c}</id><name>{c}</name><size>{} {} {}</size><voxelSize><unit>{}</unit><size>{}</size></voxelSize></ViewSetup>",
+                size[0],
+                size[1],
+                size[2],
+                escape_xml(&unit),
+                join(&voxel)
+            ));
+        }
+        lines.push("    </ViewSetups>".to_owned());
+        lines.push(format!(
+            r#"    <Timepoints type="range"><first>0</first><last>{}</last></Timepoints>"#,
+            n_timepoints.saturating_sub(1)
+        ));
+        lines.push("  </SequenceDescription>".to_owned());
+        lines.push("  <ViewRegistrations>".to_owned());
+        let affine = [
+            voxel[0], 0.0, 0.0, offset[0], 0.0, voxel[1], 0.0, offset[1], 0.0, 0.0, voxel[2],
+            offset[2],
+        ];
+        for t in 0..n_timepoints {
+            for c in 0..n_channels {
+                lines.push(format!(
+                    r#"    <ViewRegistration timepoint="{t}" setup="{c}"><ViewTransform type="affine"><affine>{}</affine></ViewTransform></ViewRegistration>"#,
+                    join(&affine)
+                ));
+            }
+        }
+        lines.push("  </ViewRegistrations>".to_owned());
+        lines.push("</SpimData>".to_owned());
+        lines.push(String::new());
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bdv_xml() {
+        let ms = Multiscale::new_czyx(2.0, 0.5, 0.25).unwrap();
+        let xml = ms.bdv_xml("/data/img.zarr?a&b", &[2, 10, 20, 30]).unwrap();
+        assert!(xml.contains("<zarr type=\"absolute\">/data/img.zarr?a&amp;b</zarr>"));
+        assert_eq!(xml.matches("<ViewSetup>").count(), 2);
+        assert!(xml.contains("<size>30 20 10</size>"));
+        assert!(xml.contains("<unit>micrometer</unit><size>0.25 0.5 2</size>"));
+        assert!(xml.contains("<affine>0.25 0 0 0 0 0.5 0 0 0 0 2 0</affine>"));
+        assert!(xml.contains("<last>0</last>"));
+        assert!(ms.bdv_xml("img.zarr", &[2, 10]).is_err());
+    }
+}
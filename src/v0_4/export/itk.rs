@@ -0,0 +1,62 @@
+use serde::Serialize;
+
+use super::space_axes;
+use crate::core::TransformError;
+use crate::v0_4::Multiscale;
+
+/// The geometry of an image in ITK's conventions, e.g. for SimpleITK's
+/// `SetSpacing`, `SetOrigin`, and `SetDirection`.
+///
+/// Only space axes are included, in ITK's `x, y, z` order (the reverse of NGFF's),
+/// in the units of the axes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ItkGeometry {
+    pub spacing: Vec<f64>,
+    /// The world coordinate of the first voxel.
+    pub origin: Vec<f64>,
+    /// The row-major direction cosine matrix, which is always the identity for NGFF v0.4.
+    pub direction: Vec<f64>,
+}
+
+impl Multiscale {
+    /// The geometry of the dataset at `level` in ITK's conventions.
+    pub fn itk_geometry(&self, level: usize) -> Result<ItkGeometry, TransformError> {
+        let (scale, translation) = self.absolute_affine(level)?;
+        let space = space_axes(self.axes());
+        let ndim = space.len();
+        Ok(ItkGeometry {
+            spacing: space.iter().rev().map(|idx| scale[*idx]).collect(),
+            origin: space.iter().rev().map(|idx| translation[*idx]).collect(),
+            direction: (0..ndim * ndim)
+                .map(|i| if i % (ndim + 1) == 0 { 1.0 } else { 0.0 })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v0_4::Axis;
+
+    #[test]
+    fn itk_geometry() {
+        let axes = ["t", "z", "y", "x"]
+            .into_iter()
+            .map(Axis::from_name)
+            .collect();
+        let ms = Multiscale::single_level(
+            axes,
+            vec![10.0, 3.0, 2.0, 1.0],
+            Some(vec![0.0, 30.0, 20.0, 10.0]),
+        )
+        .unwrap();
+        let geometry = ms.itk_geometry(0).unwrap();
+        assert_eq!(geometry.spacing, [1.0, 2.0, 3.0]);
+        assert_eq!(geometry.origin, [10.0, 20.0, 30.0]);
+        assert_eq!(
+            geometry.direction,
+            [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Descriptions of multiscale images for other tools and file formats.
+use crate::core::{Axis, AxisType, AxisUnit};
+
+mod bdv;
+mod itk;
+mod napari;
+mod neuroglancer;
+mod nifti;
+mod xarray;
+
+pub use itk::ItkGeometry;
+pub use napari::{NapariImage, NapariLabels};
+pub use neuroglancer::NeuroglancerLayerType;
+pub use xarray::{XarrayCoordinate, XarrayDescription};
+
+/// The factor converting an axis' values to SI base units (meters or seconds),
+/// and the symbol of that unit; dimensionless if the unit is missing or unknown.
+fn si_unit(axis: &Axis) -> (f64, &'static str) {
+    let factor = axis
+        .unit()
+        .and_then(|u| u.to_unit())
+        .and_then(|u| u.si_factor());
+    match (axis.axis_type(), axis.unit(), factor) {
+        (AxisType::Space, Some(AxisUnit::Space(_)), Some(f)) => (f, "m"),
+        (AxisType::Time, Some(AxisUnit::Time(_)), Some(f)) => (f, "s"),
+        _ => (1.0, ""),
+    }
+}
+
+/// Indices of the space axes, in axis order.
+fn space_axes(axes: &[Axis]) -> Vec<usize> {
+    axes.iter()
+        .enumerate()
+        .filter(|(_, a)| a.axis_type() == AxisType::Space)
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
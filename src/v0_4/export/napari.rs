@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::AxisType;
+use crate::core::TransformError;
+use crate::v0_4::{Channel, ImageLabel, Multiscale, Omero};
+
+/// Keyword arguments for napari's `Viewer.add_image`.
+///
+/// If the image has a channel axis, `channel_axis` is set,
+/// the spatial fields exclude it, and the per-channel fields are filled from the [Omero] metadata.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NapariImage {
+    pub scale: Vec<f64>,
+    pub translate: Vec<f64>,
+    pub axis_labels: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_axis: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<Vec<String>>,
+    /// A linear colormap per channel, from black (or the color, if inverted) to the color,
+    /// as RGBA floats.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colormap: Option<Vec<[[f32; 4]; 2]>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contrast_limits: Option<Vec<[f64; 2]>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visible: Option<Vec<bool>>,
+}
+
+/// Keyword arguments for napari's `Viewer.add_labels`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NapariLabels {
+    pub scale: Vec<f64>,
+    pub translate: Vec<f64>,
+    pub axis_labels: Vec<String>,
+    /// RGBA floats by label value, for a `DirectLabelColormap`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colormap: Option<BTreeMap<u64, [f32; 4]>>,
+}
+
+struct Spatial {
+    scale: Vec<f64>,
+    translate: Vec<f64>,
+    axis_labels: Vec<String>,
+}
+
+impl Multiscale {
+    /// The transform and axes of the dataset at `level`, leaving out the axis at `skip`.
+    fn napari_spatial(&self, level: usize, skip: Option<usize>) -> Result<Spatial, TransformError> {
+        let (scale, translation) = self.absolute_affine(level)?;
+        Ok(Spatial {
+            scale: without(scale, skip),
+            translate: without(translation, skip),
+            axis_labels: without(self.axes().iter().map(|a| a.name().to_owned()), skip),
+        })
+    }
+
+    /// Arguments to add the dataset at `level` as a napari image layer,
+    /// with channel display settings from `omero` if given.
+    pub fn napari_image(
+        &self,
+        level: usize,
+        omero: Option<&Omero>,
+    ) -> Result<NapariImage, TransformError> {
+        let channel_axis = self
+            .axes()
+            .iter()
+            .position(|a| a.axis_type() == AxisType::Channel);
+        let spatial = self.napari_spatial(level, channel_axis)?;
+        let channels = omero
+            .filter(|_| channel_axis.is_some())
+            .map(Omero::channels)
+            .filter(|chs| !chs.is_empty());
+        Ok(NapariImage {
+            scale: spatial.scale,
+            translate: spatial.translate,
+            axis_labels: spatial.axis_labels,
+            channel_axis,
+            name: per_channel(channels, |c| c.label.clone()),
+            colormap: per_channel(channels, |c| {
+                let color = hex_rgba(c.color.as_deref()?)?;
+                let black = [0.0, 0.0, 0.0, 1.0];
+                Some(if c.inverted == Some(true) {
+                    [color, black]
+                } else {
+                    [black, color]
+                })
+            }),
+            contrast_limits: per_channel(channels, |c| c.window.map(|w| [w.start, w.end])),
+            visible: per_channel(channels, |c| Some(c.active.unwrap_or(true))),
+        })
+    }
+
+    /// Arguments to add the dataset at `level` as a napari labels layer,
+    /// with colors from `image_label` if given (the first of any repeated label).
+    pub fn napari_labels(
+        &self,
+        level: usize,
+        image_label: Option<&ImageLabel>,
+    ) -> Result<NapariLabels, TransformError> {
+        let spatial = self.napari_spatial(level, None)?;
+        let colormap = image_label
+            .map(|il| {
+                il.label_colors()
+                    .into_iter()
+                    .map(|(label, rgba)| (label, rgba.map(|c| c as f32 / 255.0)))
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .filter(|cmap| !cmap.is_empty());
+        Ok(NapariLabels {
+            scale: spatial.scale,
+            translate: spatial.translate,
+            axis_labels: spatial.axis_labels,
+            colormap,
+        })
+    }
+}
+
+/// The items other than the one at index `skip`.
+fn without<T>(items: impl IntoIterator<Item = T>, skip: Option<usize>) -> Vec<T> {
+    items
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| Some(*idx) != skip)
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// A value for every channel, or `None` if there are no channels or any value is missing.
+fn per_channel<T>(
+    channels: Option<&[Channel]>,
+    f: impl Fn(&Channel) -> Option<T>,
+) -> Option<Vec<T>> {
+    channels?.iter().map(f).collect()
+}
+
+/// Parse a 6-digit hex RGB color into opaque RGBA floats.
+fn hex_rgba(hex: &str) -> Option<[f32; 4]> {
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(hex.get(i..i + 2)?, 16)
+            .ok()
+            .map(|c| c as f32 / 255.0)
+    };
+    Some([channel(0)?, channel(2)?, channel(4)?, 1.0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn napari_image() {
+        let ms = Multiscale::new_czyx(2.0, 0.5, 0.5).unwrap();
+        let omero: Omero = serde_json::from_value(json!({
+            "channels": [
+                {"label": "DAPI", "color": "0000FF", "window": {"start": 0, "end": 100, "min": 0, "max": 255}},
+                {"label": "GFP", "color": "00FF00", "active": false, "window": {"start": 10, "end": 200, "min": 0, "max": 255}}
+            ]
+        }))
+        .unwrap();
+        let image = ms.napari_image(0, Some(&omero)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&image).unwrap(),
+            json!({
+                "scale": [2.0, 0.5, 0.5],
+                "translate": [0.0, 0.0, 0.0],
+                "axis_labels": ["z", "y", "x"],
+                "channel_axis": 0,
+                "name": ["DAPI", "GFP"],
+                "colormap": [
+                    [[0.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]],
+                    [[0.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0]]
+                ],
+                "contrast_limits": [[0.0, 100.0], [10.0, 200.0]],
+                "visible": [true, false]
+            })
+        );
+
+        let labels: ImageLabel = serde_json::from_value(json!({
+            "colors": [
+                {"label-value": 1, "rgba": [255, 0, 0, 255]},
+                {"label-value": 1, "rgba": [0, 255, 0, 255]}
+            ]
+        }))
+        .unwrap();
+        let ms = Multiscale::new_3d(2.0, 0.5, 0.5).unwrap();
+        let labels = ms.napari_labels(0, Some(&labels)).unwrap();
+        assert_eq!(labels.axis_labels, ["z", "y", "x"]);
+        assert_eq!(labels.colormap.unwrap()[&1], [1.0, 0.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,140 @@
+use serde_json::Value;
+
+use super::si_unit;
+use crate::core::AxisType;
+use crate::core::TransformError;
+use crate::v0_4::Multiscale;
+
+/// The kind of neuroglancer layer to display a multiscale as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NeuroglancerLayerType {
+    #[default]
+    Image,
+    /// For label images.
+    Segmentation,
+}
+
+impl NeuroglancerLayerType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Segmentation => "segmentation",
+        }
+    }
+}
+
+impl Multiscale {
+    /// A neuroglancer layer (as JSON) showing the image in the zarr group at `url`.
+    ///
+    /// The layer's output dimensions are in the order of the axes,
+    /// with the highest-resolution voxel size in SI base units (meters and seconds);
+    /// channel axes become local dimensions (e.g. `c'`), and other axes are dimensionless.
+    /// The transform's `matrix` carries the highest-resolution dataset's translation,
+    /// in those voxels.
+    ///
+    /// The JSON is built by hand, as neuroglancer depends on the order of the dimensions' keys.
+    pub fn neuroglancer_layer(
+        &self,
+        url: &str,
+        layer_type: NeuroglancerLayerType,
+    ) -> Result<String, TransformError> {
+        let (voxel_size, translation) = self.absolute_affine(0)?;
+        let ndim = voxel_size.len();
+        let matrix: Vec<Vec<f64>> = translation
+            .iter()
+            .zip(voxel_size.iter())
+            .enumerate()
+            .map(|(row, (t, s))| {
+                let mut values = vec![0.0; ndim + 1];
+                values[row] = 1.0;
+                values[ndim] = if *s == 0.0 { 0.0 } else { t / s };
+                values
+            })
+            .collect();
+        let dimensions: Vec<String> = self
+            .axes()
+            .iter()
+            .zip(voxel_size)
+            .map(|(axis, size)| {
+                let (factor, unit) = si_unit(axis);
+                let name = match axis.axis_type() {
+                    AxisType::Channel => format!("{}'", axis.name()),
+                    _ => axis.name().to_owned(),
+                };
+                format!(
+                    "{}: [{}, {}]",
+                    Value::from(name),
+                    Value::from(size * factor),
+                    Value::from(unit)
+                )
+            })
+            .collect();
+        Ok(format!(
+            r#"{{"type": {}, "source": {{"url": {}, "transform": {{"matrix": {}, "outputDimensions": {{{}}}}}}}}}"#,
+            Value::from(layer_type.as_str()),
+            Value::from(format!("zarr://{url}")),
+            Value::from(matrix),
+            dimensions.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neuroglancer_layer() {
+        let ms = Multiscale::new_czyx(0.5, 0.25, 0.25).unwrap();
+        let layer = ms
+            .neuroglancer_layer("https://example.com/img.zarr", NeuroglancerLayerType::Image)
+            .unwrap();
+        let value: Value = serde_json::from_str(&layer).unwrap();
+        assert_eq!(
+            value["source"]["url"],
+            "zarr://https://example.com/img.zarr"
+        );
+        let dims = &value["source"]["transform"]["outputDimensions"];
+        assert_eq!(dims["c'"], serde_json::json!([1.0, ""]));
+        assert_eq!(dims["z"], serde_json::json!([5e-7, "m"]));
+
+        let order: Vec<_> = ["\"c'\"", "\"z\"", "\"y\"", "\"x\""]
+            .iter()
+            .map(|k| layer.find(k).unwrap())
+            .collect();
+        assert!(order.is_sorted());
+        assert_eq!(
+            value["source"]["transform"]["matrix"],
+            serde_json::json!([
+                [1.0, 0.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0, 0.0]
+            ])
+        );
+    }
+
+    #[test]
+    fn neuroglancer_translation() {
+        let ms = Multiscale::single_level(
+            Multiscale::new_2d(1.0, 1.0).unwrap().axes().to_vec(),
+            vec![0.5, 0.25],
+            Some(vec![2.0, -1.0]),
+        )
+        .unwrap();
+        let layer = ms
+            .neuroglancer_layer("img.zarr", NeuroglancerLayerType::Segmentation)
+            .unwrap();
+        let value: Value = serde_json::from_str(&layer).unwrap();
+        assert_eq!(value["type"], "segmentation");
+        let transform = &value["source"]["transform"];
+        assert_eq!(
+            transform["matrix"],
+            serde_json::json!([[1.0, 0.0, 4.0], [0.0, 1.0, -4.0]])
+        );
+        assert_eq!(
+            transform["outputDimensions"]["y"],
+            serde_json::json!([5e-7, "m"])
+        );
+    }
+}
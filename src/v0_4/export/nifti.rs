@@ -0,0 +1,50 @@
+use super::{si_unit, space_axes};
+use crate::core::TransformError;
+use crate::v0_4::Multiscale;
+
+impl Multiscale {
+    /// The sform-style affine of the dataset at `level`, mapping NIfTI voxel indices `(i, j, k)`
+    /// (i.e. the space axes reversed, `x` first) to world coordinates in millimeters.
+    ///
+    /// NGFF v0.4 has no anatomical orientation metadata,
+    /// so the `x`, `y`, and `z` axes are taken to point right, anterior, and superior (RAS).
+    /// Axes with missing or unknown units are not converted.
+    /// 2D images have a unit `k` axis.
+    pub fn to_nifti_affine(&self, level: usize) -> Result<[[f64; 4]; 4], TransformError> {
+        let (scale, translation) = self.absolute_affine(level)?;
+        let mut affine = [[0.0; 4]; 4];
+        for (row, a) in affine.iter_mut().enumerate() {
+            a[row] = 1.0;
+        }
+        for (ijk, idx) in space_axes(self.axes()).iter().rev().enumerate().take(3) {
+            let mm = match si_unit(&self.axes()[*idx]) {
+                (factor, "m") => factor * 1e3,
+                _ => 1.0,
+            };
+            affine[ijk][ijk] = scale[*idx] * mm;
+            affine[ijk][3] = translation[*idx] * mm;
+        }
+        Ok(affine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nifti_affine() {
+        let ms = Multiscale::new_3d(2.0, 0.5, 0.25).unwrap();
+        let affine = ms.to_nifti_affine(0).unwrap();
+        assert_eq!(affine[0][0], 0.25e-3);
+        assert_eq!(affine[1][1], 0.5e-3);
+        assert_eq!(affine[2][2], 2e-3);
+        assert_eq!(affine[3], [0.0, 0.0, 0.0, 1.0]);
+
+        let affine = Multiscale::new_2d(1.0, 1.0)
+            .unwrap()
+            .to_nifti_affine(0)
+            .unwrap();
+        assert_eq!(affine[2][2], 1.0);
+    }
+}
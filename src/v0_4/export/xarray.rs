@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::{Axis, AxisType, AxisUnit};
+use crate::core::TransformError;
+use crate::util::InconsistentDimensionality;
+use crate::v0_4::Multiscale;
+
+/// The dimensions and coordinates of an array, in the layout of xarray's
+/// `DataArray.to_dict(data=False)` (so it can be passed to `DataArray.from_dict` with the data).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct XarrayDescription {
+    pub dims: Vec<String>,
+    pub coords: BTreeMap<String, XarrayCoordinate>,
+    pub attrs: BTreeMap<String, String>,
+}
+
+/// The world coordinate of each index along one dimension.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct XarrayCoordinate {
+    pub dims: Vec<String>,
+    pub data: Vec<f64>,
+    /// The axis' `units` and `type`, if known.
+    pub attrs: BTreeMap<String, String>,
+}
+
+impl Multiscale {
+    /// The dimensions and coordinates of the dataset at `level`, whose array has the given shape.
+    pub fn xarray_description(
+        &self,
+        level: usize,
+        shape: &[u64],
+    ) -> Result<XarrayDescription, TransformError> {
+        InconsistentDimensionality::check_dims(self.axes().len(), shape.len())?;
+        let (scale, translation) = self.absolute_affine(level)?;
+        let dims: Vec<String> = self.axes().iter().map(|a| a.name().to_owned()).collect();
+        let coords = self
+            .axes()
+            .iter()
+            .enumerate()
+            .map(|(idx, axis)| {
+                let mut attrs = BTreeMap::default();
+                if let Some(unit) = axis.unit() {
+                    let unit = match unit {
+                        AxisUnit::Space(u) => u.to_string(),
+                        AxisUnit::Time(u) => u.to_string(),
+                        AxisUnit::Other(u) => u.to_owned(),
+                    };
+                    attrs.insert("units".to_owned(), unit);
+                }
+                if let Some(t) = axis_type_name(axis) {
+                    attrs.insert("type".to_owned(), t.to_owned());
+                }
+                let coord = XarrayCoordinate {
+                    dims: vec![dims[idx].clone()],
+                    data: (0..shape[idx])
+                        .map(|i| translation[idx] + scale[idx] * i as f64)
+                        .collect(),
+                    attrs,
+                };
+                (dims[idx].clone(), coord)
+            })
+            .collect();
+        Ok(XarrayDescription {
+            dims,
+            coords,
+            attrs: BTreeMap::default(),
+        })
+    }
+}
+
+fn axis_type_name(axis: &Axis) -> Option<&str> {
+    match axis.axis_type() {
+        AxisType::Space => Some("space"),
+        AxisType::Time => Some("time"),
+        AxisType::Channel => Some("channel"),
+        AxisType::Custom(t) => t,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xarray_description() {
+        let ms = Multiscale::new_czyx(2.0, 0.5, 0.5).unwrap();
+        let desc = ms.xarray_description(0, &[2, 1, 2, 3]).unwrap();
+        assert_eq!(desc.dims, ["c", "z", "y", "x"]);
+        assert_eq!(desc.coords["x"].data, [0.0, 0.5, 1.0]);
+        assert_eq!(desc.coords["c"].data, [0.0, 1.0]);
+        assert_eq!(desc.coords["x"].attrs["units"], "micrometer");
+        assert!(!desc.coords["c"].attrs.contains_key("units"));
+        let value = serde_json::to_value(&desc).unwrap();
+        assert_eq!(value["coords"]["z"]["dims"], serde_json::json!(["z"]));
+    }
+}
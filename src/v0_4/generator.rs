@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+/// Typed view of a multiscale's free-form `metadata`,
+/// for the shapes written by known pyramid generators.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeneratorMetadata<'a> {
+    /// scikit-image, e.g. `skimage.transform.pyramid_gaussian`.
+    Skimage {
+        method: &'a str,
+        version: Option<&'a str>,
+        description: Option<&'a str>,
+        args: Option<&'a Value>,
+        kwargs: Option<&'a Value>,
+    },
+    /// bioformats2raw, whose method is a Java scaler class, e.g. `loci.common.image.SimpleImageScaler`.
+    Bioformats2raw {
+        method: &'a str,
+        version: Option<&'a str>,
+    },
+    /// ngff-zarr, whose method is one of its `Methods`, e.g. `itkwasm_gaussian`.
+    NgffZarr {
+        method: &'a str,
+        version: Option<&'a str>,
+        description: Option<&'a str>,
+    },
+    /// Anything not recognised.
+    Other(&'a HashMap<String, Value>),
+}
+
+const NGFF_ZARR_METHODS: &[&str] = &[
+    "itkwasm_gaussian",
+    "itkwasm_bin_shrink",
+    "itkwasm_label_image",
+    "itk_gaussian",
+    "itk_bin_shrink",
+    "dask_image_gaussian",
+    "dask_image_mode",
+    "dask_image_nearest",
+];
+
+impl<'a> GeneratorMetadata<'a> {
+    pub fn from_map(map: &'a HashMap<String, Value>) -> Self {
+        let get_str = |k: &str| map.get(k).and_then(Value::as_str);
+        let Some(method) = get_str("method") else {
+            return Self::Other(map);
+        };
+        let version = get_str("version");
+        if method.starts_with("skimage.") {
+            Self::Skimage {
+                method,
+                version,
+                description: get_str("description"),
+                args: map.get("args"),
+                kwargs: map.get("kwargs"),
+            }
+        } else if method.starts_with("loci.") {
+            Self::Bioformats2raw { method, version }
+        } else if NGFF_ZARR_METHODS.contains(&method) {
+            Self::NgffZarr {
+                method,
+                version,
+                description: get_str("description"),
+            }
+        } else {
+            Self::Other(map)
+        }
+    }
+
+    /// The generating method, if given.
+    pub fn method(&self) -> Option<&'a str> {
+        match self {
+            Self::Skimage { method, .. }
+            | Self::Bioformats2raw { method, .. }
+            | Self::NgffZarr { method, .. } => Some(method),
+            Self::Other(m) => m.get("method").and_then(Value::as_str),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use thiserror::Error;
+use zarrs::array::{Array, ArrayCreateError, ArrayError};
+use zarrs::group::{Group, GroupCreateError};
+use zarrs::storage::ReadableStorageTraits;
+
+use super::multiscale::{InvalidMultiscale, Multiscale};
+#[cfg(feature = "ndarray")]
+use super::omero::ChannelStats;
+use super::region::VoxelRegion;
+use super::NgffMetadata;
+use crate::core::Axis;
+use crate::core::TransformError;
+use crate::store::join_key;
+
+#[derive(Debug, Error)]
+pub enum ImageError {
+    #[error(transparent)]
+    Group(#[from] GroupCreateError),
+    #[error(transparent)]
+    ArrayCreate(#[from] ArrayCreateError),
+    #[error(transparent)]
+    Array(#[from] ArrayError),
+    #[error("Could not read group attributes: {0}")]
+    Attributes(#[source] serde_json::Error),
+    #[error("No multiscale at index {0}")]
+    NoMultiscale(usize),
+    #[error(transparent)]
+    Transform(#[from] TransformError),
+    #[error(transparent)]
+    Multiscale(#[from] InvalidMultiscale),
+    #[error("Array {0} has no dimension names, or they differ from the first level's")]
+    DimensionNames(String),
+}
+
+/// A multiscale image in a zarr hierarchy, read with `zarrs`.
+pub struct Image<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    path: String,
+    multiscale: Multiscale,
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Image<TStorage> {
+    pub fn new(storage: Arc<TStorage>, path: &str, multiscale: Multiscale) -> Self {
+        Self {
+            storage,
+            path: format!("/{}", join_key([path])),
+            multiscale,
+        }
+    }
+
+    /// Read the multiscale at `index` in the attributes of the group at `path`.
+    pub fn open(storage: Arc<TStorage>, path: &str, index: usize) -> Result<Self, ImageError> {
+        let image_path = format!("/{}", join_key([path]));
+        let group = Group::open(storage.clone(), &image_path)?;
+        let attrs = serde_json::Value::Object(group.attributes().clone());
+        let meta: NgffMetadata = serde_json::from_value(attrs).map_err(ImageError::Attributes)?;
+        let multiscale = meta
+            .multiscales
+            .and_then(|mut mss| (index < mss.len()).then(|| mss.swap_remove(index)))
+            .ok_or(ImageError::NoMultiscale(index))?;
+        Ok(Self::new(storage, path, multiscale))
+    }
+
+    /// Retro-fit multiscale metadata onto plain zarr arrays at `level_paths` under `path`,
+    /// from highest to lowest resolution, inferring each level's scale from its shape.
+    ///
+    /// Axes are typed from the first level's dimension names (see [Axis::from_name]);
+    /// arrays without dimension names are assumed to use the trailing axes of `tczyx`.
+    pub fn from_arrays(
+        storage: Arc<TStorage>,
+        path: &str,
+        level_paths: &[&str],
+        base_voxel_size: &[f64],
+    ) -> Result<Self, ImageError> {
+        let mut names: Option<Vec<String>> = None;
+        let mut levels = Vec::with_capacity(level_paths.len());
+        for level_path in level_paths {
+            let array_path = format!("/{}", join_key([path, level_path]));
+            let array = Array::open(storage.clone(), &array_path)?;
+            let level_names = match array.dimension_names() {
+                Some(ns) => ns
+                    .iter()
+                    .map(|n| {
+                        n.clone()
+                            .ok_or_else(|| ImageError::DimensionNames(array_path.clone()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+                None if array.dimensionality() <= 5 => "tczyx"[5 - array.dimensionality()..]
+                    .chars()
+                    .map(String::from)
+                    .collect(),
+                None => return Err(ImageError::DimensionNames(array_path)),
+            };
+            match &names {
+                Some(ns) if ns != &level_names => {
+                    return Err(ImageError::DimensionNames(array_path));
+                }
+                Some(_) => (),
+                None => names = Some(level_names),
+            }
+            levels.push((level_path, array.shape().to_vec()));
+        }
+        let axes = names
+            .unwrap_or_default()
+            .iter()
+            .map(|n| Axis::from_name(n))
+            .collect();
+        let multiscale = Multiscale::from_shapes(axes, &levels, base_voxel_size)?;
+        Ok(Self::new(storage, path, multiscale))
+    }
+
+    pub fn multiscale(&self) -> &Multiscale {
+        &self.multiscale
+    }
+
+    /// The array of the dataset at `level`.
+    pub fn array(&self, level: usize) -> Result<Array<TStorage>, ImageError> {
+        let ds = self
+            .multiscale
+            .datasets()
+            .get(level)
+            .ok_or(TransformError::NoDataset(level))?;
+        let path = format!("/{}", join_key([self.path.as_str(), ds.path()]));
+        Ok(Array::open(self.storage.clone(), &path)?)
+    }
+
+    /// The array shape of every dataset, in dataset order.
+    pub fn shapes(&self) -> Result<Vec<Vec<u64>>, ImageError> {
+        (0..self.multiscale.datasets().len())
+            .map(|level| Ok(self.array(level)?.shape().to_vec()))
+            .collect()
+    }
+
+    /// The voxels intersecting a world-space box at the given level,
+    /// or at the highest-resolution level with at most `max_voxels` voxels in the box.
+    pub fn region(
+        &self,
+        world_min: &[f64],
+        world_max: &[f64],
+        level: LevelSelection,
+    ) -> Result<VoxelRegion, ImageError> {
+        Ok(match level {
+            LevelSelection::Level(level) => {
+                let shape = self.array(level)?.shape().to_vec();
+                self.multiscale
+                    .region_at_level(world_min, world_max, level, &shape)?
+            }
+            LevelSelection::MaxVoxels(max) => {
+                self.multiscale
+                    .region(world_min, world_max, &self.shapes()?, max)?
+            }
+        })
+    }
+}
+
+/// How to choose the pyramid level to read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelSelection {
+    Level(usize),
+    /// The highest resolution at which the region has at most this many voxels.
+    MaxVoxels(u64),
+}
+
+#[cfg(feature = "ndarray")]
+impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Image<TStorage> {
+    /// Read the voxels intersecting a world-space box, at the selected level.
+    pub fn read_region<T: zarrs::array::ElementOwned>(
+        &self,
+        world_min: &[f64],
+        world_max: &[f64],
+        level: LevelSelection,
+    ) -> Result<ndarray::ArrayD<T>, ImageError> {
+        let region = self.region(world_min, world_max, level)?;
+        let subset = zarrs::array::ArraySubset::new_with_ranges(&region.ranges);
+        Ok(self
+            .array(region.level)?
+            .retrieve_array_subset::<ndarray::ArrayD<T>>(&subset)?)
+    }
+
+    /// Statistics of each channel's values at `level`, e.g. to fill [super::Omero] windows,
+    /// with the given low and high percentiles (e.g. `(0.1, 99.9)`).
+    ///
+    /// The whole level is read into memory, so this is best used on a low-resolution level.
+    /// Images without a channel axis are treated as a single channel.
+    pub fn channel_stats<T: zarrs::array::ElementOwned + Copy + Into<f64>>(
+        &self,
+        level: usize,
+        percentiles: (f64, f64),
+    ) -> Result<Vec<ChannelStats>, ImageError> {
+        let array = self.array(level)?;
+        let data = array.retrieve_array_subset::<ndarray::ArrayD<T>>(&array.subset_all())?;
+        let channel_axis = self
+            .multiscale
+            .axes()
+            .iter()
+            .position(|a| matches!(a, Axis::Core(super::CoreAxis::Channel { .. })));
+        let stats = |values: ndarray::ArrayViewD<T>| {
+            let mut values: Vec<f64> = values.iter().map(|v| (*v).into()).collect();
+            values.sort_by(f64::total_cmp);
+            let at = |p: f64| {
+                let idx = (p / 100.0 * (values.len() as f64 - 1.0)).round();
+                values
+                    .get(idx.max(0.0) as usize)
+                    .copied()
+                    .unwrap_or_default()
+            };
+            ChannelStats {
+                min: values.first().copied().unwrap_or_default(),
+                max: values.last().copied().unwrap_or_default(),
+                percentiles: Some((at(percentiles.0), at(percentiles.1))),
+            }
+        };
+        Ok(match channel_axis {
+            Some(ax) => data.axis_iter(ndarray::Axis(ax)).map(stats).collect(),
+            None => vec![stats(data.view())],
+        })
+    }
+}
+
+#[cfg(all(test, feature = "ndarray"))]
+mod tests {
+    use super::*;
+    use zarrs::array::{data_type, ArrayBuilder};
+    use zarrs::group::GroupBuilder;
+    use zarrs::storage::store::MemoryStore;
+
+    /// A two-level `yx` pyramid at `img`, with values counting up from 0 in each level.
+    fn pyramid() -> Arc<MemoryStore> {
+        let store = Arc::new(MemoryStore::new());
+        let mut group = GroupBuilder::new().build(store.clone(), "/img").unwrap();
+        let attrs: serde_json::Value = serde_json::from_str(
+            r#"{"multiscales": [{
+                "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                "datasets": [
+                    {"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [0.5, 0.5]}]},
+                    {"path": "1", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}
+                ]
+            }]}"#,
+        )
+        .unwrap();
+        *group.attributes_mut() = attrs.as_object().unwrap().clone();
+        group.store_metadata().unwrap();
+        for (level, size) in [(0, 8u64), (1, 4)] {
+            let array = ArrayBuilder::new(vec![size, size], vec![2, 2], data_type::uint8(), 0u8)
+                .build(store.clone(), &format!("/img/{level}"))
+                .unwrap();
+            array.store_metadata().unwrap();
+            let values: Vec<u8> = (0..size * size).map(|v| v as u8).collect();
+            let data = ndarray::ArrayD::from_shape_vec(vec![size as usize; 2], values).unwrap();
+            array.store_array_subset(&array.subset_all(), data).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn read_region() {
+        let image = Image::open(pyramid(), "img", 0).unwrap();
+        let data: ndarray::ArrayD<u8> = image
+            .read_region(&[1.0, 0.0], &[2.0, 1.0], LevelSelection::Level(0))
+            .unwrap();
+        assert_eq!(data.shape(), &[2, 2]);
+        assert_eq!(data[[0, 0]], 16);
+
+        let data: ndarray::ArrayD<u8> = image
+            .read_region(&[0.0, 0.0], &[2.0, 2.0], LevelSelection::MaxVoxels(4))
+            .unwrap();
+        assert_eq!(data.shape(), &[2, 2]);
+        assert_eq!(data[[1, 1]], 5);
+    }
+
+    #[test]
+    fn channel_stats() {
+        let image = Image::open(pyramid(), "img", 0).unwrap();
+        let stats = image.channel_stats::<u8>(1, (0.0, 50.0)).unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!((stats[0].min, stats[0].max), (0.0, 15.0));
+        assert_eq!(stats[0].percentiles, Some((0.0, 8.0)));
+
+        let stats = image.channel_stats::<u8>(0, (0.0, 100.0)).unwrap();
+        assert_eq!((stats[0].min, stats[0].max), (0.0, 63.0));
+        assert_eq!(stats[0].percentiles, Some((0.0, 63.0)));
+    }
+
+    #[test]
+    fn from_arrays() {
+        let inferred = Image::from_arrays(pyramid(), "img", &["0", "1"], &[0.5, 0.5]).unwrap();
+        assert_eq!(inferred.multiscale().voxel_size(1).unwrap(), vec![1.0, 1.0]);
+        let names: Vec<_> = inferred
+            .multiscale()
+            .axes()
+            .iter()
+            .map(Axis::name)
+            .collect();
+        assert_eq!(names, ["y", "x"]);
+        assert_eq!(inferred.multiscale().voxel_size(0).unwrap(), vec![0.5, 0.5]);
+    }
+}
@@ -1,9 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::util::{impl_spec_diagnostic, SpecError};
+
 pub type LabelType = u64;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -16,7 +18,7 @@ pub struct Color {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImageLabel {
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "super::serialize_version")]
     version: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     colors: Option<Vec<Color>>,
@@ -26,49 +28,211 @@ pub struct ImageLabel {
     source: Option<Source>,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum InvalidImageLabel {
-    #[error("Label values are not unique")]
-    NonUniqueLabels,
+    #[error("Label value {0} is not unique")]
+    NonUniqueLabels(LabelType),
+    #[error("Expected version {}, got {0}", super::VERSION)]
+    Version(String),
+    #[error("Label array {0} has non-integer data type {1}")]
+    Dtype(String, String),
+    #[error("Label array {0} has no data type")]
+    MissingDtype(String),
+}
+
+impl SpecError for InvalidImageLabel {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NonUniqueLabels(_) => "ngff::image_label::non_unique_label",
+            Self::Version(_) => "ngff::image_label::version",
+            Self::Dtype(..) | Self::MissingDtype(_) => "ngff::image_label::dtype",
+        }
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        super::spec_url("label-md")
+    }
+}
+
+impl_spec_diagnostic!(InvalidImageLabel);
+
+fn check_unique_labels<I: Iterator<Item = LabelType>>(labels: I) -> Result<(), InvalidImageLabel> {
+    let mut seen = HashSet::new();
+    for label in labels {
+        if !seen.insert(label) {
+            return Err(InvalidImageLabel::NonUniqueLabels(label));
+        }
+    }
+    Ok(())
 }
 
 impl ImageLabel {
     pub fn validate(&self) -> Result<(), InvalidImageLabel> {
-        let lcs = self.label_colors();
-        if !lcs.is_empty() && self.colors.as_ref().unwrap().len() != lcs.len() {
-            return Err(InvalidImageLabel::NonUniqueLabels);
+        if let Some(v) = &self.version {
+            if v != super::VERSION {
+                return Err(InvalidImageLabel::Version(v.clone()));
+            }
         }
+        let colors = self.colors.iter().flatten().map(|c| c.label_value);
+        check_unique_labels(colors)?;
+        let properties = self.properties.iter().flatten().map(|p| p.label_value);
+        check_unique_labels(properties)
+    }
 
-        let lps = self.label_properties();
-        if !lps.is_empty() && self.properties.as_ref().unwrap().len() != lps.len() {
-            return Err(InvalidImageLabel::NonUniqueLabels);
+    /// Apply canonical forms: sort colors and properties by label value,
+    /// and drop empty lists.
+    pub fn normalize(&mut self) {
+        if let Some(cols) = self.colors.as_mut() {
+            cols.sort_by_key(|c| c.label_value);
+        }
+        if let Some(props) = self.properties.as_mut() {
+            props.sort_by_key(|p| p.label_value);
+        }
+        if self.colors.as_ref().is_some_and(Vec::is_empty) {
+            self.colors = None;
+        }
+        if self.properties.as_ref().is_some_and(Vec::is_empty) {
+            self.properties = None;
+        }
+    }
+
+    /// Check that the zarr data type of the label array at `path` is an integer type,
+    /// e.g. `"<u2"` (zarr v2) or `"uint16"` (zarr v3).
+    pub fn validate_dtype(path: &str, dtype: &str) -> Result<(), InvalidImageLabel> {
+        const V2: [&str; 8] = ["i1", "i2", "i4", "i8", "u1", "u2", "u4", "u8"];
+        const V3: [&str; 8] = [
+            "int8", "int16", "int32", "int64", "uint8", "uint16", "uint32", "uint64",
+        ];
+        let is_int = match dtype.strip_prefix(['<', '>', '|', '=']) {
+            Some(t) => V2.contains(&t),
+            None => V3.contains(&dtype),
+        };
+        if !is_int {
+            return Err(InvalidImageLabel::Dtype(path.to_owned(), dtype.to_owned()));
         }
         Ok(())
     }
 
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Set the version to [super::VERSION] if it is missing.
+    pub fn fill_version(&mut self) {
+        self.version
+            .get_or_insert_with(|| super::VERSION.to_owned());
+    }
+
+    /// Label values with their RGBA colors, in document order; labels without a color are skipped.
+    pub fn colors(&self) -> impl Iterator<Item = (LabelType, &[u8; 4])> + '_ {
+        self.colors
+            .iter()
+            .flatten()
+            .filter_map(|c| c.rgba.as_ref().map(|rgba| (c.label_value, rgba)))
+    }
+
+    /// Label values with their properties, in document order.
+    pub fn properties(&self) -> impl Iterator<Item = (LabelType, &HashMap<String, Value>)> + '_ {
+        self.properties
+            .iter()
+            .flatten()
+            .map(|p| (p.label_value, &p.metadata))
+    }
+
+    /// The color of a single label, if it has one.
+    ///
+    /// If a label value is repeated (which fails validation), its first color is used,
+    /// as by every other color lookup.
+    pub fn color_for(&self, label: LabelType) -> Option<&[u8; 4]> {
+        self.colors()
+            .find_map(|(value, rgba)| (value == label).then_some(rgba))
+    }
+
+    /// The properties of a single label, if it has any.
+    pub fn properties_for(&self, label: LabelType) -> Option<&HashMap<String, Value>> {
+        self.properties()
+            .find_map(|(value, props)| (value == label).then_some(props))
+    }
+
+    /// Index the label colors for repeated lookups.
+    pub fn color_index(&self) -> LabelColorIndex {
+        LabelColorIndex::new(self)
+    }
+
+    /// All label colors by label value.
+    ///
+    /// For single lookups, use [ImageLabel::color_for] instead.
     pub fn label_colors(&self) -> HashMap<LabelType, &[u8; 4]> {
-        let Some(cols) = &self.colors else {
-            return HashMap::with_capacity(0);
-        };
-        cols.iter()
-            .fold(HashMap::with_capacity(cols.len()), |mut accum, el| {
-                if let Some(rgba) = el.rgba.as_ref() {
-                    accum.insert(el.label_value, rgba);
-                }
-                accum
-            })
+        first_by_label(self.colors())
     }
 
+    /// All label properties by label value.
+    ///
+    /// For single lookups, use [ImageLabel::properties_for] instead.
     pub fn label_properties(&self) -> HashMap<LabelType, &HashMap<String, Value>> {
-        let Some(props) = &self.properties else {
-            return HashMap::with_capacity(0);
-        };
-        props
-            .iter()
-            .fold(HashMap::with_capacity(props.len()), |mut accum, el| {
-                accum.insert(el.label_value, &el.metadata);
-                accum
-            })
+        first_by_label(self.properties())
+    }
+}
+
+/// Collect values by label, keeping the first of any repeated label.
+fn first_by_label<T>(items: impl Iterator<Item = (LabelType, T)>) -> HashMap<LabelType, T> {
+    let mut map = HashMap::new();
+    for (label, value) in items {
+        map.entry(label).or_insert(value);
+    }
+    map
+}
+
+/// Label colors indexed for fast repeated lookups, e.g. when rendering.
+///
+/// If every colored label value is small, colors are stored in a dense array indexed by label;
+/// otherwise in a hash map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelColorIndex {
+    Dense(Vec<Option<[u8; 4]>>),
+    Sparse(HashMap<LabelType, [u8; 4]>),
+}
+
+impl LabelColorIndex {
+    /// Labels up to this value are stored densely.
+    pub const MAX_DENSE_LABEL: LabelType = u16::MAX as LabelType;
+
+    pub fn new(image_label: &ImageLabel) -> Self {
+        let max = image_label.colors().map(|(label, _)| label).max();
+        match max {
+            Some(max) if max > Self::MAX_DENSE_LABEL => {
+                Self::Sparse(first_by_label(image_label.colors().map(|(l, c)| (l, *c))))
+            }
+            _ => {
+                let len = max.map_or(0, |m| m as usize + 1);
+                let mut dense = vec![None; len];
+                for (label, rgba) in image_label.colors() {
+                    dense[label as usize].get_or_insert(*rgba);
+                }
+                Self::Dense(dense)
+            }
+        }
+    }
+
+    /// The color of a label, if it has one.
+    #[inline]
+    pub fn rgba(&self, label: LabelType) -> Option<[u8; 4]> {
+        match self {
+            Self::Dense(colors) => usize::try_from(label)
+                .ok()
+                .and_then(|idx| colors.get(idx).copied().flatten()),
+            Self::Sparse(colors) => colors.get(&label).copied(),
+        }
+    }
+}
+
+impl Color {
+    pub fn label_value(&self) -> LabelType {
+        self.label_value
+    }
+
+    pub fn rgba(&self) -> Option<&[u8; 4]> {
+        self.rgba.as_ref()
     }
 }
 
@@ -80,6 +244,17 @@ pub struct Properties {
     metadata: HashMap<String, Value>,
 }
 
+impl Properties {
+    pub fn label_value(&self) -> LabelType {
+        self.label_value
+    }
+
+    /// The label's properties, other than its value.
+    pub fn metadata(&self) -> &HashMap<String, Value> {
+        &self.metadata
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Source {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -94,44 +269,124 @@ impl Default for Source {
     }
 }
 
+#[cfg(feature = "proptest")]
+mod arbitrary {
+    use super::*;
+    use crate::util::Validity;
+    use proptest::prelude::*;
+
+    impl Arbitrary for ImageLabel {
+        type Parameters = Validity;
+        type Strategy = BoxedStrategy<Self>;
+
+        /// Valid image-labels have unique label values.
+        fn arbitrary_with(validity: Validity) -> Self::Strategy {
+            let labels = match validity {
+                Validity::Valid => prop::collection::btree_set(any::<LabelType>(), 0..8)
+                    .prop_map(|s| s.into_iter().collect::<Vec<_>>())
+                    .boxed(),
+                Validity::Any => prop::collection::vec(0..4 as LabelType, 0..8).boxed(),
+            };
+            (labels.clone(), labels, prop::option::of(any::<[u8; 4]>()))
+                .prop_map(|(color_labels, prop_labels, rgba)| ImageLabel {
+                    version: Some(crate::v0_4::VERSION.to_owned()),
+                    colors: Some(
+                        color_labels
+                            .into_iter()
+                            .map(|label_value| Color { label_value, rgba })
+                            .collect(),
+                    ),
+                    properties: Some(
+                        prop_labels
+                            .into_iter()
+                            .map(|label_value| Properties {
+                                label_value,
+                                metadata: HashMap::default(),
+                            })
+                            .collect(),
+                    ),
+                    source: Some(Source::default()),
+                })
+                .boxed()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json;
 
-    const EXAMPLE: &str = r#"
-    {
-        "version": "0.4",
-        "colors": [
-        {
-            "label-value": 1,
-            "rgba": [255, 255, 255, 255]
-        },
-        {
-            "label-value": 4,
-            "rgba": [0, 255, 255, 128]
-        }
-        ],
-        "properties": [
-        {
-            "label-value": 1,
-            "area (pixels)": 1200,
-            "class": "foo"
-        },
-        {
-            "label-value": 4,
-            "area (pixels)": 1650
+    #[test]
+    fn dtype() {
+        for dtype in ["<u2", "|i1", ">i8", "=u4", "uint8", "int32", "uint64"] {
+            ImageLabel::validate_dtype("0", dtype).unwrap();
         }
-        ],
-        "source": {
-        "image": "../../"
+        for dtype in [
+            "<f4", "|b1", "float32", "bool", "u2", "<i3", "<u16", "int7", "uint3", "uint128",
+        ] {
+            assert_eq!(
+                ImageLabel::validate_dtype("0", dtype),
+                Err(InvalidImageLabel::Dtype("0".to_owned(), dtype.to_owned()))
+            );
         }
     }
-    "#;
+
+    /// The spec's image-label example, from the corpus.
+    fn example() -> ImageLabel {
+        let fixture = crate::v0_4::corpus::fixture("colors_properties").unwrap();
+        fixture.parse().unwrap().image_label().unwrap().clone()
+    }
 
     #[test]
     fn test_example() {
-        let im: ImageLabel = serde_json::from_str(EXAMPLE).unwrap();
+        let im: ImageLabel = example();
         im.validate().unwrap();
     }
+
+    #[test]
+    fn lookups() {
+        let im: ImageLabel = example();
+        let labels: Vec<_> = im.colors().map(|(label, _)| label).collect();
+        assert_eq!(labels, [1, 4]);
+        assert_eq!(im.color_for(4), Some(&[0, 255, 255, 128]));
+        assert_eq!(im.color_for(2), None);
+        assert_eq!(im.properties_for(1).unwrap()["class"], "foo");
+        assert_eq!(im.properties().count(), 2);
+        assert_eq!(im.label_colors().len(), 2);
+    }
+
+    #[test]
+    fn color_index() {
+        let im: ImageLabel = example();
+        let index = im.color_index();
+        assert!(matches!(&index, LabelColorIndex::Dense(c) if c.len() == 5));
+        assert_eq!(index.rgba(4), Some([0, 255, 255, 128]));
+        assert_eq!(index.rgba(2), None);
+        assert_eq!(index.rgba(LabelType::MAX), None);
+
+        let im: ImageLabel = serde_json::from_str(
+            r#"{"colors": [{"label-value": 4294967296, "rgba": [1, 2, 3, 4]}]}"#,
+        )
+        .unwrap();
+        let index = im.color_index();
+        assert!(matches!(index, LabelColorIndex::Sparse(_)));
+        assert_eq!(index.rgba(4294967296), Some([1, 2, 3, 4]));
+        assert_eq!(index.rgba(1), None);
+    }
+
+    #[test]
+    fn duplicate_colors() {
+        for label in [3, 4294967296] {
+            let im: ImageLabel = serde_json::from_value(serde_json::json!({"colors": [
+                {"label-value": label, "rgba": [1, 1, 1, 1]},
+                {"label-value": label, "rgba": [2, 2, 2, 2]}
+            ]}))
+            .unwrap();
+            assert!(im.validate().is_err());
+            assert_eq!(im.color_for(label), Some(&[1, 1, 1, 1]));
+            assert_eq!(im.color_index().rgba(label), Some([1, 1, 1, 1]));
+            assert_eq!(im.label_colors()[&label], &[1, 1, 1, 1]);
+        }
+    }
 }
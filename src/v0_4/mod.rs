@@ -1,21 +1,191 @@
+use std::collections::HashSet;
+
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::diff::{diff, Change};
+use crate::store::{join_key, InvalidHierarchy, LoadError, Progress, ReadableStore};
+use crate::util::{
+    impl_json_io, impl_spec_diagnostic, SpecError, ToCanonicalJson, Validate, Validated,
+};
 
 mod axes;
-mod coordinate_transformations;
+pub mod bioformats2raw;
+mod chunks;
+#[cfg(any(test, feature = "corpus"))]
+pub mod corpus;
+#[cfg(feature = "export")]
+pub mod export;
+mod generator;
+#[cfg(feature = "zarrs")]
+mod image;
 mod image_label;
 mod multiscale;
+#[cfg(feature = "async")]
+mod nonblocking;
+#[cfg(feature = "ome_xml")]
+mod ome_xml;
+mod omero;
+mod patch;
 mod plate;
+mod plate_index;
+mod region;
+#[cfg(feature = "udunits")]
+mod udunits;
+mod validation;
 mod well;
+mod write;
 
-pub use axes::{Axis, CoreAxis, InvalidAxes, SpaceUnit, TimeUnit};
-pub use coordinate_transformations::{
-    CoordinateTransformation, InvalidCoordinateTransforms, ScaleOrPath, Transform,
-    TranslationOrPath,
+#[cfg(feature = "proptest")]
+pub use crate::core::{arb_axes, arb_transforms};
+pub use crate::core::{
+    simplify, Axis, AxisStrictness, AxisType, AxisUnit, CoordinateTransformation, CoreAxis,
+    Dimension, InvalidAxes, InvalidCoordinateTransforms, MultiscaleDataset, Rounding, ScaleOrPath,
+    SpaceUnit, TimeUnit, Transform, TransformError, TranslationOrPath, Unit,
 };
-pub use image_label::{Color, ImageLabel, InvalidImageLabel, Properties, Source};
-pub use multiscale::{InvalidMultiscale, Multiscale, MultiscaleDataset};
-pub use plate::{Acquisition, AcquisitionId, Index, InvalidPlate, Plate, PlateWell};
+pub use bioformats2raw::Bioformats2Raw;
+pub use chunks::{ChunkLocation, InvalidChunks};
+pub use generator::GeneratorMetadata;
+#[cfg(feature = "zarrs")]
+pub use image::{Image, ImageError, LevelSelection};
+pub use image_label::{Color, ImageLabel, InvalidImageLabel, LabelColorIndex, Properties, Source};
+pub use multiscale::{InvalidMultiscale, Multiscale};
+#[cfg(feature = "ome_xml")]
+pub use ome_xml::{OmeChannel, OmeImage, OmePlate, OmeWell, OmeXml, OmeXmlError};
+pub use omero::{Channel, ChannelStats, InvalidOmero, Omero, Window};
+pub use patch::{Patch, PatchError};
+pub use plate::{
+    column_label, parse_column_label, parse_row_label, parse_well_path, row_label, Acquisition,
+    AcquisitionBuilder, AcquisitionId, GridCharset, Index, InvalidPlate, Plate, PlateReader,
+    PlateSummary, PlateWell,
+};
+pub use plate_index::PlateIndex;
+pub use region::VoxelRegion;
+#[cfg(feature = "udunits")]
+pub use udunits::{validate_unit, InvalidUnit};
+pub use validation::{InvalidGroup, ValidatorConfig, Warning};
 pub use well::{FieldOfView, InvalidWell, Well};
+pub use write::{write_plate, ArrayPlaceholder, DryRun, Transaction, WriteError, WritePlan};
+
+impl ToCanonicalJson for NgffMetadata {}
+impl ToCanonicalJson for Multiscale {}
+impl ToCanonicalJson for ImageLabel {}
+impl ToCanonicalJson for Omero {}
+impl ToCanonicalJson for Plate {}
+impl ToCanonicalJson for Well {}
+
+impl Validate for NgffMetadata {
+    type Error = InvalidNgffMetadata;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        NgffMetadata::validate(self)
+    }
+}
+
+impl Validate for Multiscale {
+    type Error = InvalidMultiscale;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        Multiscale::validate(self)
+    }
+}
+
+impl Validate for ImageLabel {
+    type Error = InvalidImageLabel;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        ImageLabel::validate(self)
+    }
+}
+
+impl Validate for Omero {
+    type Error = InvalidOmero;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        Omero::validate(self)
+    }
+}
+
+impl Validate for Plate {
+    type Error = InvalidPlate;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        Plate::validate(self)
+    }
+}
+
+impl Validate for Well {
+    type Error = InvalidWell;
+
+    fn validate(&self) -> Result<(), Self::Error> {
+        Well::validate(self, None)
+    }
+}
+
+impl_json_io!(NgffMetadata);
+impl_json_io!(Multiscale);
+impl_json_io!(ImageLabel);
+impl_json_io!(Plate);
+impl_json_io!(Well);
+
+/// The key of a zarr v2 group's attributes, relative to the group.
+pub const ATTRS_KEY: &str = ".zattrs";
+/// The key of a zarr v2 array's metadata, relative to the array.
+pub const ARRAY_KEY: &str = ".zarray";
+/// The key of a zarr v2 group's metadata, relative to the group.
+pub const GROUP_KEY: &str = ".zgroup";
+
+/// The spec version implemented by this module.
+pub const VERSION: &str = "0.4";
+
+/// This version of the specification as a type, for code generic over [crate::Spec] versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct V0_4;
+
+impl crate::version::sealed::Sealed for V0_4 {}
+
+impl crate::Spec for V0_4 {
+    const VERSION: crate::NgffVersion = crate::NgffVersion::V0_4;
+
+    type Metadata = NgffMetadata;
+    type Multiscale = Multiscale;
+    type ImageLabel = ImageLabel;
+    type Omero = Omero;
+    type Plate = Plate;
+    type Well = Well;
+}
+
+/// Link to a section of this version of the specification.
+pub(crate) fn spec_url(fragment: &str) -> Option<String> {
+    Some(format!(
+        "https://ngff.openmicroscopy.org/{VERSION}/#{fragment}"
+    ))
+}
+
+/// How to treat missing `version` fields when serializing.
+///
+/// Serializing with serde (including `to_writer` and `to_string_pretty`) always injects versions;
+/// use [NgffMetadata::to_value] to preserve them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionPolicy {
+    /// Fill missing `version` fields with [VERSION].
+    #[default]
+    Inject,
+    /// Serialize exactly what is present, e.g. for round-tripping.
+    Preserve,
+}
+
+/// Serialize a missing `version` field as [VERSION].
+fn serialize_version<T, S>(version: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: serde::Serializer,
+{
+    match version {
+        Some(v) => v.serialize(serializer),
+        None => VERSION.serialize(serializer),
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NgffMetadata {
@@ -26,9 +196,1180 @@ pub struct NgffMetadata {
     #[serde(rename = "image-label", skip_serializing_if = "Option::is_none")]
     image_label: Option<ImageLabel>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    omero: Option<Omero>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     plate: Option<Plate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     well: Option<Well>,
 }
 
 // todo: partial deser for when we know which bits to look for
+
+impl NgffMetadata {
+    pub fn builder() -> NgffMetadataBuilder {
+        NgffMetadataBuilder::default()
+    }
+
+    pub fn multiscales(&self) -> Option<&[Multiscale]> {
+        self.multiscales.as_deref()
+    }
+
+    pub fn labels(&self) -> Option<&[String]> {
+        self.labels.as_deref()
+    }
+
+    pub fn image_label(&self) -> Option<&ImageLabel> {
+        self.image_label.as_ref()
+    }
+
+    pub fn omero(&self) -> Option<&Omero> {
+        self.omero.as_ref()
+    }
+
+    /// The rendering metadata, for editing, e.g. with [Omero::fill_windows].
+    pub fn omero_mut(&mut self) -> Option<&mut Omero> {
+        self.omero.as_mut()
+    }
+
+    pub fn plate(&self) -> Option<&Plate> {
+        self.plate.as_ref()
+    }
+
+    pub fn well(&self) -> Option<&Well> {
+        self.well.as_ref()
+    }
+
+    /// Parse JSON, first repairing any non-standard values which the config allows
+    /// (see [ValidatorConfig::lenient_times] and [ValidatorConfig::iso_times])
+    /// and returning a warning for each.
+    ///
+    /// Repaired values are stored, and so written, in their standard form.
+    pub fn from_slice_with(
+        bytes: &[u8],
+        config: &ValidatorConfig,
+    ) -> Result<(Self, Vec<Warning>), crate::JsonError> {
+        let read_err = |e| crate::JsonError::Read("NgffMetadata", e);
+        if !(config.lenient_times || config.iso_times) {
+            return Ok((Self::from_slice(bytes)?, Vec::default()));
+        }
+        let mut value: serde_json::Value = serde_json::from_slice(bytes).map_err(read_err)?;
+        let warnings = value
+            .get_mut("plate")
+            .map(|p| plate::repair_acquisition_times(p, config))
+            .unwrap_or_default();
+        Ok((serde_json::from_value(value).map_err(read_err)?, warnings))
+    }
+
+    /// Read the attributes of the zarr group at `group`.
+    pub fn from_store<S: ReadableStore + ?Sized>(
+        store: &S,
+        group: &str,
+    ) -> Result<Self, LoadError> {
+        let key = join_key([group, ATTRS_KEY]);
+        let bytes = store.get(&key)?.ok_or(LoadError::NotFound(key))?;
+        Ok(Self::from_slice(&bytes)?)
+    }
+
+    /// Validate every metadata object present in the document.
+    pub fn validate(&self) -> Result<(), InvalidNgffMetadata> {
+        self.validate_with(&ValidatorConfig::default()).map(|_| ())
+    }
+
+    /// Validate every metadata object present in the document according to the config,
+    /// returning any warnings.
+    pub fn validate_with(
+        &self,
+        config: &ValidatorConfig,
+    ) -> Result<Vec<Warning>, InvalidNgffMetadata> {
+        let mut warnings = Vec::default();
+        if self.image_label.is_some() && self.multiscales.is_none() {
+            return Err(InvalidNgffMetadata::ImageLabelWithoutMultiscales);
+        }
+        if let Some(il_version) = self.image_label.as_ref().and_then(ImageLabel::version) {
+            let mut ms_versions = self
+                .multiscales
+                .iter()
+                .flatten()
+                .filter_map(Multiscale::version);
+            if let Some(v) = ms_versions.find(|v| *v != il_version) {
+                return Err(InvalidNgffMetadata::ImageLabelVersion(
+                    il_version.to_owned(),
+                    v.to_owned(),
+                ));
+            }
+        }
+        if let Some(mss) = &self.multiscales {
+            for ms in mss.iter() {
+                warnings.extend(ms.validate_with(config)?);
+            }
+        }
+        if let Some(il) = &self.image_label {
+            il.validate()?;
+        }
+        if let Some(o) = &self.omero {
+            o.validate()?;
+            warnings.extend(o.warnings());
+        }
+        if let Some(p) = &self.plate {
+            p.validate()?;
+        }
+        if let Some(w) = &self.well {
+            w.validate(None)?;
+        }
+        Ok(warnings)
+    }
+
+    /// Read and validate the group at `group` according to the config,
+    /// returning the [Validated] document and any warnings.
+    ///
+    /// Unlike [NgffMetadata::validate_with], this can check that referenced nodes exist
+    /// and cross-check a plate against its wells' metadata.
+    pub fn validate_group<S: ReadableStore + ?Sized>(
+        store: &S,
+        group: &str,
+        config: &ValidatorConfig,
+    ) -> Result<(Validated<Self>, Vec<Warning>), InvalidGroup> {
+        Self::validate_group_with_progress(store, group, config, |_| ())
+    }
+
+    /// As [NgffMetadata::validate_group], calling `progress` after the group,
+    /// and after each of a plate's wells and their images is checked.
+    pub fn validate_group_with_progress<S, F>(
+        store: &S,
+        group: &str,
+        config: &ValidatorConfig,
+        progress: F,
+    ) -> Result<(Validated<Self>, Vec<Warning>), InvalidGroup>
+    where
+        S: ReadableStore + ?Sized,
+        F: FnMut(Progress),
+    {
+        let node = Self::validate_node(store, group, config)?;
+        let (mut check, wells) = GroupCheck::new(group, node, config, progress);
+        for well in wells {
+            let images = check.well(&well, Self::from_store(store, &well))?;
+            for image in images {
+                check.image(&image, Self::validate_node(store, &image, config))?;
+            }
+        }
+        Ok(check.finish())
+    }
+
+    /// Read and validate the group at `group`, without cross-checking a plate's wells.
+    fn validate_node<S: ReadableStore + ?Sized>(
+        store: &S,
+        group: &str,
+        config: &ValidatorConfig,
+    ) -> Result<(Self, Vec<Warning>), InvalidGroup> {
+        let key = join_key([group, ATTRS_KEY]);
+        let bytes = store.get(&key).map_err(LoadError::from)?;
+        let (meta, warnings) = Self::parse_node(key, bytes, config)?;
+        if config.store_checks {
+            meta.validate_store(store, group)?;
+            for (path, zarray) in meta.label_arrays(group) {
+                let bytes = store.get(&zarray).map_err(LoadError::from)?;
+                check_label_dtype(&path, bytes)?;
+            }
+        }
+        Ok((meta, warnings))
+    }
+
+    /// Parse and validate the attributes read from `key`, without checking the store.
+    fn parse_node(
+        key: String,
+        bytes: Option<Vec<u8>>,
+        config: &ValidatorConfig,
+    ) -> Result<(Self, Vec<Warning>), InvalidGroup> {
+        let bytes = bytes.ok_or(LoadError::NotFound(key))?;
+        let (meta, mut warnings) =
+            Self::from_slice_with(&bytes, config).map_err(LoadError::from)?;
+        warnings.extend(meta.validate_with(config)?);
+        Ok((meta, warnings))
+    }
+
+    /// The path and array metadata key of every dataset of a label image at `group`.
+    fn label_arrays(&self, group: &str) -> Vec<(String, String)> {
+        if self.image_label.is_none() {
+            return Vec::default();
+        }
+        self.multiscales
+            .iter()
+            .flatten()
+            .flat_map(Multiscale::datasets)
+            .map(|ds| {
+                let path = join_key([group, ds.path()]);
+                let key = join_key([path.as_str(), ARRAY_KEY]);
+                (path, key)
+            })
+            .collect()
+    }
+
+    /// Check that the nodes this document (at the group `group`) refers to exist in the store:
+    /// every multiscale dataset must be an array, and every listed label must be a group.
+    pub fn validate_store<S: ReadableStore + ?Sized>(
+        &self,
+        store: &S,
+        group: &str,
+    ) -> Result<(), InvalidHierarchy> {
+        for ms in self.multiscales.iter().flatten() {
+            ms.validate_store(store, group)?;
+        }
+        for label in self.labels.iter().flatten() {
+            let path = join_key([group, label.as_str()]);
+            if !store.contains(&join_key([path.as_str(), GROUP_KEY]))? {
+                return Err(InvalidHierarchy::MissingGroup(path));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply canonical forms to every object in the document
+    /// (see [Multiscale::normalize], [Plate::normalize], [ImageLabel::normalize]),
+    /// dropping empty multiscale and label lists,
+    /// so that equivalent documents serialize identically.
+    pub fn normalize(&mut self) {
+        if let Some(mss) = self.multiscales.as_mut() {
+            mss.iter_mut().for_each(Multiscale::normalize);
+        }
+        if let Some(il) = self.image_label.as_mut() {
+            il.normalize();
+        }
+        if let Some(p) = self.plate.as_mut() {
+            p.normalize();
+        }
+        if self.multiscales.as_ref().is_some_and(Vec::is_empty) {
+            self.multiscales = None;
+        }
+        if self.labels.as_ref().is_some_and(Vec::is_empty) {
+            self.labels = None;
+        }
+    }
+
+    /// Replace unrecognised axis units with their suggested known units where possible,
+    /// returning a warning for each replacement.
+    pub fn repair_units(&mut self) -> Vec<Warning> {
+        self.multiscales
+            .iter_mut()
+            .flatten()
+            .flat_map(Multiscale::repair_units)
+            .collect()
+    }
+
+    /// List the structural differences from another document.
+    pub fn diff(&self, other: &Self) -> serde_json::Result<Vec<Change>> {
+        diff(self, other)
+    }
+
+    /// Parse and validate JSON text,
+    /// reporting any failure with its location in the text.
+    #[cfg(feature = "miette")]
+    pub fn from_str_diagnostic(
+        name: &str,
+        json: &str,
+    ) -> Result<Self, Box<crate::diagnostic::SourceDiagnostic>> {
+        use crate::diagnostic::SourceDiagnostic;
+
+        let meta = Self::from_slice(json.as_bytes()).map_err(|e| {
+            let (line, column) = e.line_column();
+            Box::new(SourceDiagnostic::at_line_column(
+                name, json, line, column, e,
+            ))
+        })?;
+        let at = |pointer: String, e: InvalidNgffMetadata| {
+            Box::new(SourceDiagnostic::at_pointer(name, json, &pointer, e))
+        };
+        for (idx, ms) in meta.multiscales.iter().flatten().enumerate() {
+            ms.validate()
+                .map_err(|e| at(format!("/multiscales/{idx}"), e.into()))?;
+        }
+        if let Some(il) = &meta.image_label {
+            il.validate()
+                .map_err(|e| at("/image-label".into(), e.into()))?;
+        }
+        if let Some(o) = &meta.omero {
+            o.validate().map_err(|e| at("/omero".into(), e.into()))?;
+        }
+        if let Some(p) = &meta.plate {
+            p.validate().map_err(|e| at("/plate".into(), e.into()))?;
+        }
+        if let Some(w) = &meta.well {
+            w.validate(None).map_err(|e| at("/well".into(), e.into()))?;
+        }
+        meta.validate().map_err(|e| at(String::new(), e))?;
+        Ok(meta)
+    }
+
+    /// Set any missing `version` fields to [VERSION].
+    pub fn fill_versions(&mut self) {
+        if let Some(mss) = self.multiscales.as_mut() {
+            mss.iter_mut().for_each(Multiscale::fill_version);
+        }
+        if let Some(il) = self.image_label.as_mut() {
+            il.fill_version();
+        }
+        if let Some(o) = self.omero.as_mut() {
+            o.fill_version();
+        }
+        if let Some(p) = self.plate.as_mut() {
+            p.fill_version();
+        }
+        if let Some(w) = self.well.as_mut() {
+            w.fill_version();
+        }
+    }
+
+    /// Serialize to a JSON value, handling missing versions according to the policy.
+    pub fn to_value(&self, policy: VersionPolicy) -> serde_json::Result<serde_json::Value> {
+        let mut value = serde_json::to_value(self)?;
+        if policy == VersionPolicy::Preserve {
+            let mut remove = |pointer: String, missing: bool| {
+                if let Some(obj) = value.pointer_mut(&pointer).and_then(|v| v.as_object_mut()) {
+                    if missing {
+                        obj.remove("version");
+                    }
+                }
+            };
+            for (idx, ms) in self.multiscales.iter().flatten().enumerate() {
+                remove(format!("/multiscales/{idx}"), !ms.has_version());
+            }
+            if let Some(il) = &self.image_label {
+                remove("/image-label".into(), il.version().is_none());
+            }
+            if let Some(o) = &self.omero {
+                remove("/omero".into(), !o.has_version());
+            }
+            if let Some(p) = &self.plate {
+                remove("/plate".into(), p.version().is_none());
+            }
+            if let Some(w) = &self.well {
+                remove("/well".into(), w.version().is_none());
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// The cross-checks and progress reporting of [NgffMetadata::validate_group_with_progress],
+/// shared with its async version so that only reading from the store differs.
+struct GroupCheck<'a, F> {
+    meta: NgffMetadata,
+    warnings: Vec<Warning>,
+    config: &'a ValidatorConfig,
+    acquisitions: Option<HashSet<AcquisitionId>>,
+    done: usize,
+    total: usize,
+    progress: F,
+}
+
+impl<'a, F: FnMut(Progress)> GroupCheck<'a, F> {
+    /// Start from the validated group at `group`,
+    /// returning the paths of the wells to check in plate order.
+    fn new(
+        group: &str,
+        (meta, warnings): (NgffMetadata, Vec<Warning>),
+        config: &'a ValidatorConfig,
+        mut progress: F,
+    ) -> (Self, Vec<String>) {
+        let wells: Vec<_> = match (&meta.plate, config.hcs_cross_refs) {
+            (Some(plate), true) => plate
+                .wells()
+                .iter()
+                .map(|pw| join_key([group, pw.path()]))
+                .collect(),
+            _ => Vec::default(),
+        };
+        let acquisitions = meta
+            .plate
+            .as_ref()
+            .and_then(|p| p.acquisitions().map(|_| p.acquisition_ids()));
+        let total = 1 + wells.len();
+        progress(Progress {
+            group,
+            done: 1,
+            total,
+        });
+        let check = Self {
+            meta,
+            warnings,
+            config,
+            acquisitions,
+            done: 1,
+            total,
+            progress,
+        };
+        (check, wells)
+    }
+
+    /// Check the well loaded from `path`, returning the paths of its images to check.
+    fn well(
+        &mut self,
+        path: &str,
+        loaded: Result<NgffMetadata, LoadError>,
+    ) -> Result<Vec<String>, InvalidGroup> {
+        let well = loaded?
+            .well
+            .ok_or_else(|| LoadError::MissingMetadata(path.to_owned(), "well"))?;
+        well.validate_against(self.acquisitions.as_ref())
+            .map_err(|e| InvalidGroup::Well(path.to_owned(), e))?;
+        let images = match self.config.store_checks {
+            true => well.image_paths(path),
+            false => Vec::default(),
+        };
+        self.total += images.len();
+        self.report(path);
+        Ok(images)
+    }
+
+    /// Record the result of validating the image at `path`.
+    fn image(
+        &mut self,
+        path: &str,
+        validated: Result<(NgffMetadata, Vec<Warning>), InvalidGroup>,
+    ) -> Result<(), InvalidGroup> {
+        let (_, warnings) =
+            validated.map_err(|e| InvalidGroup::Image(path.to_owned(), Box::new(e)))?;
+        self.warnings.extend(warnings);
+        self.report(path);
+        Ok(())
+    }
+
+    fn report(&mut self, group: &str) {
+        self.done += 1;
+        (self.progress)(Progress {
+            group,
+            done: self.done,
+            total: self.total,
+        });
+    }
+
+    fn finish(self) -> (Validated<NgffMetadata>, Vec<Warning>) {
+        (Validated::assume_valid(self.meta), self.warnings)
+    }
+}
+
+/// Check the data type in a label array's (zarr v2) metadata, if present.
+fn check_label_dtype(path: &str, zarray: Option<Vec<u8>>) -> Result<(), InvalidGroup> {
+    let Some(bytes) = zarray else {
+        return Ok(());
+    };
+    let meta: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&bytes)
+        .map_err(|e| LoadError::from(crate::JsonError::Read(ARRAY_KEY, e)))?;
+    let result = match meta.get("dtype") {
+        Some(serde_json::Value::String(dtype)) => ImageLabel::validate_dtype(path, dtype),
+        // e.g. structured data types
+        Some(other) => Err(InvalidImageLabel::Dtype(path.to_owned(), other.to_string())),
+        None => Err(InvalidImageLabel::MissingDtype(path.to_owned())),
+    };
+    result.map_err(InvalidNgffMetadata::from)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidNgffMetadata {
+    #[error("No metadata given")]
+    Empty,
+    #[error("Group has \"image-label\" metadata but is missing the required \"multiscales\" key")]
+    ImageLabelWithoutMultiscales,
+    #[error("Incompatible metadata: {0} and {1}")]
+    Incompatible(&'static str, &'static str),
+    #[error("Image-label version {0} does not match multiscales version {1}")]
+    ImageLabelVersion(String, String),
+    #[error(transparent)]
+    Multiscale(#[from] InvalidMultiscale),
+    #[error(transparent)]
+    ImageLabel(#[from] InvalidImageLabel),
+    #[error(transparent)]
+    Omero(#[from] InvalidOmero),
+    #[error(transparent)]
+    Plate(#[from] InvalidPlate),
+    #[error(transparent)]
+    Well(#[from] InvalidWell),
+}
+
+impl SpecError for InvalidNgffMetadata {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Empty => "ngff::metadata::empty",
+            Self::ImageLabelWithoutMultiscales => "ngff::metadata::image_label_without_multiscales",
+            Self::Incompatible(..) => "ngff::metadata::incompatible",
+            Self::ImageLabelVersion(..) => "ngff::metadata::image_label_version",
+            Self::Multiscale(e) => e.code(),
+            Self::ImageLabel(e) => e.code(),
+            Self::Omero(e) => e.code(),
+            Self::Plate(e) => e.code(),
+            Self::Well(e) => e.code(),
+        }
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        match self {
+            Self::Empty | Self::Incompatible(..) => spec_url("metadata"),
+            Self::ImageLabelWithoutMultiscales | Self::ImageLabelVersion(..) => {
+                spec_url("label-md")
+            }
+            Self::Multiscale(e) => e.spec_url(),
+            Self::ImageLabel(e) => e.spec_url(),
+            Self::Omero(e) => e.spec_url(),
+            Self::Plate(e) => e.spec_url(),
+            Self::Well(e) => e.spec_url(),
+        }
+    }
+}
+
+impl_spec_diagnostic!(InvalidNgffMetadata);
+
+/// Assembles an [NgffMetadata] document,
+/// checking at [NgffMetadataBuilder::build] that the given keys can share a group.
+#[derive(Debug, Clone, Default)]
+pub struct NgffMetadataBuilder {
+    multiscales: Option<Vec<Multiscale>>,
+    labels: Option<Vec<String>>,
+    image_label: Option<ImageLabel>,
+    omero: Option<Omero>,
+    plate: Option<Plate>,
+    well: Option<Well>,
+}
+
+impl NgffMetadataBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a multiscale image.
+    pub fn with_multiscale(mut self, multiscale: Multiscale) -> Self {
+        self.multiscales
+            .get_or_insert_with(Vec::new)
+            .push(multiscale);
+        self
+    }
+
+    /// Replace all multiscale images.
+    pub fn with_multiscales(mut self, multiscales: Vec<Multiscale>) -> Self {
+        self.multiscales = Some(multiscales);
+        self
+    }
+
+    /// Append the path of a label image.
+    pub fn with_label<S: Into<String>>(mut self, label: S) -> Self {
+        self.labels.get_or_insert_with(Vec::new).push(label.into());
+        self
+    }
+
+    /// Replace all label image paths.
+    pub fn with_labels(mut self, labels: Vec<String>) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+
+    pub fn with_image_label(mut self, image_label: ImageLabel) -> Self {
+        self.image_label = Some(image_label);
+        self
+    }
+
+    pub fn with_omero(mut self, omero: Omero) -> Self {
+        self.omero = Some(omero);
+        self
+    }
+
+    pub fn with_plate(mut self, plate: Plate) -> Self {
+        self.plate = Some(plate);
+        self
+    }
+
+    pub fn with_well(mut self, well: Well) -> Self {
+        self.well = Some(well);
+        self
+    }
+
+    /// Check that the given keys can co-exist in a single group.
+    ///
+    /// A group may be an image (multiscales, optionally with image-label),
+    /// a labels collection, a plate, or a well.
+    pub fn build(self) -> Result<NgffMetadata, InvalidNgffMetadata> {
+        let present = [
+            ("multiscales", self.multiscales.is_some()),
+            ("labels", self.labels.is_some()),
+            ("plate", self.plate.is_some()),
+            ("well", self.well.is_some()),
+        ];
+        let mut kinds = present.iter().filter(|(_, p)| *p).map(|(k, _)| *k);
+        match (kinds.next(), kinds.next()) {
+            (None, _) => {
+                if self.image_label.is_some() {
+                    return Err(InvalidNgffMetadata::ImageLabelWithoutMultiscales);
+                }
+                return Err(InvalidNgffMetadata::Empty);
+            }
+            (Some(k1), Some(k2)) => return Err(InvalidNgffMetadata::Incompatible(k1, k2)),
+            (Some(_), None) => (),
+        }
+        if self.image_label.is_some() && self.multiscales.is_none() {
+            return Err(InvalidNgffMetadata::ImageLabelWithoutMultiscales);
+        }
+
+        Ok(NgffMetadata {
+            multiscales: self.multiscales,
+            labels: self.labels,
+            image_label: self.image_label,
+            omero: self.omero,
+            plate: self.plate,
+            well: self.well,
+        })
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod arbitrary {
+    use super::*;
+    use crate::util::Validity;
+    use proptest::prelude::*;
+
+    impl Arbitrary for NgffMetadata {
+        type Parameters = Validity;
+        type Strategy = BoxedStrategy<Self>;
+
+        /// Valid documents describe exactly one of an image (optionally a label image),
+        /// a labels collection, a plate, or a well.
+        fn arbitrary_with(validity: Validity) -> Self::Strategy {
+            let empty = NgffMetadata {
+                multiscales: None,
+                labels: None,
+                image_label: None,
+                omero: None,
+                plate: None,
+                well: None,
+            };
+            let labels = prop::collection::vec("[a-z]{1,8}", 0..4);
+            match validity {
+                Validity::Valid => prop_oneof![
+                    (
+                        any_with::<Multiscale>(validity),
+                        prop::option::of(any_with::<ImageLabel>(validity))
+                    )
+                        .prop_map({
+                            let empty = empty.clone();
+                            move |(ms, image_label)| NgffMetadata {
+                                multiscales: Some(vec![ms]),
+                                image_label,
+                                ..empty.clone()
+                            }
+                        }),
+                    labels.prop_map({
+                        let empty = empty.clone();
+                        move |l| NgffMetadata {
+                            labels: Some(l),
+                            ..empty.clone()
+                        }
+                    }),
+                    any_with::<Plate>(validity).prop_map({
+                        let empty = empty.clone();
+                        move |p| NgffMetadata {
+                            plate: Some(p),
+                            ..empty.clone()
+                        }
+                    }),
+                    any_with::<Well>(validity).prop_map(move |w| NgffMetadata {
+                        well: Some(w),
+                        ..empty.clone()
+                    }),
+                ]
+                .boxed(),
+                Validity::Any => (
+                    prop::option::of(prop::collection::vec(
+                        any_with::<Multiscale>(validity),
+                        0..2,
+                    )),
+                    prop::option::of(labels),
+                    prop::option::of(any_with::<ImageLabel>(validity)),
+                    prop::option::of(any_with::<Plate>(validity)),
+                    prop::option::of(any_with::<Well>(validity)),
+                )
+                    .prop_map(
+                        |(multiscales, labels, image_label, plate, well)| NgffMetadata {
+                            multiscales,
+                            labels,
+                            image_label,
+                            omero: None,
+                            plate,
+                            well,
+                        },
+                    )
+                    .boxed(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generic_spec() {
+        use crate::{Spec, SpecType};
+
+        fn valid_well<S: Spec>(json: &str) -> bool {
+            serde_json::from_str::<S::Well>(json).is_ok_and(|w| w.is_valid())
+        }
+
+        fn canonical<T: SpecType>(value: &T) -> String {
+            value.to_canonical_json().unwrap()
+        }
+
+        assert_eq!(V0_4::VERSION, crate::NgffVersion::V0_4);
+        assert!(valid_well::<V0_4>(r#"{"images": [{"path": "0"}]}"#));
+        assert!(!valid_well::<V0_4>(r#"{"images": [{"path": "a/b"}]}"#));
+        let ms = Multiscale::new_2d(1.0, 1.0).unwrap();
+        assert!(canonical(&ms).starts_with(r#"{"axes":"#));
+    }
+
+    #[test]
+    fn validated() {
+        use crate::{Validated, ValidatedError};
+
+        let valid: Well = serde_json::from_str(r#"{"images": [{"path": "0"}]}"#).unwrap();
+        let validated = valid.clone().into_validated().unwrap();
+        assert_eq!(validated.images().len(), 1);
+        assert_eq!(
+            serde_json::to_value(&validated).unwrap(),
+            serde_json::to_value(&valid).unwrap()
+        );
+
+        let invalid: Well = serde_json::from_str(r#"{"images": [{"path": "a/b"}]}"#).unwrap();
+        assert_eq!(
+            Validated::new(invalid).unwrap_err(),
+            InvalidWell::InvalidPath("a/b".to_owned())
+        );
+
+        let checked: Validated<Well> =
+            serde_json::from_str(r#"{"images": [{"path": "0"}]}"#).unwrap();
+        assert_eq!(checked.into_inner(), valid);
+        let err = serde_json::from_str::<Validated<Well>>(r#"{"images": [{"path": "a/b"}]}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("must be alphanumeric"));
+
+        let checked = Validated::<Well>::from_json_slice(br#"{"images": [{"path": "0"}]}"#);
+        assert_eq!(checked.unwrap().into_inner(), valid);
+        assert!(matches!(
+            Validated::<Well>::from_json_slice(br#"{"images": [{"path": "a/b"}]}"#),
+            Err(ValidatedError::Invalid(InvalidWell::InvalidPath(p))) if p == "a/b"
+        ));
+        assert!(matches!(
+            Validated::<Well>::from_json_slice(br#"{"images": 1}"#),
+            Err(ValidatedError::Deserialize(_))
+        ));
+    }
+
+    #[test]
+    fn builder_combinations() {
+        let well: Well = serde_json::from_str(r#"{"images": [{"path": "0"}]}"#).unwrap();
+        let image_label: ImageLabel = serde_json::from_str(r#"{"version": "0.4"}"#).unwrap();
+
+        let meta = NgffMetadata::builder()
+            .with_label("cells")
+            .with_label("nuclei")
+            .build()
+            .unwrap();
+        assert_eq!(meta.labels().unwrap().len(), 2);
+
+        assert_eq!(
+            NgffMetadata::builder().build().unwrap_err(),
+            InvalidNgffMetadata::Empty
+        );
+        assert_eq!(
+            NgffMetadata::builder()
+                .with_well(well)
+                .with_label("cells")
+                .build()
+                .unwrap_err(),
+            InvalidNgffMetadata::Incompatible("labels", "well")
+        );
+        assert_eq!(
+            NgffMetadata::builder()
+                .with_image_label(image_label)
+                .build()
+                .unwrap_err(),
+            InvalidNgffMetadata::ImageLabelWithoutMultiscales
+        );
+    }
+
+    #[test]
+    fn generic_validate() {
+        fn count_valid<T: Validate + ?Sized>(items: &[&T]) -> usize {
+            items.iter().filter(|i| i.is_valid()).count()
+        }
+
+        let good: Well = serde_json::from_str(r#"{"images": [{"path": "0"}]}"#).unwrap();
+        let bad: Well =
+            serde_json::from_str(r#"{"images": [{"path": "0"}, {"path": "0"}]}"#).unwrap();
+        assert_eq!(count_valid(&[&good, &bad]), 1);
+        assert_eq!(
+            Validate::validate(&vec![good.clone(), bad]),
+            Err(InvalidWell::NonUniquePaths("0".to_owned()))
+        );
+        assert!(Some(good).validated().is_ok());
+    }
+
+    #[test]
+    fn validate_group() {
+        use crate::store::{MemoryStore, WritableStore};
+
+        let store = MemoryStore::new();
+        store
+            .set(
+                "p/.zattrs",
+                br#"{"plate": {
+                    "acquisitions": [{"id": 1}],
+                    "rows": [{"name": "A"}],
+                    "columns": [{"name": "1"}],
+                    "wells": [{"path": "A/1", "rowIndex": 0, "columnIndex": 0}]
+                }}"#,
+            )
+            .unwrap();
+        store
+            .set(
+                "p/A/1/.zattrs",
+                br#"{"well": {"images": [{"path": "0", "acquisition": 2}]}}"#,
+            )
+            .unwrap();
+
+        let err = NgffMetadata::validate_group(&store, "p", &ValidatorConfig::default());
+        assert!(matches!(
+            err,
+            Err(InvalidGroup::Well(p, InvalidWell::UnknownAcquisition(2))) if p == "p/A/1"
+        ));
+        let config = ValidatorConfig {
+            hcs_cross_refs: false,
+            ..Default::default()
+        };
+        NgffMetadata::validate_group(&store, "p", &config).unwrap();
+
+        store
+            .set(
+                "p/A/1/.zattrs",
+                br#"{"well": {"images": [{"path": "0", "acquisition": 1}, {"path": "1", "acquisition": 1}]}}"#,
+            )
+            .unwrap();
+        let err = NgffMetadata::validate_group(&store, "p", &ValidatorConfig::default());
+        assert!(matches!(
+            err,
+            Err(InvalidGroup::Image(p, e)) if p == "p/A/1/0" && matches!(*e, InvalidGroup::Load(LoadError::NotFound(_)))
+        ));
+        let config = ValidatorConfig {
+            store_checks: false,
+            ..Default::default()
+        };
+        NgffMetadata::validate_group(&store, "p", &config).unwrap();
+
+        for fov in ["0", "1"] {
+            store
+                .set(&format!("p/A/1/{fov}/.zattrs"), br#"{"multiscales": [{
+                    "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                    "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}]
+                }]}"#)
+                .unwrap();
+            store.set(&format!("p/A/1/{fov}/0/.zarray"), b"{}").unwrap();
+        }
+        let mut reports = Vec::default();
+        NgffMetadata::validate_group_with_progress(&store, "p", &Default::default(), |p| {
+            reports.push((p.group.to_owned(), p.done, p.total))
+        })
+        .unwrap();
+        assert_eq!(
+            reports,
+            [
+                ("p".to_owned(), 1, 2),
+                ("p/A/1".to_owned(), 2, 4),
+                ("p/A/1/0".to_owned(), 3, 4),
+                ("p/A/1/1".to_owned(), 4, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn lenient_times() {
+        let json = br#"{"plate": {
+            "acquisitions": [{"id": 1, "starttime": 1.5, "endtime": -2}],
+            "rows": [{"name": "A"}],
+            "columns": [{"name": "1"}],
+            "wells": [{"path": "A/1", "rowIndex": 0, "columnIndex": 0}]
+        }}"#;
+        assert!(NgffMetadata::from_slice(json).is_err());
+
+        let config = ValidatorConfig {
+            lenient_times: true,
+            ..Default::default()
+        };
+        let (meta, warnings) = NgffMetadata::from_slice_with(json, &config).unwrap();
+        meta.validate().unwrap();
+        assert_eq!(
+            warnings,
+            [
+                Warning::AcquisitionTime {
+                    acquisition: 1,
+                    key: "starttime",
+                    value: "1.5".to_owned(),
+                    replacement: Some(1)
+                },
+                Warning::AcquisitionTime {
+                    acquisition: 1,
+                    key: "endtime",
+                    value: "-2".to_owned(),
+                    replacement: None
+                },
+            ]
+        );
+
+        let json = br#"{"plate": {
+            "acquisitions": [{"id": 1, "starttime": "1970-01-01T00:00:01Z"}],
+            "rows": [{"name": "A"}],
+            "columns": [{"name": "1"}],
+            "wells": [{"path": "A/1", "rowIndex": 0, "columnIndex": 0}]
+        }}"#;
+        let config = ValidatorConfig {
+            iso_times: true,
+            ..Default::default()
+        };
+        let (meta, warnings) = NgffMetadata::from_slice_with(json, &config).unwrap();
+        assert_eq!(warnings.len(), 1);
+        let value = serde_json::to_value(meta).unwrap();
+        assert_eq!(value["plate"]["acquisitions"][0]["starttime"], 1000);
+    }
+
+    #[test]
+    fn version_policy() {
+        let well: Well = serde_json::from_str(r#"{"images": [{"path": "0"}]}"#).unwrap();
+        let meta = NgffMetadata::builder().with_well(well).build().unwrap();
+
+        let preserved = meta.to_value(VersionPolicy::Preserve).unwrap();
+        assert!(preserved["well"].get("version").is_none());
+
+        let injected = meta.to_value(VersionPolicy::Inject).unwrap();
+        assert_eq!(injected["well"]["version"], VERSION);
+    }
+
+    #[test]
+    fn serialize_injects_versions() {
+        let ms: Multiscale = serde_json::from_str(
+            r#"{
+                "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}]
+            }"#,
+        )
+        .unwrap();
+        let omero: Omero = serde_json::from_str(r#"{"channels": []}"#).unwrap();
+        let meta = NgffMetadata::builder()
+            .with_multiscale(ms.clone())
+            .with_omero(omero)
+            .build()
+            .unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(&meta.to_string_pretty().unwrap()).unwrap();
+        assert_eq!(written["multiscales"][0]["version"], VERSION);
+        assert_eq!(written["omero"]["version"], VERSION);
+        let mut buf = Vec::default();
+        ms.to_writer(&mut buf).unwrap();
+        let written: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(written["version"], VERSION);
+
+        let preserved = meta.to_value(VersionPolicy::Preserve).unwrap();
+        assert!(preserved["multiscales"][0].get("version").is_none());
+        assert!(preserved["omero"].get("version").is_none());
+    }
+
+    #[test]
+    fn canonical_json() {
+        let a: Multiscale = serde_json::from_str(
+            r#"{
+                "version": "0.4",
+                "axes": [{"type": "space", "name": "y"}, {"name": "x", "type": "space"}],
+                "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1.0, 0.5]}]}]
+            }"#,
+        )
+        .unwrap();
+        let b: Multiscale = serde_json::from_str(
+            r#"{
+                "datasets": [{"coordinateTransformations": [{"scale": [1, 0.5], "type": "scale"}], "path": "0"}],
+                "axes": [{"name": "y", "type": "space"}, {"type": "space", "name": "x"}],
+                "version": "0.4"
+            }"#,
+        )
+        .unwrap();
+        let canonical = a.to_canonical_json().unwrap();
+        assert_eq!(canonical, b.to_canonical_json().unwrap());
+        assert!(canonical.contains(r#""scale":[1,0.5]"#));
+        assert!(canonical.starts_with(r#"{"axes":"#));
+    }
+
+    #[test]
+    fn normalize() {
+        let mut meta: NgffMetadata = serde_json::from_str(
+            r#"{
+                "labels": [],
+                "multiscales": [{
+                    "axes": [{"name": "y", "type": "space", "unit": "MicroMeter"}, {"name": "x", "type": "space", "unit": "Furlong"}],
+                    "datasets": [{"path": "0", "coordinateTransformations": [{"type": "identity"}, {"type": "scale", "scale": [1, 1]}]}],
+                    "coordinateTransformations": [{"type": "identity"}],
+                    "metadata": {}
+                }]
+            }"#,
+        )
+        .unwrap();
+        meta.normalize();
+        let value = serde_json::to_value(&meta).unwrap();
+        assert!(value.get("labels").is_none());
+        let ms = &value["multiscales"][0];
+        assert_eq!(ms["axes"][0]["unit"], "micrometer");
+        assert_eq!(ms["axes"][1]["unit"], "Furlong");
+        assert_eq!(
+            ms["datasets"][0]["coordinateTransformations"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(ms.get("coordinateTransformations").is_none());
+        assert!(ms.get("metadata").is_none());
+    }
+
+    #[test]
+    fn json_io() {
+        let meta = NgffMetadata::from_slice(br#"{"labels": ["cells"]}"#).unwrap();
+        let mut buf = Vec::default();
+        meta.to_writer(&mut buf).unwrap();
+        let meta2 = NgffMetadata::from_reader(buf.as_slice()).unwrap();
+        assert_eq!(meta2.labels().unwrap(), ["cells"]);
+        assert!(meta2.to_string_pretty().unwrap().contains('\n'));
+
+        let err = Well::from_slice(b"{").unwrap_err();
+        assert!(err.to_string().starts_with("Could not read Well"));
+    }
+
+    #[cfg(feature = "proptest")]
+    mod prop {
+        use super::*;
+        use crate::Validity;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn valid_documents_validate(meta in any_with::<NgffMetadata>(Validity::Valid)) {
+                meta.validate().unwrap();
+            }
+
+            #[test]
+            fn any_documents_validate_without_panic(meta in any_with::<NgffMetadata>(Validity::Any)) {
+                let _ = meta.validate();
+            }
+        }
+    }
+
+    #[cfg(feature = "miette")]
+    #[test]
+    fn diagnostic() {
+        let json = r#"{"plate": {"version": "0.4", "columns": [], "rows": [], "wells": []},
+            "well": {"images": [{"path": "a/b"}]}}"#;
+        let err = NgffMetadata::from_str_diagnostic(".zattrs", json).unwrap_err();
+        let report = format!("{:?}", miette::Report::new(*err));
+        assert!(report.contains("in /well"), "{report}");
+
+        let err =
+            NgffMetadata::from_str_diagnostic(".zattrs", "{\n  \"labels\": [1]}").unwrap_err();
+        let report = format!("{:?}", miette::Report::new(*err));
+        assert!(report.contains("Could not read NgffMetadata"), "{report}");
+    }
+
+    #[test]
+    fn spec_citations() {
+        let err = InvalidNgffMetadata::Well(InvalidWell::InvalidPath("a/b".to_owned()));
+        assert_eq!(err.code(), "ngff::well::invalid_path");
+        assert_eq!(
+            err.spec_url().unwrap(),
+            "https://ngff.openmicroscopy.org/0.4/#well-md"
+        );
+    }
+
+    #[test]
+    fn mixed_versions() {
+        let meta: NgffMetadata =
+            serde_json::from_str(r#"{"well": {"version": "0.5", "images": [{"path": "0"}]}}"#)
+                .unwrap();
+        assert_eq!(
+            meta.validate().unwrap_err(),
+            InvalidNgffMetadata::Well(InvalidWell::Version("0.5".to_owned()))
+        );
+
+        let meta: NgffMetadata = serde_json::from_str(
+            r#"{
+                "multiscales": [{
+                    "version": "0.4",
+                    "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                    "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}]
+                }],
+                "image-label": {"version": "0.5"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            meta.validate().unwrap_err(),
+            InvalidNgffMetadata::ImageLabelVersion("0.5".to_owned(), "0.4".to_owned())
+        );
+    }
+
+    #[test]
+    fn label_dtype() {
+        use crate::store::{MemoryStore, WritableStore};
+
+        let store = MemoryStore::new();
+        store
+            .set(
+                "labels/cells/.zattrs",
+                br#"{
+                    "multiscales": [{
+                        "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                        "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}]
+                    }],
+                    "image-label": {"version": "0.4"}
+                }"#,
+            )
+            .unwrap();
+        store
+            .set("labels/cells/0/.zarray", br#"{"dtype": "<f4"}"#)
+            .unwrap();
+        let result =
+            NgffMetadata::validate_group(&store, "labels/cells", &ValidatorConfig::default());
+        assert!(matches!(
+            result,
+            Err(InvalidGroup::Metadata(InvalidNgffMetadata::ImageLabel(
+                InvalidImageLabel::Dtype(..)
+            )))
+        ));
+
+        store
+            .set("labels/cells/0/.zarray", br#"{"shape": [2, 2]}"#)
+            .unwrap();
+        let result =
+            NgffMetadata::validate_group(&store, "labels/cells", &ValidatorConfig::default());
+        assert!(matches!(
+            result,
+            Err(InvalidGroup::Metadata(InvalidNgffMetadata::ImageLabel(
+                InvalidImageLabel::MissingDtype(p)
+            ))) if p == "labels/cells/0"
+        ));
+
+        store
+            .set("labels/cells/0/.zarray", br#"{"dtype": "<u4"}"#)
+            .unwrap();
+        NgffMetadata::validate_group(&store, "labels/cells", &ValidatorConfig::default()).unwrap();
+    }
+
+    #[test]
+    fn image_label_without_multiscales() {
+        let meta: NgffMetadata =
+            serde_json::from_str(r#"{"image-label": {"version": "0.4"}}"#).unwrap();
+        let err = meta.validate().unwrap_err();
+        assert_eq!(err, InvalidNgffMetadata::ImageLabelWithoutMultiscales);
+        assert!(err.to_string().contains("\"multiscales\""));
+    }
+}
@@ -1,46 +1,66 @@
-use crate::util::{InconsistentDimensionality, Ndim, ZPath};
-use std::collections::HashMap;
+use crate::diff::Change;
+use crate::store::{join_key, InvalidHierarchy, ReadableStore};
+use crate::util::{impl_spec_diagnostic, InconsistentDimensionality, Ndim, SpecError};
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
+use crate::core::{
+    simplify, Axis, CoordinateTransformation, CoreAxis, InvalidAxes, InvalidCoordinateTransforms,
+    MultiscaleDataset, ScaleOrPath, SpaceUnit, TimeUnit, Transform, TransformError,
+    TranslationOrPath,
+};
+
 use super::{
-    axes::{Axis, InvalidAxes},
-    coordinate_transformations::{
-        CoordinateTransformation, InvalidCoordinateTransforms, Transform,
-    },
+    generator::GeneratorMetadata,
+    validation::{ValidatorConfig, Warning},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct MultiscaleDataset {
-    path: ZPath,
-    coordinate_transformations: Vec<CoordinateTransformation>,
-}
-
-impl MultiscaleDataset {
-    pub fn validate(
-        &self,
-        ndim: Option<usize>,
-    ) -> Result<Option<usize>, InvalidCoordinateTransforms> {
-        InvalidCoordinateTransforms::validate(
-            self.coordinate_transformations.as_slice(),
-            true,
-            ndim,
-        )
-    }
-}
-
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum InvalidMultiscale {
     #[error(transparent)]
     Axes(#[from] InvalidAxes),
     #[error(transparent)]
     Transforms(#[from] InvalidCoordinateTransforms),
+    #[error("Invalid dataset {0}: {1}")]
+    Dataset(usize, InvalidCoordinateTransforms),
     #[error(transparent)]
     Dimensions(#[from] InconsistentDimensionality),
+    #[error("Expected version {}, got {0}", super::VERSION)]
+    Version(String),
+    #[error("Dataset {1} has higher resolution than preceding dataset {0}")]
+    ResolutionOrder(usize, usize),
+    #[error("Dataset path {0:?} is not unique")]
+    NonUniquePath(String),
+}
+
+impl SpecError for InvalidMultiscale {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Axes(e) => e.code(),
+            Self::Transforms(e) | Self::Dataset(_, e) => e.code(),
+            Self::Dimensions(e) => e.code(),
+            Self::Version(_) => "ngff::multiscale::version",
+            Self::ResolutionOrder(..) => "ngff::multiscale::resolution_order",
+            Self::NonUniquePath(_) => "ngff::multiscale::non_unique_path",
+        }
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        match self {
+            Self::Axes(e) => e.spec_url(),
+            Self::Transforms(e) | Self::Dataset(_, e) => e.spec_url(),
+            Self::Dimensions(_)
+            | Self::Version(_)
+            | Self::ResolutionOrder(..)
+            | Self::NonUniquePath(_) => super::spec_url("multiscale-md"),
+        }
+    }
 }
 
+impl_spec_diagnostic!(InvalidMultiscale);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Multiscale {
@@ -49,6 +69,7 @@ pub struct Multiscale {
     #[serde(skip_serializing_if = "Option::is_none")]
     coordinate_transformations: Option<Vec<CoordinateTransformation>>,
     name: Option<Value>,
+    #[serde(serialize_with = "super::serialize_version")]
     version: Option<Value>,
     #[serde(rename = "type")]
     multiscale_type: Option<Value>,
@@ -63,38 +84,576 @@ impl Ndim for Multiscale {
 }
 
 impl Multiscale {
+    /// A multiscale with a single dataset at path "0", for non-pyramidal images.
+    pub fn single_level(
+        axes: Vec<Axis>,
+        scale: Vec<f64>,
+        translation: Option<Vec<f64>>,
+    ) -> Result<Self, InvalidMultiscale> {
+        let mut coordinate_transformations = vec![CoordinateTransformation::scale(scale)];
+        coordinate_transformations.extend(translation.map(CoordinateTransformation::translation));
+        let ms = Self {
+            axes,
+            datasets: vec![MultiscaleDataset {
+                path: "0".to_owned(),
+                coordinate_transformations,
+            }],
+            coordinate_transformations: None,
+            name: None,
+            version: Some(Value::String(super::VERSION.to_owned())),
+            multiscale_type: None,
+            metadata: None,
+        };
+        ms.validate()?;
+        Ok(ms)
+    }
+
+    /// A multiscale over an existing pyramid of arrays, given as `(path, shape)` pairs
+    /// from highest to lowest resolution.
+    ///
+    /// Each level's scale is `base_voxel_size` multiplied by the downsampling factor
+    /// of its shape relative to the first level.
+    pub fn from_shapes<P: AsRef<str>, S: AsRef<[u64]>>(
+        axes: Vec<Axis>,
+        levels: &[(P, S)],
+        base_voxel_size: &[f64],
+    ) -> Result<Self, InvalidMultiscale> {
+        let ndim = axes.len();
+        InconsistentDimensionality::check_dims(ndim, base_voxel_size.len())?;
+        let base = levels.first().map(|(_, s)| s.as_ref()).unwrap_or_default();
+        let mut datasets = Vec::with_capacity(levels.len());
+        for (path, shape) in levels {
+            let shape = shape.as_ref();
+            InconsistentDimensionality::check_dims(ndim, shape.len())?;
+            let scale = base_voxel_size
+                .iter()
+                .zip(base.iter().zip(shape.iter()))
+                .map(|(v, (b, s))| v * *b as f64 / (*s).max(1) as f64)
+                .collect();
+            datasets.push(MultiscaleDataset {
+                path: path.as_ref().to_owned(),
+                coordinate_transformations: vec![CoordinateTransformation::scale(scale)],
+            });
+        }
+        let ms = Self {
+            axes,
+            datasets,
+            coordinate_transformations: None,
+            name: None,
+            version: Some(Value::String(super::VERSION.to_owned())),
+            multiscale_type: None,
+            metadata: None,
+        };
+        ms.validate()?;
+        Ok(ms)
+    }
+
+    /// A single-level `yx` image with the given voxel size in micrometers.
+    pub fn new_2d(y_um: f64, x_um: f64) -> Result<Self, InvalidMultiscale> {
+        Self::single_level(
+            vec![space_axis("y"), space_axis("x")],
+            vec![y_um, x_um],
+            None,
+        )
+    }
+
+    /// A single-level `zyx` image with the given voxel size in micrometers.
+    pub fn new_3d(z_um: f64, y_um: f64, x_um: f64) -> Result<Self, InvalidMultiscale> {
+        Self::single_level(
+            vec![space_axis("z"), space_axis("y"), space_axis("x")],
+            vec![z_um, y_um, x_um],
+            None,
+        )
+    }
+
+    /// A single-level `czyx` image with the given voxel size in micrometers.
+    pub fn new_czyx(z_um: f64, y_um: f64, x_um: f64) -> Result<Self, InvalidMultiscale> {
+        Self::single_level(
+            vec![
+                channel_axis("c"),
+                space_axis("z"),
+                space_axis("y"),
+                space_axis("x"),
+            ],
+            vec![1.0, z_um, y_um, x_um],
+            None,
+        )
+    }
+
+    /// A single-level `tczyx` image with the given time step in seconds
+    /// and voxel size in micrometers.
+    pub fn new_tczyx(t_s: f64, z_um: f64, y_um: f64, x_um: f64) -> Result<Self, InvalidMultiscale> {
+        Self::single_level(
+            vec![
+                Axis::Core(CoreAxis::Time {
+                    name: "t".into(),
+                    unit: Some(TimeUnit::Second),
+                }),
+                channel_axis("c"),
+                space_axis("z"),
+                space_axis("y"),
+                space_axis("x"),
+            ],
+            vec![t_s, 1.0, z_um, y_um, x_um],
+            None,
+        )
+    }
+
     pub fn validate(&self) -> Result<(), InvalidMultiscale> {
-        InvalidAxes::validate(self.axes.as_slice())?;
+        self.validate_with(&ValidatorConfig::default()).map(|_| ())
+    }
+
+    /// Validate according to the config, returning any warnings.
+    pub fn validate_with(
+        &self,
+        config: &ValidatorConfig,
+    ) -> Result<Vec<Warning>, InvalidMultiscale> {
+        if let Some(v) = &self.version {
+            if v.as_str() != Some(super::VERSION) {
+                let found = v.as_str().map_or_else(|| v.to_string(), str::to_owned);
+                return Err(InvalidMultiscale::Version(found));
+            }
+        }
+        let mut warnings: Vec<_> = InvalidAxes::validate_with(self.axes.as_slice(), config.axes)?
+            .into_iter()
+            .map(Warning::from)
+            .collect();
+        if config.units {
+            warnings.extend(self.axes.iter().filter_map(Axis::check_unit));
+        }
         let ndim = self.ndim();
-        for ds in self.datasets.iter() {
-            ds.validate(Some(ndim))?;
+        let mut paths = HashSet::with_capacity(self.datasets.len());
+        for (idx, ds) in self.datasets.iter().enumerate() {
+            if !paths.insert(ds.path.as_str()) {
+                return Err(InvalidMultiscale::NonUniquePath(ds.path.clone()));
+            }
+            ds.validate(Some(ndim))
+                .map_err(|e| InvalidMultiscale::Dataset(idx, e))?;
         }
         if let Some(cs) = &self.coordinate_transformations {
             InvalidCoordinateTransforms::validate(cs.as_slice(), false, Some(ndim))?;
         }
+        if config.resolution_order {
+            self.validate_resolution_order(config.float_tolerance)?;
+        }
+        Ok(warnings)
+    }
+
+    /// Check that every dataset path is an array in the store,
+    /// relative to the multiscale's group `group`.
+    pub fn validate_store<S: ReadableStore + ?Sized>(
+        &self,
+        store: &S,
+        group: &str,
+    ) -> Result<(), InvalidHierarchy> {
+        for ds in self.datasets.iter() {
+            let path = join_key([group, ds.path.as_str()]);
+            if !store.contains(&join_key([path.as_str(), super::ARRAY_KEY]))? {
+                return Err(InvalidHierarchy::MissingArray(path));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that datasets are ordered from highest to lowest resolution,
+    /// i.e. that no scale factor decreases from one dataset to the next.
+    ///
+    /// Datasets whose scales are stored at a path are skipped.
+    fn validate_resolution_order(&self, tolerance: f64) -> Result<(), InvalidMultiscale> {
+        let scales: Vec<_> = self
+            .datasets
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, ds)| ds.scale().map(|s| (idx, s)))
+            .collect();
+        for pair in scales.windows(2) {
+            let (idx0, s0) = pair[0];
+            let (idx1, s1) = pair[1];
+            if s0
+                .iter()
+                .zip(s1.iter())
+                .any(|(a, b)| b.abs() < a.abs() * (1.0 - tolerance))
+            {
+                return Err(InvalidMultiscale::ResolutionOrder(idx0, idx1));
+            }
+        }
         Ok(())
     }
+
+    pub fn axes(&self) -> &[Axis] {
+        self.axes.as_slice()
+    }
+
+    pub fn datasets(&self) -> &[MultiscaleDataset] {
+        self.datasets.as_slice()
+    }
+
+    /// Insert a dataset among the others according to its scale,
+    /// after every level with an equal or finer scale on all axes, returning its index.
+    /// A dataset whose scale is stored at a path is appended.
+    ///
+    /// Fails, leaving the multiscale unchanged, if the dataset is invalid,
+    /// its path is already used, or it does not fit into the resolution order.
+    pub fn add_dataset(&mut self, dataset: MultiscaleDataset) -> Result<usize, InvalidMultiscale> {
+        let idx = match dataset.scale() {
+            Some(scale) => self
+                .datasets
+                .iter()
+                .take_while(|ds| {
+                    ds.scale()
+                        .is_none_or(|s| s.iter().zip(scale).all(|(a, b)| a.abs() <= b.abs()))
+                })
+                .count(),
+            None => self.datasets.len(),
+        };
+        dataset
+            .validate(Some(self.ndim()))
+            .map_err(|e| InvalidMultiscale::Dataset(idx, e))?;
+        if self.datasets.iter().any(|ds| ds.path == dataset.path) {
+            return Err(InvalidMultiscale::NonUniquePath(dataset.path));
+        }
+        self.datasets.insert(idx, dataset);
+        if let Err(e) = self.validate_resolution_order(0.0) {
+            self.datasets.remove(idx);
+            return Err(e);
+        }
+        Ok(idx)
+    }
+
+    /// The raw metadata about how the pyramid was generated.
+    pub fn metadata(&self) -> Option<&HashMap<String, Value>> {
+        self.metadata.as_ref()
+    }
+
+    /// A typed view of [Multiscale::metadata], if present.
+    pub fn generator(&self) -> Option<GeneratorMetadata<'_>> {
+        self.metadata.as_ref().map(GeneratorMetadata::from_map)
+    }
+
+    /// The transform from voxel to world coordinates of the dataset with the given path,
+    /// e.g. as found in the store.
+    pub fn transform_for_path(&self, path: &str) -> Result<(&Self, usize), TransformError> {
+        self.datasets
+            .iter()
+            .position(|ds| ds.path() == path)
+            .map(|level| (self, level))
+            .ok_or_else(|| TransformError::NoPath(path.to_owned()))
+    }
+
+    /// Map a continuous voxel coordinate of the dataset at `from_level`
+    /// to the same world location in the dataset at `to_level`, in place.
+    pub fn map_between_levels(
+        &self,
+        coord: &mut [f64],
+        from_level: usize,
+        to_level: usize,
+    ) -> Result<(), TransformError> {
+        let (from, _) = self.level_transforms(from_level)?;
+        let (to, _) = self.level_transforms(to_level)?;
+        // the multiscale's own transforms apply to both levels, so cancel out
+        from.transform(coord)?;
+        to.rev_transform(coord)
+    }
+
+    /// The per-axis scale and translation mapping voxel coordinates of the dataset at `to_level`
+    /// to those of the dataset at `from_level`.
+    ///
+    /// The scale is the downsampling factor of `to_level` relative to `from_level`,
+    /// and the translation is the position of `to_level`'s origin in `from_level`'s voxels.
+    /// Fails if either dataset's transforms are stored at a path.
+    pub fn relative_transform(
+        &self,
+        from_level: usize,
+        to_level: usize,
+    ) -> Result<(Vec<f64>, Vec<f64>), TransformError> {
+        let affine = |level| self.affine(self.level_transforms(level)?.0);
+        let (from_scale, from_translation) = affine(from_level)?;
+        let (to_scale, to_translation) = affine(to_level)?;
+        let scale = to_scale
+            .iter()
+            .zip(from_scale.iter())
+            .map(|(t, f)| t / f)
+            .collect();
+        let translation = to_translation
+            .iter()
+            .zip(from_translation.iter())
+            .zip(from_scale.iter())
+            .map(|((t, f), s)| (t - f) / s)
+            .collect();
+        Ok((scale, translation))
+    }
+
+    /// The per-axis scale and translation of the dataset's [Multiscale::absolute_transform].
+    #[cfg(feature = "export")]
+    pub(crate) fn absolute_affine(
+        &self,
+        level: usize,
+    ) -> Result<(Vec<f64>, Vec<f64>), TransformError> {
+        self.affine(&self.absolute_transform(level)?)
+    }
+
+    /// The per-axis scale and translation equivalent to the given transforms,
+    /// failing if any are stored at a path.
+    fn affine(
+        &self,
+        transforms: &[CoordinateTransformation],
+    ) -> Result<(Vec<f64>, Vec<f64>), TransformError> {
+        let ndim = self.ndim();
+        let mut scale = vec![1.0; ndim];
+        let mut translation = vec![0.0; ndim];
+        for t in simplify(transforms) {
+            if let CoordinateTransformation::Scale(ScaleOrPath::Path(p))
+            | CoordinateTransformation::Translation(TranslationOrPath::Path(p)) = t
+            {
+                return Err(TransformError::UnresolvedPath(p));
+            }
+            if let Some(s) = t.scale_values() {
+                InconsistentDimensionality::check_dims(ndim, s.len())?;
+                scale.copy_from_slice(s);
+            } else if let Some(t) = t.translation_values() {
+                InconsistentDimensionality::check_dims(ndim, t.len())?;
+                translation.copy_from_slice(t);
+            }
+        }
+        Ok((scale, translation))
+    }
+
+    /// The dataset's transforms at `level` followed by the multiscale's own,
+    /// as an owned [Transform] from voxel to world coordinates.
+    pub fn absolute_transform(
+        &self,
+        level: usize,
+    ) -> Result<Vec<CoordinateTransformation>, TransformError> {
+        let (dataset, global) = self.level_transforms(level)?;
+        Ok(dataset.iter().chain(global).cloned().collect())
+    }
+
+    /// The transforms of the dataset at `level`, and those of the multiscale.
+    fn level_transforms(
+        &self,
+        level: usize,
+    ) -> Result<(&[CoordinateTransformation], &[CoordinateTransformation]), TransformError> {
+        let ds = self
+            .datasets
+            .get(level)
+            .ok_or(TransformError::NoDataset(level))?;
+        Ok((
+            ds.coordinate_transformations.as_slice(),
+            self.coordinate_transformations
+                .as_deref()
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// The size of a voxel of the dataset at the given level, in world units.
+    pub fn voxel_size(&self, level: usize) -> Result<Vec<f64>, TransformError> {
+        let ndim = self.ndim();
+        let mut origin = vec![0.0; ndim];
+        let mut corner = vec![1.0; ndim];
+        (self, level).transform(&mut origin)?;
+        (self, level).transform(&mut corner)?;
+        Ok(corner
+            .iter()
+            .zip(origin.iter())
+            .map(|(c, o)| c - o)
+            .collect())
+    }
+
+    /// Apply canonical forms: remove identity transforms,
+    /// normalize axis units, and drop empty optional collections.
+    pub fn normalize(&mut self) {
+        self.axes.iter_mut().for_each(Axis::normalize);
+        for ds in self.datasets.iter_mut() {
+            ds.coordinate_transformations
+                .retain(|c| c != &CoordinateTransformation::Identity);
+        }
+        if let Some(cs) = self.coordinate_transformations.as_mut() {
+            cs.retain(|c| c != &CoordinateTransformation::Identity);
+        }
+        if self
+            .coordinate_transformations
+            .as_ref()
+            .is_some_and(Vec::is_empty)
+        {
+            self.coordinate_transformations = None;
+        }
+        if self.metadata.as_ref().is_some_and(HashMap::is_empty) {
+            self.metadata = None;
+        }
+    }
+
+    /// Replace unrecognised axis units with their suggested known units where possible,
+    /// returning a warning for each replacement.
+    pub fn repair_units(&mut self) -> Vec<Warning> {
+        self.axes.iter_mut().filter_map(Axis::repair_unit).collect()
+    }
+
+    /// List the structural differences from another multiscale.
+    pub fn diff(&self, other: &Self) -> serde_json::Result<Vec<Change>> {
+        crate::diff::diff(self, other)
+    }
+
+    /// The declared version, if it is a string.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_ref().and_then(Value::as_str)
+    }
+
+    /// Set the version to [super::VERSION] if it is missing.
+    pub fn fill_version(&mut self) {
+        self.version
+            .get_or_insert_with(|| Value::String(super::VERSION.to_owned()));
+    }
+
+    /// Whether a version is given, even one which is not a string.
+    pub(crate) fn has_version(&self) -> bool {
+        self.version.is_some()
+    }
+}
+
+fn space_axis(name: &str) -> Axis {
+    Axis::Core(CoreAxis::Space {
+        name: name.into(),
+        unit: Some(SpaceUnit::Micrometer),
+    })
+}
+
+fn channel_axis(name: &str) -> Axis {
+    Axis::Core(CoreAxis::Channel {
+        name: name.into(),
+        unit: None,
+    })
 }
 
 impl Transform for (&Multiscale, usize) {
-    fn transform(&self, coord: &mut [f64]) -> Result<(), InconsistentDimensionality> {
-        let ds = &self.0.datasets[self.1];
-        ds.coordinate_transformations.as_slice().transform(coord)?;
-        if let Some(cs) = &self.0.coordinate_transformations {
-            cs.as_slice().transform(coord)?;
+    fn transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        let (dataset, global) = self.0.level_transforms(self.1)?;
+        dataset.transform(coord)?;
+        global.transform(coord)
+    }
+
+    fn rev_transform(&self, coord: &mut [f64]) -> Result<(), TransformError> {
+        let (dataset, global) = self.0.level_transforms(self.1)?;
+        global.rev_transform(coord)?;
+        dataset.rev_transform(coord)
+    }
+
+    fn transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        let (dataset, global) = self.0.level_transforms(self.1)?;
+        dataset.transform_f32(coord)?;
+        global.transform_f32(coord)
+    }
+
+    fn rev_transform_f32(&self, coord: &mut [f32]) -> Result<(), TransformError> {
+        let (dataset, global) = self.0.level_transforms(self.1)?;
+        global.rev_transform_f32(coord)?;
+        dataset.rev_transform_f32(coord)
+    }
+}
+
+/// Drops the multiscale's version, which v0.5 gives for the whole `ome` attribute.
+#[cfg(feature = "v0_5")]
+impl From<Multiscale> for crate::v0_5::Multiscale {
+    fn from(value: Multiscale) -> Self {
+        Self {
+            axes: value.axes,
+            datasets: value.datasets,
+            coordinate_transformations: value.coordinate_transformations,
+            name: value.name,
+            multiscale_type: value.multiscale_type,
+            metadata: value.metadata,
+        }
+    }
+}
+
+#[cfg(feature = "v0_5")]
+impl From<crate::v0_5::Multiscale> for Multiscale {
+    fn from(value: crate::v0_5::Multiscale) -> Self {
+        Self {
+            axes: value.axes,
+            datasets: value.datasets,
+            coordinate_transformations: value.coordinate_transformations,
+            name: value.name,
+            version: Some(Value::String(super::VERSION.to_owned())),
+            multiscale_type: value.multiscale_type,
+            metadata: value.metadata,
         }
-        Ok(())
     }
+}
+
+#[cfg(feature = "proptest")]
+mod arbitrary {
+    use super::*;
+    use crate::util::Validity;
+    use crate::v0_4::{arb_axes, arb_transforms, CoreAxis, ScaleOrPath};
+    use proptest::prelude::*;
+
+    impl Arbitrary for Multiscale {
+        type Parameters = Validity;
+        type Strategy = BoxedStrategy<Self>;
 
-    fn rev_transform(&self, coord: &mut [f64]) -> Result<(), InconsistentDimensionality> {
-        if let Some(cs) = &self.0.coordinate_transformations {
-            cs.as_slice().rev_transform(coord)?;
+        /// Valid multiscales have 1-4 levels, each downsampled by 2 in space
+        /// from the previous.
+        fn arbitrary_with(validity: Validity) -> Self::Strategy {
+            match validity {
+                Validity::Valid => arb_axes(validity)
+                    .prop_flat_map(move |axes| {
+                        let ndim = axes.len();
+                        (Just(axes), arb_transforms(validity, ndim), 1usize..=4)
+                    })
+                    .prop_map(|(axes, base, n_levels)| {
+                        let datasets = (0..n_levels)
+                            .map(|level| {
+                                let mut cs = base.clone();
+                                if let CoordinateTransformation::Scale(ScaleOrPath::Scale(s)) =
+                                    &mut cs[0]
+                                {
+                                    for (v, ax) in s.iter_mut().zip(axes.iter()) {
+                                        if let Axis::Core(CoreAxis::Space { .. }) = ax {
+                                            *v *= 2f64.powi(level as i32);
+                                        }
+                                    }
+                                }
+                                MultiscaleDataset {
+                                    path: level.to_string(),
+                                    coordinate_transformations: cs,
+                                }
+                            })
+                            .collect();
+                        Multiscale {
+                            axes,
+                            datasets,
+                            coordinate_transformations: None,
+                            name: None,
+                            version: Some(Value::String(crate::v0_4::VERSION.to_owned())),
+                            multiscale_type: None,
+                            metadata: None,
+                        }
+                    })
+                    .boxed(),
+                Validity::Any => (
+                    arb_axes(validity),
+                    prop::collection::vec(("[0-9a-z]{1,3}", arb_transforms(validity, 3)), 0..4),
+                    prop::option::of(arb_transforms(validity, 3)),
+                    prop::option::of("0\\.[0-9]"),
+                )
+                    .prop_map(|(axes, datasets, cs, version)| Multiscale {
+                        axes,
+                        datasets: datasets
+                            .into_iter()
+                            .map(|(path, coordinate_transformations)| MultiscaleDataset {
+                                path,
+                                coordinate_transformations,
+                            })
+                            .collect(),
+                        coordinate_transformations: cs,
+                        name: None,
+                        version: version.map(Value::String),
+                        multiscale_type: None,
+                        metadata: None,
+                    })
+                    .boxed(),
+            }
         }
-        let ds = &self.0.datasets[self.1];
-        ds.coordinate_transformations
-            .as_slice()
-            .rev_transform(coord)?;
-        Ok(())
     }
 }
 
@@ -103,58 +662,298 @@ mod tests {
     use super::*;
     use serde_json;
 
-    const EXAMPLE: &str = r#"
-        {
-            "version": "0.4",
-            "name": "example",
-            "axes": [
-                {"name": "t", "type": "time", "unit": "millisecond"},
-                {"name": "c", "type": "channel"},
-                {"name": "z", "type": "space", "unit": "micrometer"},
-                {"name": "y", "type": "space", "unit": "micrometer"},
-                {"name": "x", "type": "space", "unit": "micrometer"}
-            ],
-            "datasets": [
-                {
-                    "path": "0",
-                    "coordinateTransformations": [{
-                        "type": "scale",
-                        "scale": [1.0, 1.0, 0.5, 0.5, 0.5]
-                    }]
-                },
-                {
-                    "path": "1",
-                    "coordinateTransformations": [{
-                        "type": "scale",
-                        "scale": [1.0, 1.0, 1.0, 1.0, 1.0]
-                    }]
-                },
-                {
-                    "path": "2",
-                    "coordinateTransformations": [{
-                        "type": "scale",
-                        "scale": [1.0, 1.0, 2.0, 2.0, 2.0]
-                    }]
-                }
-            ],
-            "coordinateTransformations": [{
-                "type": "scale",
-                "scale": [0.1, 1.0, 1.0, 1.0, 1.0]
-            }],
-            "type": "gaussian",
-            "metadata": {
-                "description": "the fields in metadata depend on the downscaling implementation. Here, the parameters passed to the skimage function are given",
-                "method": "skimage.transform.pyramid_gaussian",
-                "version": "0.16.1",
-                "args": "[true]",
-                "kwargs": {"multichannel": true}
-            }
-        }
-    "#;
+    /// The spec's multiscales example, from the corpus.
+    fn example() -> Multiscale {
+        let fixture = crate::v0_4::corpus::fixture("multiscales_example").unwrap();
+        fixture.parse().unwrap().multiscales().unwrap()[0].clone()
+    }
 
     #[test]
     fn deser_example() {
-        let ms: Multiscale = serde_json::from_str(EXAMPLE).unwrap();
+        let ms: Multiscale = example();
+        ms.validate().unwrap();
+    }
+
+    #[test]
+    fn generator() {
+        let mut ms: Multiscale = example();
+        assert!(matches!(
+            ms.generator(),
+            Some(GeneratorMetadata::Skimage {
+                method: "skimage.transform.pyramid_gaussian",
+                version: Some("0.16.1"),
+                ..
+            })
+        ));
+        ms.metadata = None;
+        assert!(ms.generator().is_none());
+    }
+
+    #[test]
+    fn voxel_size() {
+        let ms: Multiscale = example();
+        assert_eq!(ms.voxel_size(2).unwrap(), vec![0.1, 1.0, 2.0, 2.0, 2.0]);
+        assert_eq!(ms.voxel_size(3).unwrap_err(), TransformError::NoDataset(3));
+    }
+
+    #[test]
+    fn transform_f32() {
+        let ms: Multiscale = example();
+        let mut single = [1.0f32; 5];
+        (&ms, 2).transform_f32(&mut single).unwrap();
+        assert_eq!(single, [0.1, 1.0, 2.0, 2.0, 2.0]);
+        (&ms, 2).rev_transform_f32(&mut single).unwrap();
+        assert_eq!(single, [1.0; 5]);
+        assert_eq!(
+            (&ms, 3).transform_f32(&mut single),
+            Err(TransformError::NoDataset(3))
+        );
+    }
+
+    #[test]
+    fn map_between_levels() {
+        let ms: Multiscale = example();
+        let mut coord = [4.0; 5];
+        ms.map_between_levels(&mut coord, 0, 2).unwrap();
+        assert_eq!(coord, [4.0, 4.0, 1.0, 1.0, 1.0]);
+        ms.map_between_levels(&mut coord, 2, 0).unwrap();
+        assert_eq!(coord, [4.0; 5]);
+        assert_eq!(
+            ms.map_between_levels(&mut coord, 0, 3),
+            Err(TransformError::NoDataset(3))
+        );
+    }
+
+    #[test]
+    fn absolute_transform() {
+        let ms: Multiscale = example();
+        let absolute = ms.absolute_transform(2).unwrap();
+        assert_eq!(absolute.len(), 2);
+        assert_eq!(
+            ms.absolute_transform(3).unwrap_err(),
+            TransformError::NoDataset(3)
+        );
+        // the transform outlives the multiscale it came from
+        drop(ms);
+        let mut coord = [1.0; 5];
+        absolute.transform(&mut coord).unwrap();
+        assert_eq!(coord, [0.1, 1.0, 2.0, 2.0, 2.0]);
+        absolute.rev_transform(&mut coord).unwrap();
+        assert_eq!(coord, [1.0; 5]);
+    }
+
+    #[test]
+    fn transform_for_path() {
+        let ms: Multiscale = example();
+        let mut coord = [1.0; 5];
+        ms.transform_for_path("2")
+            .unwrap()
+            .transform(&mut coord)
+            .unwrap();
+        assert_eq!(coord, [0.1, 1.0, 2.0, 2.0, 2.0]);
+
+        let mut root = [1.0; 5];
+        ms.transform_for_path("0")
+            .unwrap()
+            .transform(&mut root)
+            .unwrap();
+        assert_eq!(root, [0.1, 1.0, 0.5, 0.5, 0.5]);
+        assert_eq!(
+            ms.transform_for_path("3").unwrap_err(),
+            TransformError::NoPath("3".to_owned())
+        );
+    }
+
+    #[test]
+    fn relative_transform() {
+        let ms: Multiscale = example();
+        assert_eq!(
+            ms.relative_transform(0, 2).unwrap(),
+            (vec![1.0, 1.0, 4.0, 4.0, 4.0], vec![0.0; 5])
+        );
+
+        let level = |path: &str, scale: f64, translation: [f64; 2]| {
+            MultiscaleDataset::new(
+                path,
+                vec![
+                    CoordinateTransformation::scale(vec![scale; 2]),
+                    CoordinateTransformation::translation(translation.to_vec()),
+                ],
+            )
+        };
+        let mut ms = Multiscale::new_2d(1.0, 1.0).unwrap();
+        ms.datasets = vec![level("0", 1.0, [0.5, 0.5]), level("1", 2.0, [1.0, 1.5])];
+        let (scale, translation) = ms.relative_transform(0, 1).unwrap();
+        assert_eq!(scale, [2.0, 2.0]);
+        assert_eq!(translation, [0.5, 1.0]);
+
+        // the origin of level 1 lands at the reported translation in level 0
+        let mut origin = [0.0; 2];
+        ms.map_between_levels(&mut origin, 1, 0).unwrap();
+        assert_eq!(origin, translation.as_slice());
+
+        ms.datasets[1].coordinate_transformations[1] =
+            serde_json::from_str(r#"{"type": "translation", "path": "translations.bin"}"#).unwrap();
+        assert_eq!(
+            ms.relative_transform(0, 1).unwrap_err(),
+            TransformError::UnresolvedPath("translations.bin".to_owned())
+        );
+    }
+
+    #[test]
+    fn invalid_version() {
+        let mut ms: Multiscale = example();
+        ms.version = Some(Value::String("0.5".to_owned()));
+        let err = ms.validate().unwrap_err();
+        assert_eq!(err, InvalidMultiscale::Version("0.5".to_owned()));
+        assert_eq!(err.to_string(), "Expected version 0.4, got 0.5");
+
+        ms.version = Some(serde_json::json!(0.4));
+        assert_eq!(
+            ms.validate().unwrap_err(),
+            InvalidMultiscale::Version("0.4".to_owned())
+        );
+    }
+
+    #[test]
+    fn invalid_datasets() {
+        let mut ms: Multiscale = example();
+        ms.datasets.swap(1, 2);
+        assert_eq!(
+            ms.validate().unwrap_err(),
+            InvalidMultiscale::ResolutionOrder(1, 2)
+        );
+
+        let mut ms: Multiscale = example();
+        ms.datasets[2].path = "1".to_owned();
+        assert_eq!(
+            ms.validate().unwrap_err(),
+            InvalidMultiscale::NonUniquePath("1".to_owned())
+        );
+    }
+
+    #[test]
+    fn add_dataset() {
+        let mut ms = Multiscale::new_3d(1.0, 1.0, 1.0).unwrap();
+        let level = |path: &str, s: f64| {
+            MultiscaleDataset::new(path, vec![CoordinateTransformation::scale(vec![s; 3])])
+        };
+        assert_eq!(ms.add_dataset(level("2", 4.0)).unwrap(), 1);
+        assert_eq!(ms.add_dataset(level("1", 2.0)).unwrap(), 1);
+        let paths: Vec<_> = ms.datasets().iter().map(MultiscaleDataset::path).collect();
+        assert_eq!(paths, ["0", "1", "2"]);
+        assert_eq!(
+            ms.add_dataset(level("1", 8.0)).unwrap_err(),
+            InvalidMultiscale::NonUniquePath("1".to_owned())
+        );
+        let anisotropic = MultiscaleDataset::new(
+            "3",
+            vec![CoordinateTransformation::scale(vec![8.0, 8.0, 0.5])],
+        );
+        assert_eq!(
+            ms.add_dataset(anisotropic).unwrap_err(),
+            InvalidMultiscale::ResolutionOrder(0, 1)
+        );
+        assert_eq!(ms.datasets().len(), 3);
         ms.validate().unwrap();
     }
+
+    #[test]
+    fn single_level() {
+        let ms: Multiscale = example();
+        let axes = ms.axes()[2..].to_vec();
+        let single =
+            Multiscale::single_level(axes.clone(), vec![2.0, 0.5, 0.5], Some(vec![0.0, 1.0, 1.0]))
+                .unwrap();
+        assert_eq!(single.datasets().len(), 1);
+        assert_eq!(single.voxel_size(0).unwrap(), vec![2.0, 0.5, 0.5]);
+
+        assert!(matches!(
+            Multiscale::single_level(axes, vec![1.0, 1.0], None),
+            Err(InvalidMultiscale::Dataset(..) | InvalidMultiscale::Dimensions(_))
+        ));
+    }
+
+    #[test]
+    fn standard_layouts() {
+        fn names(ms: &Multiscale) -> Vec<&str> {
+            ms.axes().iter().map(Axis::name).collect()
+        }
+
+        let yx = Multiscale::new_2d(0.5, 0.25).unwrap();
+        assert_eq!(names(&yx), ["y", "x"]);
+        assert_eq!(yx.voxel_size(0).unwrap(), vec![0.5, 0.25]);
+
+        let zyx = Multiscale::new_3d(2.0, 0.5, 0.5).unwrap();
+        assert_eq!(names(&zyx), ["z", "y", "x"]);
+        assert_eq!(zyx.voxel_size(0).unwrap(), vec![2.0, 0.5, 0.5]);
+
+        let czyx = Multiscale::new_czyx(2.0, 0.5, 0.5).unwrap();
+        assert_eq!(names(&czyx), ["c", "z", "y", "x"]);
+        assert_eq!(czyx.voxel_size(0).unwrap(), vec![1.0, 2.0, 0.5, 0.5]);
+
+        let tczyx = Multiscale::new_tczyx(1.0, 2.0, 0.5, 0.5).unwrap();
+        assert_eq!(names(&tczyx), ["t", "c", "z", "y", "x"]);
+        assert_eq!(tczyx.voxel_size(0).unwrap(), vec![1.0, 1.0, 2.0, 0.5, 0.5]);
+        tczyx.validate().unwrap();
+    }
+
+    #[test]
+    fn from_shapes() {
+        let axes = vec![
+            Axis::from_name("c"),
+            Axis::from_name("y"),
+            Axis::from_name("x"),
+        ];
+        let levels = [
+            ("0", vec![3, 100, 100]),
+            ("1", vec![3, 50, 50]),
+            ("2", vec![3, 25, 25]),
+        ];
+        let ms = Multiscale::from_shapes(axes.clone(), &levels, &[1.0, 0.5, 0.5]).unwrap();
+        assert_eq!(ms.voxel_size(2).unwrap(), vec![1.0, 2.0, 2.0]);
+
+        let reversed: Vec<_> = levels.iter().rev().cloned().collect();
+        assert_eq!(
+            Multiscale::from_shapes(axes, &reversed, &[1.0, 0.5, 0.5]).unwrap_err(),
+            InvalidMultiscale::ResolutionOrder(0, 1)
+        );
+    }
+
+    #[test]
+    fn validate_store() {
+        use crate::store::{MemoryStore, WritableStore};
+
+        let ms: Multiscale = example();
+        let store = MemoryStore::new();
+        for level in ["0", "1"] {
+            store.set(&format!("image/{level}/.zarray"), b"{}").unwrap();
+        }
+        assert!(matches!(
+            ms.validate_store(&store, "image"),
+            Err(InvalidHierarchy::MissingArray(p)) if p == "image/2"
+        ));
+        store.set("image/2/.zarray", b"{}").unwrap();
+        ms.validate_store(&store, "image").unwrap();
+    }
+
+    #[cfg(feature = "v0_5")]
+    #[test]
+    fn v0_5_round_trip() {
+        let ms: Multiscale = example();
+        let v5 = crate::v0_5::Multiscale::from(ms.clone());
+        let v5_json = serde_json::to_value(&v5).unwrap();
+        assert!(v5_json.get("version").is_none());
+        assert_eq!(v5.axes(), ms.axes());
+        assert_eq!(
+            v5_json["datasets"],
+            serde_json::to_value(ms.datasets()).unwrap()
+        );
+
+        let back = Multiscale::from(v5);
+        assert_eq!(
+            serde_json::to_value(&back).unwrap(),
+            serde_json::to_value(&ms).unwrap()
+        );
+    }
 }
@@ -0,0 +1,397 @@
+//! Async variants of the store-backed loading and validation APIs.
+use std::future::{poll_fn, Future};
+use std::task::Poll;
+
+use crate::store::{join_key, AsyncReadableStore, InvalidHierarchy, LoadError, Progress};
+use crate::util::{Validated, ZPath};
+
+use super::{
+    GroupCheck, InvalidGroup, Multiscale, NgffMetadata, Plate, ValidatorConfig, Warning, ARRAY_KEY,
+    ATTRS_KEY, GROUP_KEY,
+};
+
+impl NgffMetadata {
+    /// Async version of [NgffMetadata::from_store].
+    pub async fn from_store_async<S: AsyncReadableStore + ?Sized>(
+        store: &S,
+        group: &str,
+    ) -> Result<Self, LoadError> {
+        let key = join_key([group, ATTRS_KEY]);
+        let bytes = store.get(&key).await?.ok_or(LoadError::NotFound(key))?;
+        Ok(Self::from_slice(&bytes)?)
+    }
+
+    /// Async version of [NgffMetadata::validate_store].
+    pub async fn validate_store_async<S: AsyncReadableStore + ?Sized>(
+        &self,
+        store: &S,
+        group: &str,
+    ) -> Result<(), InvalidHierarchy> {
+        for ms in self.multiscales.iter().flatten() {
+            ms.validate_store_async(store, group).await?;
+        }
+        for label in self.labels.iter().flatten() {
+            let path = join_key([group, label.as_str()]);
+            if !store
+                .contains(&join_key([path.as_str(), GROUP_KEY]))
+                .await?
+            {
+                return Err(InvalidHierarchy::MissingGroup(path));
+            }
+        }
+        Ok(())
+    }
+
+    /// Async version of [NgffMetadata::validate_group].
+    ///
+    /// A plate's documents are fetched [Plate::DEFAULT_CONCURRENCY] at a time.
+    pub async fn validate_group_async<S: AsyncReadableStore + ?Sized>(
+        store: &S,
+        group: &str,
+        config: &ValidatorConfig,
+    ) -> Result<(Validated<Self>, Vec<Warning>), InvalidGroup> {
+        Self::validate_group_async_with_progress(
+            store,
+            group,
+            config,
+            Plate::DEFAULT_CONCURRENCY,
+            |_| (),
+        )
+        .await
+    }
+
+    /// Async version of [NgffMetadata::validate_group_with_progress],
+    /// fetching at most `concurrency` of a plate's documents at once:
+    /// first every well's, then every image's.
+    ///
+    /// Progress is still reported, and the first error returned, in plate order,
+    /// but every well is reported before any image.
+    pub async fn validate_group_async_with_progress<S, F>(
+        store: &S,
+        group: &str,
+        config: &ValidatorConfig,
+        concurrency: usize,
+        progress: F,
+    ) -> Result<(Validated<Self>, Vec<Warning>), InvalidGroup>
+    where
+        S: AsyncReadableStore + ?Sized,
+        F: FnMut(Progress),
+    {
+        let node = Self::validate_node_async(store, group, config).await?;
+        let (mut check, wells) = GroupCheck::new(group, node, config, progress);
+        let loaded = wells.iter().map(|well| Self::from_store_async(store, well));
+        let mut images = Vec::default();
+        for (well, loaded) in wells.iter().zip(buffered(loaded, concurrency).await) {
+            images.extend(check.well(well, loaded)?);
+        }
+        let validated = images
+            .iter()
+            .map(|image| Self::validate_node_async(store, image, config));
+        for (image, validated) in images.iter().zip(buffered(validated, concurrency).await) {
+            check.image(image, validated)?;
+        }
+        Ok(check.finish())
+    }
+
+    /// Async version of [NgffMetadata::validate_node].
+    async fn validate_node_async<S: AsyncReadableStore + ?Sized>(
+        store: &S,
+        group: &str,
+        config: &ValidatorConfig,
+    ) -> Result<(Self, Vec<Warning>), InvalidGroup> {
+        let key = join_key([group, ATTRS_KEY]);
+        let bytes = store.get(&key).await.map_err(LoadError::from)?;
+        let (meta, warnings) = Self::parse_node(key, bytes, config)?;
+        if config.store_checks {
+            meta.validate_store_async(store, group).await?;
+            for (path, zarray) in meta.label_arrays(group) {
+                let bytes = store.get(&zarray).await.map_err(LoadError::from)?;
+                super::check_label_dtype(&path, bytes)?;
+            }
+        }
+        Ok((meta, warnings))
+    }
+}
+
+impl Multiscale {
+    /// Async version of [Multiscale::validate_store].
+    pub async fn validate_store_async<S: AsyncReadableStore + ?Sized>(
+        &self,
+        store: &S,
+        group: &str,
+    ) -> Result<(), InvalidHierarchy> {
+        for ds in self.datasets() {
+            let path = join_key([group, ds.path()]);
+            if !store
+                .contains(&join_key([path.as_str(), ARRAY_KEY]))
+                .await?
+            {
+                return Err(InvalidHierarchy::MissingArray(path));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Plate {
+    /// The default number of documents [Plate::load_images_async] fetches at once.
+    pub const DEFAULT_CONCURRENCY: usize = 16;
+
+    /// Async version of [Plate::iter_images], collecting every image in plate order.
+    ///
+    /// Documents are fetched [Plate::DEFAULT_CONCURRENCY] at a time.
+    pub async fn load_images_async<S: AsyncReadableStore + ?Sized>(
+        &self,
+        store: &S,
+        plate_group: &str,
+    ) -> Vec<Result<(ZPath, NgffMetadata), LoadError>> {
+        self.load_images_async_with(store, plate_group, Self::DEFAULT_CONCURRENCY)
+            .await
+    }
+
+    /// As [Plate::load_images_async], fetching at most `concurrency` documents at once:
+    /// first every well's, then every field of view's.
+    pub async fn load_images_async_with<S: AsyncReadableStore + ?Sized>(
+        &self,
+        store: &S,
+        plate_group: &str,
+        concurrency: usize,
+    ) -> Vec<Result<(ZPath, NgffMetadata), LoadError>> {
+        let wells = self.wells().iter().map(|pw| async move {
+            let well_group = join_key([plate_group, pw.path()]);
+            let well = NgffMetadata::from_store_async(store, &well_group).await;
+            (well_group, well)
+        });
+        let mut images = Vec::default();
+        for (well_group, well) in buffered(wells, concurrency).await {
+            match well {
+                Ok(NgffMetadata { well: Some(w), .. }) => {
+                    images.extend(w.image_paths(&well_group).into_iter().map(Ok))
+                }
+                Ok(_) => images.push(Err(LoadError::MissingMetadata(well_group, "well"))),
+                Err(e) => images.push(Err(e)),
+            }
+        }
+        let images = images.into_iter().map(|path| async move {
+            let path = path?;
+            let meta = NgffMetadata::from_store_async(store, &path).await?;
+            Ok((path, meta))
+        });
+        buffered(images, concurrency).await
+    }
+}
+
+/// Run futures with at most `limit` (at least 1) in progress at once,
+/// returning their outputs in order.
+async fn buffered<I>(futures: I, limit: usize) -> Vec<<I::Item as Future>::Output>
+where
+    I: IntoIterator,
+    I::Item: Future,
+{
+    let limit = limit.max(1);
+    let mut pending: Vec<_> = futures.into_iter().map(|f| Some(Box::pin(f))).collect();
+    let mut outputs: Vec<_> = pending.iter().map(|_| None).collect();
+    let mut in_progress = Vec::with_capacity(limit);
+    let mut next = 0;
+    poll_fn(|cx| loop {
+        while in_progress.len() < limit && next < pending.len() {
+            in_progress.push(next);
+            next += 1;
+        }
+        let before = in_progress.len();
+        in_progress.retain(|idx: &usize| {
+            let future = pending[*idx].as_mut().expect("polled after completion");
+            match future.as_mut().poll(cx) {
+                Poll::Ready(out) => {
+                    outputs[*idx] = Some(out);
+                    pending[*idx] = None;
+                    false
+                }
+                Poll::Pending => true,
+            }
+        });
+        if in_progress.is_empty() && next == pending.len() {
+            return Poll::Ready(());
+        }
+        if in_progress.len() == before {
+            return Poll::Pending;
+        }
+    })
+    .await;
+    outputs
+        .into_iter()
+        .map(|out| out.expect("every future completed"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{MemoryStore, WritableStore};
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    /// Run a future which never waits on anything external.
+    fn block_on<F: Future>(f: F) -> F::Output {
+        let mut f = pin!(f);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(out) = f.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    /// Yields once, so that other futures get a chance to start.
+    async fn yield_once() {
+        let mut yielded = false;
+        poll_fn(|cx| {
+            if yielded {
+                return Poll::Ready(());
+            }
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await
+    }
+
+    #[test]
+    fn buffered_limit() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let current = AtomicUsize::new(0);
+        let max = AtomicUsize::new(0);
+        let futures = (0..10).map(|idx| {
+            let (current, max) = (&current, &max);
+            async move {
+                let n = current.fetch_add(1, Ordering::SeqCst) + 1;
+                max.fetch_max(n, Ordering::SeqCst);
+                yield_once().await;
+                current.fetch_sub(1, Ordering::SeqCst);
+                idx
+            }
+        });
+        let out = block_on(buffered(futures, 3));
+        assert_eq!(out, (0..10).collect::<Vec<_>>());
+        assert_eq!(max.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn load_images_async() {
+        let store = MemoryStore::new();
+        store
+            .set(
+                "plate/A/1/.zattrs",
+                br#"{"well": {"images": [{"path": "0"}, {"path": "1"}]}}"#,
+            )
+            .unwrap();
+        store.set("plate/A/1/0/.zattrs", b"{}").unwrap();
+        store.set("plate/A/1/1/.zattrs", b"{}").unwrap();
+        let plate: Plate = serde_json::from_str(
+            r#"{
+                "rows": [{"name": "A"}],
+                "columns": [{"name": "1"}, {"name": "2"}],
+                "wells": [
+                    {"path": "A/1", "rowIndex": 0, "columnIndex": 0},
+                    {"path": "A/2", "rowIndex": 0, "columnIndex": 1}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let images = block_on(plate.load_images_async_with(&store, "plate", 2));
+        assert_eq!(images.len(), 3);
+        assert_eq!(images[0].as_ref().unwrap().0, "plate/A/1/0");
+        assert_eq!(images[1].as_ref().unwrap().0, "plate/A/1/1");
+        assert!(matches!(&images[2], Err(LoadError::NotFound(k)) if k == "plate/A/2/.zattrs"));
+    }
+
+    #[test]
+    fn validate_group_async() {
+        let store = MemoryStore::new();
+        store
+            .set(
+                "img/.zattrs",
+                br#"{"multiscales": [{
+                    "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                    "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}]
+                }]}"#,
+            )
+            .unwrap();
+        let config = ValidatorConfig::default();
+        let result = block_on(NgffMetadata::validate_group_async(&store, "img", &config));
+        assert!(matches!(
+            result,
+            Err(InvalidGroup::Hierarchy(InvalidHierarchy::MissingArray(p))) if p == "img/0"
+        ));
+        store.set("img/0/.zarray", b"{}").unwrap();
+        block_on(NgffMetadata::validate_group_async(&store, "img", &config)).unwrap();
+    }
+
+    #[test]
+    fn validate_group_async_with_progress() {
+        let store = MemoryStore::new();
+        store
+            .set(
+                "p/.zattrs",
+                br#"{"plate": {
+                    "rows": [{"name": "A"}],
+                    "columns": [{"name": "1"}],
+                    "wells": [{"path": "A/1", "rowIndex": 0, "columnIndex": 0}]
+                }}"#,
+            )
+            .unwrap();
+        store
+            .set("p/A/1/.zattrs", br#"{"well": {"images": [{"path": "0"}]}}"#)
+            .unwrap();
+        let config = ValidatorConfig::default();
+        let result = block_on(NgffMetadata::validate_group_async(&store, "p", &config));
+        assert!(matches!(result, Err(InvalidGroup::Image(p, _)) if p == "p/A/1/0"));
+
+        store
+            .set(
+                "p/A/1/0/.zattrs",
+                br#"{"multiscales": [{
+                    "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                    "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}]
+                }]}"#,
+            )
+            .unwrap();
+        store.set("p/A/1/0/0/.zarray", b"{}").unwrap();
+        store
+            .set(
+                "p/.zattrs",
+                br#"{"plate": {
+                    "rows": [{"name": "A"}],
+                    "columns": [{"name": "1"}, {"name": "2"}],
+                    "wells": [
+                        {"path": "A/1", "rowIndex": 0, "columnIndex": 0},
+                        {"path": "A/2", "rowIndex": 0, "columnIndex": 1}
+                    ]
+                }}"#,
+            )
+            .unwrap();
+        store
+            .set("p/A/2/.zattrs", br#"{"well": {"images": []}}"#)
+            .unwrap();
+        let mut reports = Vec::default();
+        block_on(NgffMetadata::validate_group_async_with_progress(
+            &store,
+            "p",
+            &config,
+            2,
+            |p| reports.push((p.group.to_owned(), p.done, p.total)),
+        ))
+        .unwrap();
+        assert_eq!(
+            reports,
+            [
+                ("p".to_owned(), 1, 3),
+                ("p/A/1".to_owned(), 2, 4),
+                ("p/A/2".to_owned(), 3, 4),
+                ("p/A/1/0".to_owned(), 4, 4)
+            ]
+        );
+    }
+}
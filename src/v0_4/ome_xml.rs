@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+use serde_json::json;
+use thiserror::Error;
+
+use super::omero::{Channel, Omero};
+use super::plate::{column_label, row_label, Plate};
+use crate::core::{Axis, CoreAxis, SpaceUnit, TimeUnit};
+
+/// Failure to read OME-XML, or to convert it to NGFF metadata.
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum OmeXmlError {
+    #[error("Could not read OME-XML: {0}")]
+    Xml(#[from] quick_xml::Error),
+    #[error("Invalid {element} attribute {attribute}: {value:?}")]
+    Attribute {
+        element: &'static str,
+        attribute: String,
+        value: String,
+    },
+    #[error("Could not convert plate: {0}")]
+    Plate(#[from] serde_json::Error),
+}
+
+/// The parts of an OME-XML document (e.g. the `METADATA.ome.xml` written by bioformats2raw)
+/// from which NGFF metadata can be derived.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OmeXml {
+    pub images: Vec<OmeImage>,
+    pub plates: Vec<OmePlate>,
+}
+
+/// An OME-XML `Image` and its `Pixels`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OmeImage {
+    pub id: String,
+    pub name: Option<String>,
+    /// Sizes of the `x`, `y`, `z`, `c`, and `t` dimensions.
+    pub size: [u64; 5],
+    /// Physical sizes of a voxel in `x`, `y`, and `z`, with their units.
+    pub physical_size: [Option<(f64, String)>; 3],
+    pub time_increment: Option<(f64, String)>,
+    pub channels: Vec<OmeChannel>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OmeChannel {
+    pub name: Option<String>,
+    /// Signed 32-bit RGBA, as stored by OME-XML.
+    pub color: Option<i32>,
+}
+
+/// An OME-XML `Plate`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OmePlate {
+    pub name: Option<String>,
+    pub rows: Option<usize>,
+    pub columns: Option<usize>,
+    /// Whether rows (and columns) are named by letters (`A`, `B`, ...) rather than numbers from 1.
+    pub row_letters: bool,
+    pub column_letters: bool,
+    pub wells: Vec<OmeWell>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OmeWell {
+    pub row: usize,
+    pub column: usize,
+    /// IDs of the images of the well's fields.
+    pub images: Vec<String>,
+}
+
+impl std::str::FromStr for OmeXml {
+    type Err = OmeXmlError;
+
+    fn from_str(xml: &str) -> Result<Self, Self::Err> {
+        let mut ome = Self::default();
+        let mut reader = Reader::from_str(xml);
+        loop {
+            let (Event::Start(e) | Event::Empty(e)) = (match reader.read_event()? {
+                Event::Eof => break,
+                event => event,
+            }) else {
+                continue;
+            };
+            let attrs = attributes(&e)?;
+            let get = |key: &str| attrs.get(key).cloned();
+            match e.local_name().as_ref() {
+                b"Image" => ome.images.push(OmeImage {
+                    id: get("ID").unwrap_or_default(),
+                    name: get("Name"),
+                    ..Default::default()
+                }),
+                b"Pixels" => {
+                    let Some(image) = ome.images.last_mut() else {
+                        continue;
+                    };
+                    for (idx, dim) in ["X", "Y", "Z", "C", "T"].iter().enumerate() {
+                        let key = format!("Size{dim}");
+                        image.size[idx] = parse(&attrs, "Pixels", &key)?.unwrap_or(1);
+                    }
+                    for (idx, dim) in ["X", "Y", "Z"].iter().enumerate() {
+                        let key = format!("PhysicalSize{dim}");
+                        image.physical_size[idx] = parse(&attrs, "Pixels", &key)?.map(|v| {
+                            let unit = get(&format!("{key}Unit"));
+                            (v, unit.unwrap_or_else(|| "µm".to_owned()))
+                        });
+                    }
+                    image.time_increment = parse(&attrs, "Pixels", "TimeIncrement")?.map(|v| {
+                        (
+                            v,
+                            get("TimeIncrementUnit").unwrap_or_else(|| "s".to_owned()),
+                        )
+                    });
+                }
+                b"Channel" => {
+                    if let Some(image) = ome.images.last_mut() {
+                        image.channels.push(OmeChannel {
+                            name: get("Name"),
+                            color: parse(&attrs, "Channel", "Color")?,
+                        });
+                    }
+                }
+                b"Plate" => ome.plates.push(OmePlate {
+                    name: get("Name"),
+                    rows: parse(&attrs, "Plate", "Rows")?,
+                    columns: parse(&attrs, "Plate", "Columns")?,
+                    row_letters: get("RowNamingConvention").is_none_or(|c| c == "letter"),
+                    column_letters: get("ColumnNamingConvention").is_some_and(|c| c == "letter"),
+                    wells: Vec::default(),
+                }),
+                b"Well" => {
+                    if let Some(plate) = ome.plates.last_mut() {
+                        plate.wells.push(OmeWell {
+                            row: parse(&attrs, "Well", "Row")?.unwrap_or_default(),
+                            column: parse(&attrs, "Well", "Column")?.unwrap_or_default(),
+                            images: Vec::default(),
+                        });
+                    }
+                }
+                b"ImageRef" => {
+                    let well = ome.plates.last_mut().and_then(|p| p.wells.last_mut());
+                    if let (Some(well), Some(id)) = (well, get("ID")) {
+                        well.images.push(id);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(ome)
+    }
+}
+
+fn attributes(e: &BytesStart) -> Result<HashMap<String, String>, quick_xml::Error> {
+    e.attributes()
+        .map(|a| {
+            let a = a?;
+            let key = String::from_utf8_lossy(a.key.local_name().as_ref()).into_owned();
+            Ok((key, a.normalized_value(XmlVersion::default())?.into_owned()))
+        })
+        .collect()
+}
+
+/// Parse an optional attribute, with an error naming it if it is invalid.
+fn parse<T: std::str::FromStr>(
+    attrs: &HashMap<String, String>,
+    element: &'static str,
+    attribute: &str,
+) -> Result<Option<T>, OmeXmlError> {
+    let Some(value) = attrs.get(attribute) else {
+        return Ok(None);
+    };
+    value.parse().map(Some).map_err(|_| OmeXmlError::Attribute {
+        element,
+        attribute: attribute.to_owned(),
+        value: value.clone(),
+    })
+}
+
+/// The unit for an OME-XML unit symbol (e.g. `µm`), or the known unit an unrecognised symbol
+/// most likely means (e.g. `micron`); other symbols are kept as the unit's `Other` variant.
+///
+/// `None` only if the symbol does not parse, which the spec's unit types always do.
+fn known_unit<U: std::str::FromStr>(symbol: &str, suggest: impl Fn(&U) -> Option<U>) -> Option<U> {
+    let unit: U = symbol.parse().ok()?;
+    Some(suggest(&unit).unwrap_or(unit))
+}
+
+impl OmeImage {
+    /// The image's `t`, `c`, `z`, `y`, and `x` axes, as written by bioformats2raw,
+    /// with the units of the physical sizes and time increment.
+    pub fn axes(&self) -> Vec<Axis> {
+        let space = |name: &str, size: &Option<(f64, String)>| {
+            Axis::Core(CoreAxis::Space {
+                name: name.into(),
+                unit: size
+                    .as_ref()
+                    .and_then(|(_, u)| known_unit(u, SpaceUnit::suggest)),
+            })
+        };
+        vec![
+            Axis::Core(CoreAxis::Time {
+                name: "t".into(),
+                unit: self
+                    .time_increment
+                    .as_ref()
+                    .and_then(|(_, u)| known_unit(u, TimeUnit::suggest)),
+            }),
+            Axis::Core(CoreAxis::Channel {
+                name: "c".into(),
+                unit: None,
+            }),
+            space("z", &self.physical_size[2]),
+            space("y", &self.physical_size[1]),
+            space("x", &self.physical_size[0]),
+        ]
+    }
+
+    /// The highest-resolution scale for [OmeImage::axes], with 1 for unknown sizes.
+    pub fn scale(&self) -> Vec<f64> {
+        let size = |s: &Option<(f64, String)>| s.as_ref().map_or(1.0, |(v, _)| *v);
+        vec![
+            size(&self.time_increment),
+            1.0,
+            size(&self.physical_size[2]),
+            size(&self.physical_size[1]),
+            size(&self.physical_size[0]),
+        ]
+    }
+
+    /// Rendering metadata with the channels' names and colors.
+    pub fn omero(&self) -> Omero {
+        let mut omero = Omero::default();
+        omero
+            .channels_mut()
+            .extend(self.channels.iter().map(|c| Channel {
+                label: c.name.clone(),
+                color: c.color.map(|rgba| format!("{:06X}", (rgba as u32) >> 8)),
+                ..Default::default()
+            }));
+        omero.fill_version();
+        omero
+    }
+}
+
+impl OmePlate {
+    /// The plate layout, with wells at `<row>/<column>`.
+    ///
+    /// Rows and columns default to the largest used by any well.
+    pub fn plate(&self) -> Result<Plate, OmeXmlError> {
+        let n_rows = self
+            .wells
+            .iter()
+            .map(|w| w.row + 1)
+            .max()
+            .unwrap_or_default();
+        let n_cols = self
+            .wells
+            .iter()
+            .map(|w| w.column + 1)
+            .max()
+            .unwrap_or_default();
+        let name = |idx: usize, letters: bool| {
+            if letters {
+                row_label(idx)
+            } else {
+                column_label(idx, 0)
+            }
+        };
+        let rows: Vec<String> = (0..self.rows.unwrap_or(n_rows).max(n_rows))
+            .map(|r| name(r, self.row_letters))
+            .collect();
+        let columns: Vec<String> = (0..self.columns.unwrap_or(n_cols).max(n_cols))
+            .map(|c| name(c, self.column_letters))
+            .collect();
+        let wells: Vec<_> = self
+            .wells
+            .iter()
+            .map(|w| {
+                json!({
+                    "path": format!("{}/{}", rows[w.row], columns[w.column]),
+                    "rowIndex": w.row,
+                    "columnIndex": w.column,
+                })
+            })
+            .collect();
+        let mut plate = json!({
+            "rows": rows.iter().map(|r| json!({"name": r})).collect::<Vec<_>>(),
+            "columns": columns.iter().map(|c| json!({"name": c})).collect::<Vec<_>>(),
+            "wells": wells,
+            "version": super::VERSION,
+        });
+        if let Some(name) = &self.name {
+            plate["name"] = name.as_str().into();
+        }
+        // a field count of 0 would be invalid
+        if let Some(fields) = self
+            .wells
+            .iter()
+            .map(|w| w.images.len())
+            .max()
+            .filter(|n| *n > 0)
+        {
+            plate["field_count"] = fields.into();
+        }
+        Ok(serde_json::from_value(plate)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <OME xmlns="http://www.openmicroscopy.org/Schemas/OME/2016-06">
+            <Plate ID="Plate:0" Name="plate" Rows="2" Columns="3" RowNamingConvention="letter" ColumnNamingConvention="number">
+                <Well ID="Well:0" Row="0" Column="0">
+                    <WellSample ID="WellSample:0" Index="0"><ImageRef ID="Image:0"/></WellSample>
+                </Well>
+                <Well ID="Well:1" Row="1" Column="2">
+                    <WellSample ID="WellSample:1" Index="1"><ImageRef ID="Image:1"/></WellSample>
+                </Well>
+            </Plate>
+            <Image ID="Image:0" Name="a &amp; b">
+                <Pixels ID="Pixels:0" DimensionOrder="XYZCT" Type="uint16"
+                        SizeX="512" SizeY="256" SizeZ="10" SizeC="2" SizeT="1"
+                        PhysicalSizeX="0.5" PhysicalSizeY="0.5" PhysicalSizeZ="2" PhysicalSizeZUnit="nm"
+                        TimeIncrement="100" TimeIncrementUnit="ms">
+                    <Channel ID="Channel:0:0" Name="DAPI" Color="65535"/>
+                    <Channel ID="Channel:0:1" Name="GFP" Color="16711935"/>
+                </Pixels>
+            </Image>
+        </OME>"#;
+
+    #[test]
+    fn ome_xml() {
+        let ome: OmeXml = EXAMPLE.parse().unwrap();
+        let image = &ome.images[0];
+        assert_eq!(image.name.as_deref(), Some("a & b"));
+        assert_eq!(image.size, [512, 256, 10, 2, 1]);
+        assert_eq!(image.scale(), [100.0, 1.0, 2.0, 0.5, 0.5]);
+        let axes = image.axes();
+        assert!(matches!(
+            &axes[0],
+            Axis::Core(CoreAxis::Time {
+                unit: Some(TimeUnit::Millisecond),
+                ..
+            })
+        ));
+        assert!(matches!(
+            &axes[2],
+            Axis::Core(CoreAxis::Space {
+                unit: Some(SpaceUnit::Nanometer),
+                ..
+            })
+        ));
+        assert!(matches!(
+            &axes[4],
+            Axis::Core(CoreAxis::Space {
+                unit: Some(SpaceUnit::Micrometer),
+                ..
+            })
+        ));
+
+        let omero = image.omero();
+        omero.validate().unwrap();
+        assert_eq!(omero.channels()[0].color.as_deref(), Some("0000FF"));
+        assert_eq!(omero.channels()[1].label.as_deref(), Some("GFP"));
+
+        let plate = ome.plates[0].plate().unwrap();
+        plate.validate().unwrap();
+        let paths: Vec<_> = plate.wells().iter().map(|w| w.path()).collect();
+        assert_eq!(paths, ["A/1", "B/3"]);
+
+        assert!(matches!(
+            "<Pixels SizeX=\"big\"/>".parse::<OmeXml>(),
+            Ok(OmeXml { images, .. }) if images.is_empty()
+        ));
+        assert!(matches!(
+            "<Image/><Pixels SizeX=\"big\"/>".parse::<OmeXml>(),
+            Err(OmeXmlError::Attribute { .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_units() {
+        assert_eq!(
+            known_unit("micron", SpaceUnit::suggest),
+            Some(SpaceUnit::Micrometer)
+        );
+        assert_eq!(
+            known_unit("furlong", SpaceUnit::suggest),
+            Some(SpaceUnit::Other("furlong".to_owned()))
+        );
+        assert_eq!(
+            known_unit("Mm", SpaceUnit::suggest),
+            Some(SpaceUnit::Megameter)
+        );
+        assert_eq!(
+            known_unit("Pm", SpaceUnit::suggest),
+            Some(SpaceUnit::Petameter)
+        );
+        assert_eq!(
+            known_unit("Ms", TimeUnit::suggest),
+            Some(TimeUnit::Megasecond)
+        );
+        assert_eq!(
+            known_unit("fortnight", TimeUnit::suggest),
+            Some(TimeUnit::Other("fortnight".to_owned()))
+        );
+    }
+
+    #[test]
+    fn plate_without_fields() {
+        let ome = OmePlate {
+            row_letters: true,
+            wells: vec![OmeWell::default()],
+            ..Default::default()
+        };
+        let plate = ome.plate().unwrap();
+        assert_eq!(plate.field_count(), None);
+        plate.validate().unwrap();
+    }
+}
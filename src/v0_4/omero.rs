@@ -0,0 +1,417 @@
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::util::{impl_spec_diagnostic, SpecError};
+
+use crate::core::{Axis, CoreAxis};
+
+use super::validation::Warning;
+
+/// Transitional rendering metadata for the channels of an image.
+///
+/// As the `omero` block is transitional and often departs from the spec,
+/// it is read leniently: fields which do not have the expected type
+/// are kept as they are (and reported by [Omero::warnings]) rather than failing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Omero {
+    id: Option<Value>,
+    name: Option<String>,
+    version: Option<String>,
+    channels: Vec<Channel>,
+    rdefs: Option<Value>,
+    other: Map<String, Value>,
+}
+
+/// Fills in a missing version, unless one was kept because it was not a string.
+impl Serialize for Omero {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        if let Some(id) = &self.id {
+            map.serialize_entry("id", id)?;
+        }
+        if let Some(name) = &self.name {
+            map.serialize_entry("name", name)?;
+        }
+        if !self.other.contains_key("version") {
+            let version = self.version.as_deref().unwrap_or(super::VERSION);
+            map.serialize_entry("version", version)?;
+        }
+        map.serialize_entry("channels", &self.channels)?;
+        if let Some(rdefs) = &self.rdefs {
+            map.serialize_entry("rdefs", rdefs)?;
+        }
+        for (key, value) in self.other.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Omero {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut fields = Fields(Map::deserialize(deserializer)?);
+        Ok(Self {
+            id: fields.take("id"),
+            name: fields.take("name"),
+            version: fields.take("version"),
+            channels: fields.take("channels").unwrap_or_default(),
+            rdefs: fields.take("rdefs"),
+            other: fields.0,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Channel {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coefficient: Option<f64>,
+    /// Hex RGB color, e.g. `"0000FF"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inverted: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<Window>,
+    /// Fields not described above, and those which did not have the expected type.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl Channel {
+    const FIELDS: &'static [&'static str] = &[
+        "active",
+        "coefficient",
+        "color",
+        "family",
+        "inverted",
+        "label",
+        "window",
+    ];
+}
+
+impl<'de> Deserialize<'de> for Channel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut fields = Fields(Map::deserialize(deserializer)?);
+        Ok(Self {
+            active: fields.take("active"),
+            coefficient: fields.take("coefficient"),
+            color: fields.take("color"),
+            family: fields.take("family"),
+            inverted: fields.take("inverted"),
+            label: fields.take("label"),
+            window: fields.take("window"),
+            other: fields.0,
+        })
+    }
+}
+
+/// The fields of a JSON object, from which typed values are taken
+/// if they can be read, leaving the rest.
+struct Fields(Map<String, Value>);
+
+impl Fields {
+    /// Take the value at `key` if it can be read as a `T`.
+    /// `null` is treated as missing.
+    fn take<T: DeserializeOwned>(&mut self, key: &str) -> Option<T> {
+        let value = self.0.remove(key)?;
+        if value.is_null() {
+            return None;
+        }
+        match T::deserialize(&value) {
+            Ok(v) => Some(v),
+            Err(_) => {
+                self.0.insert(key.to_owned(), value);
+                None
+            }
+        }
+    }
+}
+
+/// The displayed range of a channel's values (`start` to `end`),
+/// within the range of values it contains (`min` to `max`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Window {
+    pub start: f64,
+    pub end: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Summary statistics of one channel's values, from which to choose a display [Window].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelStats {
+    pub min: f64,
+    pub max: f64,
+    /// Low and high percentiles of the values (e.g. the 0.1st and 99.9th),
+    /// to display instead of the full range so that outliers don't wash out the image.
+    pub percentiles: Option<(f64, f64)>,
+}
+
+impl ChannelStats {
+    /// The window displaying the percentile range if known, otherwise the full range.
+    pub fn window(&self) -> Window {
+        let (start, end) = self.percentiles.unwrap_or((self.min, self.max));
+        Window {
+            start: start.clamp(self.min, self.max),
+            end: end.clamp(self.min, self.max),
+            min: self.min,
+            max: self.max,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidOmero {
+    #[error("Expected version {}, got {0}", super::VERSION)]
+    Version(String),
+    #[error("Channel {0} color {1:?} is not 6 hex digits")]
+    Color(usize, String),
+    #[error("Channel {0} window does not satisfy min <= start <= end <= max")]
+    Window(usize),
+    #[error("Expected {expected} channels for the channel axis, got {got}")]
+    ChannelCount { expected: u64, got: usize },
+}
+
+impl SpecError for InvalidOmero {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Version(_) => "ngff::omero::version",
+            Self::Color(..) => "ngff::omero::color",
+            Self::Window(_) => "ngff::omero::window",
+            Self::ChannelCount { .. } => "ngff::omero::channel_count",
+        }
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        super::spec_url("omero-md")
+    }
+}
+
+impl_spec_diagnostic!(InvalidOmero);
+
+impl Omero {
+    pub fn validate(&self) -> Result<(), InvalidOmero> {
+        if let Some(v) = &self.version {
+            if v != super::VERSION {
+                return Err(InvalidOmero::Version(v.clone()));
+            }
+        }
+        for (idx, ch) in self.channels.iter().enumerate() {
+            if let Some(color) = &ch.color {
+                if color.len() != 6 || !color.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(InvalidOmero::Color(idx, color.clone()));
+                }
+            }
+            if let Some(w) = &ch.window {
+                if !(w.min <= w.start && w.start <= w.end && w.end <= w.max) {
+                    return Err(InvalidOmero::Window(idx));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that there is a channel for every index of the channel axis (if any),
+    /// given the axes and an array shape of the image.
+    pub fn validate_shape(&self, axes: &[Axis], shape: &[u64]) -> Result<(), InvalidOmero> {
+        let Some(expected) = axes
+            .iter()
+            .position(|a| matches!(a, Axis::Core(CoreAxis::Channel { .. })))
+            .and_then(|idx| shape.get(idx))
+        else {
+            return Ok(());
+        };
+        if *expected != self.channels.len() as u64 {
+            return Err(InvalidOmero::ChannelCount {
+                expected: *expected,
+                got: self.channels.len(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn channels(&self) -> &[Channel] {
+        self.channels.as_slice()
+    }
+
+    pub fn channels_mut(&mut self) -> &mut Vec<Channel> {
+        &mut self.channels
+    }
+
+    /// Set each channel's window from the statistics of the channel at the same index
+    /// (see [ChannelStats::window]), adding default channels if there are fewer than `stats`.
+    pub fn fill_windows(&mut self, stats: &[ChannelStats]) {
+        if self.channels.len() < stats.len() {
+            self.channels.resize_with(stats.len(), Channel::default);
+        }
+        for (channel, s) in self.channels.iter_mut().zip(stats.iter()) {
+            channel.window = Some(s.window());
+        }
+    }
+
+    /// Warnings for any fields which were kept as they are because they did not have the expected type.
+    pub fn warnings(&self) -> Vec<Warning> {
+        let unexpected = |field: String, value: &Value| Warning::OmeroField {
+            field,
+            value: value.to_string(),
+        };
+        let mut warnings: Vec<_> = ["id", "name", "version", "channels", "rdefs"]
+            .into_iter()
+            .filter_map(|k| self.other.get(k).map(|v| unexpected(k.to_owned(), v)))
+            .collect();
+        for (idx, ch) in self.channels.iter().enumerate() {
+            warnings.extend(Channel::FIELDS.iter().filter_map(|k| {
+                ch.other
+                    .get(*k)
+                    .map(|v| unexpected(format!("channels/{idx}/{k}"), v))
+            }));
+        }
+        warnings
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Whether a version is given, even one which is not a string.
+    pub(crate) fn has_version(&self) -> bool {
+        self.version.is_some() || self.other.contains_key("version")
+    }
+
+    /// Set the version to [super::VERSION] if it is missing.
+    pub fn fill_version(&mut self) {
+        self.version
+            .get_or_insert_with(|| super::VERSION.to_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"{
+        "id": 1,
+        "name": "example.tif",
+        "version": "0.4",
+        "channels": [{
+            "active": true,
+            "coefficient": 1,
+            "color": "0000FF",
+            "family": "linear",
+            "inverted": false,
+            "label": "LaminB1",
+            "window": {"end": 1500, "max": 65535, "min": 0, "start": 0}
+        }],
+        "rdefs": {"defaultT": 0, "defaultZ": 118, "model": "color"}
+    }"#;
+
+    #[test]
+    fn fill_windows() {
+        let mut omero: Omero = serde_json::from_str(EXAMPLE).unwrap();
+        omero.fill_windows(&[
+            ChannelStats {
+                min: 0.0,
+                max: 4095.0,
+                percentiles: Some((10.0, 5000.0)),
+            },
+            ChannelStats {
+                min: -1.0,
+                max: 1.0,
+                percentiles: None,
+            },
+        ]);
+        assert_eq!(omero.channels().len(), 2);
+        assert_eq!(omero.channels()[0].label.as_deref(), Some("LaminB1"));
+        assert_eq!(
+            omero.channels()[0].window,
+            Some(Window {
+                start: 10.0,
+                end: 4095.0,
+                min: 0.0,
+                max: 4095.0
+            })
+        );
+        assert_eq!(omero.channels()[1].window.unwrap().start, -1.0);
+    }
+
+    #[test]
+    fn validate() {
+        let omero: Omero = serde_json::from_str(EXAMPLE).unwrap();
+        omero.validate().unwrap();
+
+        let mut bad = omero.clone();
+        bad.channels[0].color = Some("#0000FF".to_owned());
+        assert_eq!(
+            bad.validate(),
+            Err(InvalidOmero::Color(0, "#0000FF".to_owned()))
+        );
+
+        let mut bad = omero.clone();
+        bad.channels[0].window.as_mut().unwrap().start = 2000.0;
+        assert_eq!(bad.validate(), Err(InvalidOmero::Window(0)));
+
+        let axes: Vec<Axis> = ["c", "y", "x"].into_iter().map(Axis::from_name).collect();
+        omero.validate_shape(&axes, &[1, 10, 10]).unwrap();
+        assert_eq!(
+            omero.validate_shape(&axes, &[3, 10, 10]),
+            Err(InvalidOmero::ChannelCount {
+                expected: 3,
+                got: 1
+            })
+        );
+        omero.validate_shape(&axes[1..], &[10, 10]).unwrap();
+    }
+
+    #[test]
+    fn lenient() {
+        let omero: Omero = serde_json::from_str(
+            r#"{
+                "version": 0.4,
+                "channels": [
+                    {"window": {"start": 0}, "label": "a"},
+                    {"color": 255, "emissionWavelength": 500}
+                ]
+            }"#,
+        )
+        .unwrap();
+        omero.validate().unwrap();
+        assert_eq!(omero.channels()[0].label.as_deref(), Some("a"));
+        assert_eq!(omero.channels()[0].window, None);
+        assert_eq!(omero.channels()[1].color, None);
+        assert_eq!(
+            omero.warnings(),
+            [
+                Warning::OmeroField {
+                    field: "version".to_owned(),
+                    value: "0.4".to_owned()
+                },
+                Warning::OmeroField {
+                    field: "channels/0/window".to_owned(),
+                    value: r#"{"start":0}"#.to_owned()
+                },
+                Warning::OmeroField {
+                    field: "channels/1/color".to_owned(),
+                    value: "255".to_owned()
+                },
+            ]
+        );
+
+        let value = serde_json::to_value(&omero).unwrap();
+        assert_eq!(value["version"], 0.4);
+        assert_eq!(value["channels"][0]["window"]["start"], 0);
+        assert_eq!(value["channels"][1]["color"], 255);
+        assert_eq!(value["channels"][1]["emissionWavelength"], 500);
+
+        let example: Omero = serde_json::from_str(EXAMPLE).unwrap();
+        assert!(example.warnings().is_empty());
+    }
+}
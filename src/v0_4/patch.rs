@@ -0,0 +1,174 @@
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use super::image_label::LabelType;
+use super::{CoordinateTransformation, InvalidNgffMetadata, NgffMetadata};
+
+/// Failure to apply a [Patch].
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum PatchError {
+    #[error("Nothing to patch at {0}")]
+    NotFound(String),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Metadata(#[from] InvalidNgffMetadata),
+}
+
+/// Targeted edits to a metadata document, e.g. for curation tools.
+///
+/// Edits are made to the document's JSON, so content not modelled by [NgffMetadata]
+/// (such as `omero`, or unknown keys) is preserved.
+/// The result is only validated by [Patch::metadata] and [Patch::finish],
+/// so edits may pass through invalid intermediate states.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    doc: Value,
+}
+
+impl Patch {
+    pub fn new(doc: Value) -> Self {
+        Self { doc }
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, PatchError> {
+        Ok(Self::new(serde_json::from_slice(bytes)?))
+    }
+
+    fn array_mut(&mut self, pointer: &str) -> Result<&mut Vec<Value>, PatchError> {
+        self.doc
+            .pointer_mut(pointer)
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| PatchError::NotFound(pointer.to_owned()))
+    }
+
+    /// Rename the axis `from` of the multiscale at `index`.
+    pub fn rename_axis(
+        &mut self,
+        index: usize,
+        from: &str,
+        to: &str,
+    ) -> Result<&mut Self, PatchError> {
+        let pointer = format!("/multiscales/{index}/axes");
+        let axis = self
+            .array_mut(&pointer)?
+            .iter_mut()
+            .find(|a| a["name"] == from)
+            .ok_or_else(|| PatchError::NotFound(format!("{pointer}/{from}")))?;
+        axis["name"] = to.into();
+        Ok(self)
+    }
+
+    /// Append a dataset (i.e. a lower-resolution level) to the multiscale at `index`.
+    pub fn append_dataset(
+        &mut self,
+        index: usize,
+        path: &str,
+        coordinate_transformations: &[CoordinateTransformation],
+    ) -> Result<&mut Self, PatchError> {
+        let dataset = json!({
+            "path": path,
+            "coordinateTransformations": coordinate_transformations,
+        });
+        self.array_mut(&format!("/multiscales/{index}/datasets"))?
+            .push(dataset);
+        Ok(self)
+    }
+
+    /// Set the color of a label value in the `image-label` metadata,
+    /// adding a color for it if there is none.
+    pub fn set_label_color(
+        &mut self,
+        label_value: LabelType,
+        rgba: [u8; 4],
+    ) -> Result<&mut Self, PatchError> {
+        let image_label = self
+            .doc
+            .get_mut("image-label")
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| PatchError::NotFound("/image-label".to_owned()))?;
+        let colors = image_label
+            .entry("colors")
+            .or_insert_with(|| Value::Array(Vec::default()))
+            .as_array_mut()
+            .ok_or_else(|| PatchError::NotFound("/image-label/colors".to_owned()))?;
+        match colors.iter_mut().find(|c| c["label-value"] == label_value) {
+            Some(color) => color["rgba"] = json!(rgba),
+            None => colors.push(json!({"label-value": label_value, "rgba": rgba})),
+        }
+        Ok(self)
+    }
+
+    /// Parse and validate the patched document.
+    pub fn metadata(&self) -> Result<NgffMetadata, PatchError> {
+        let meta: NgffMetadata = serde_json::from_value(self.doc.clone())?;
+        meta.validate()?;
+        Ok(meta)
+    }
+
+    /// Validate the patched document and return its JSON.
+    pub fn finish(self) -> Result<Value, PatchError> {
+        self.metadata()?;
+        Ok(self.doc)
+    }
+}
+
+impl NgffMetadata {
+    /// Start a [Patch] of this document.
+    ///
+    /// Anything not modelled by [NgffMetadata] was dropped when it was parsed;
+    /// to preserve it, create the patch from the original JSON with [Patch::from_slice].
+    pub fn patch(&self) -> Result<Patch, PatchError> {
+        Ok(Patch::new(serde_json::to_value(self)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"{
+        "multiscales": [{
+            "version": "0.4",
+            "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+            "datasets": [{"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]}]
+        }],
+        "image-label": {"version": "0.4", "colors": [{"label-value": 1, "rgba": [0, 0, 0, 255]}]},
+        "omero": {"channels": []}
+    }"#;
+
+    #[test]
+    fn patch() {
+        let mut patch = Patch::from_slice(EXAMPLE.as_bytes()).unwrap();
+        patch
+            .rename_axis(0, "y", "row")
+            .unwrap()
+            .append_dataset(0, "1", &[CoordinateTransformation::scale(vec![2.0, 2.0])])
+            .unwrap()
+            .set_label_color(1, [255, 0, 0, 255])
+            .unwrap()
+            .set_label_color(2, [0, 255, 0, 255])
+            .unwrap();
+        assert!(matches!(
+            patch.rename_axis(0, "z", "depth"),
+            Err(PatchError::NotFound(_))
+        ));
+
+        let meta = patch.metadata().unwrap();
+        assert_eq!(meta.multiscales().unwrap()[0].axes()[0].name(), "row");
+        assert_eq!(meta.multiscales().unwrap()[0].datasets().len(), 2);
+
+        let doc = patch.finish().unwrap();
+        assert_eq!(doc["omero"], json!({"channels": []}));
+        assert_eq!(
+            doc["image-label"]["colors"][0]["rgba"],
+            json!([255, 0, 0, 255])
+        );
+        assert_eq!(doc["image-label"]["colors"][1]["label-value"], 2);
+
+        let mut patch = Patch::from_slice(EXAMPLE.as_bytes()).unwrap();
+        patch.append_dataset(0, "0", &[]).unwrap();
+        assert!(matches!(patch.finish(), Err(PatchError::Metadata(_))));
+    }
+}
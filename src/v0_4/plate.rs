@@ -1,9 +1,18 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io::Read;
+use std::num::NonZeroUsize;
 
-use serde::{Deserialize, Serialize};
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 use thiserror::Error;
 
-use crate::util::ZPath;
+use crate::util::{impl_spec_diagnostic, try_for_each_item, JsonError, Name, SpecError, ZPath};
+
+use super::validation::{ValidatorConfig, Warning};
+use super::well::{FieldOfView, InvalidWell, Well};
+use super::NgffMetadata;
+use crate::store::{join_key, LoadError, ReadableStore};
 
 pub type AcquisitionId = u64;
 pub type Timestamp = u64;
@@ -23,16 +32,240 @@ pub struct Acquisition {
     end_time: Option<Timestamp>,
 }
 
+impl Acquisition {
+    pub fn builder(id: AcquisitionId) -> AcquisitionBuilder {
+        AcquisitionBuilder::new(id)
+    }
+
+    pub fn id(&self) -> AcquisitionId {
+        self.id
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn maximum_field_count(&self) -> Option<usize> {
+        self.maximum_field_count
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn start_time(&self) -> Option<Timestamp> {
+        self.start_time
+    }
+
+    pub fn end_time(&self) -> Option<Timestamp> {
+        self.end_time
+    }
+}
+
+/// Assembles an [Acquisition],
+/// checking at [AcquisitionBuilder::build] that it does not end before it starts.
+#[derive(Debug, Clone)]
+pub struct AcquisitionBuilder {
+    acquisition: Acquisition,
+}
+
+impl AcquisitionBuilder {
+    pub fn new(id: AcquisitionId) -> Self {
+        Self {
+            acquisition: Acquisition {
+                id,
+                name: None,
+                maximum_field_count: None,
+                description: None,
+                start_time: None,
+                end_time: None,
+            },
+        }
+    }
+
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.acquisition.name = Some(name.into());
+        self
+    }
+
+    pub fn with_description<S: Into<String>>(mut self, description: S) -> Self {
+        self.acquisition.description = Some(description.into());
+        self
+    }
+
+    /// The maximum number of fields of view for the acquisition.
+    pub fn with_maximum_field_count(mut self, count: usize) -> Self {
+        self.acquisition.maximum_field_count = Some(count);
+        self
+    }
+
+    /// Start time in milliseconds since the Unix epoch.
+    pub fn with_start_time(mut self, time: Timestamp) -> Self {
+        self.acquisition.start_time = Some(time);
+        self
+    }
+
+    /// End time in milliseconds since the Unix epoch.
+    pub fn with_end_time(mut self, time: Timestamp) -> Self {
+        self.acquisition.end_time = Some(time);
+        self
+    }
+
+    pub fn build(self) -> Result<Acquisition, InvalidPlate> {
+        validate_acquisitions(std::slice::from_ref(&self.acquisition))?;
+        Ok(self.acquisition)
+    }
+}
+
+/// Replace non-standard acquisition times in a plate's JSON as allowed by the config,
+/// returning a warning for each.
+///
+/// With [ValidatorConfig::lenient_times], fractional times are truncated and negative ones removed.
+/// With [ValidatorConfig::iso_times], ISO 8601 strings are converted to epoch milliseconds.
+pub(crate) fn repair_acquisition_times(
+    plate: &mut serde_json::Value,
+    config: &ValidatorConfig,
+) -> Vec<Warning> {
+    let mut warnings = Vec::default();
+    let acquisitions = plate
+        .get_mut("acquisitions")
+        .and_then(serde_json::Value::as_array_mut);
+    for acq in acquisitions.into_iter().flatten() {
+        let id = acq.get("id").and_then(serde_json::Value::as_u64);
+        let Some(acq) = acq.as_object_mut() else {
+            continue;
+        };
+        for key in ["starttime", "endtime"] {
+            let Some(value) = acq.get(key) else { continue };
+            let replacement = match value {
+                serde_json::Value::Number(n) if config.lenient_times && !n.is_u64() => n
+                    .as_f64()
+                    .filter(|t| *t >= 0.0)
+                    .map(|t| t.trunc() as Timestamp),
+                serde_json::Value::String(s) if config.iso_times => {
+                    let Some(t) = parse_iso8601_millis(s) else {
+                        continue;
+                    };
+                    Some(t)
+                }
+                _ => continue,
+            };
+            warnings.push(Warning::AcquisitionTime {
+                acquisition: id.unwrap_or_default(),
+                key,
+                value: value.to_string(),
+                replacement,
+            });
+            match replacement {
+                Some(t) => acq.insert(key.to_owned(), t.into()),
+                None => acq.remove(key),
+            };
+        }
+    }
+    warnings
+}
+
+/// Parse an ISO 8601 date (`2020-01-31`) or date-time (`2020-01-31T12:00:00.5+01:00`)
+/// as milliseconds since the Unix epoch.
+///
+/// Date-times without an offset are taken to be UTC.
+fn parse_iso8601_millis(s: &str) -> Option<Timestamp> {
+    fn num(s: &str) -> Option<i64> {
+        if !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok()
+    }
+
+    if !s.is_ascii() {
+        return None;
+    }
+    let (date, time) = match s.split_once(['T', 't', ' ']) {
+        Some((d, t)) => (d, Some(t)),
+        None => (s, None),
+    };
+    let mut parts = date.splitn(3, '-');
+    let (year, month, day) = (
+        num(parts.next()?)?,
+        num(parts.next()?)?,
+        num(parts.next()?)?,
+    );
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if !(1..=month_days).contains(&day) {
+        return None;
+    }
+    // days since the epoch of a proleptic Gregorian date,
+    // from http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let mut millis = era
+        .checked_mul(146097)?
+        .checked_add(doe - 719468)?
+        .checked_mul(86_400_000)?;
+
+    if let Some(time) = time {
+        let (time, offset_sign, offset) = match time.find(['Z', 'z', '+', '-']) {
+            Some(idx) => {
+                let (t, o) = time.split_at(idx);
+                match o.split_at(1) {
+                    ("+", o) => (t, 1, Some(o)),
+                    ("-", o) => (t, -1, Some(o)),
+                    (_, "") => (t, 0, None),
+                    _ => return None,
+                }
+            }
+            None => (time, 0, None),
+        };
+        let (hms, frac) = time.split_once(['.', ',']).unwrap_or((time, ""));
+        let mut hms = hms.split(':');
+        let hour = num(hms.next()?)?;
+        let minute = num(hms.next()?)?;
+        let second = hms.next().map(num).unwrap_or(Some(0))?;
+        if hms.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+        let frac_millis = match frac {
+            "" => 0,
+            f => (format!("{f:0<3}")[..3]).parse::<i64>().ok()?,
+        };
+        millis = millis.checked_add(((hour * 60 + minute) * 60 + second) * 1000 + frac_millis)?;
+        if let Some(offset) = offset {
+            let (oh, om) = match offset.split_once(':') {
+                Some((h, m)) => (num(h)?, num(m)?),
+                None if offset.len() == 4 => (num(&offset[..2])?, num(&offset[2..])?),
+                None => (num(offset)?, 0),
+            };
+            if oh > 23 || om > 59 {
+                return None;
+            }
+            millis = millis.checked_sub(offset_sign * (oh * 60 + om) * 60_000)?;
+        }
+    }
+    millis.try_into().ok()
+}
+
 fn validate_acquisitions(acquisitions: &[Acquisition]) -> Result<(), InvalidPlate> {
     let mut ids = HashSet::with_capacity(acquisitions.len());
     for acq in acquisitions.iter() {
         if !ids.insert(acq.id) {
-            return Err(InvalidPlate::NonUniqueAcquisitionId);
+            return Err(InvalidPlate::NonUniqueAcquisitionId(acq.id));
         }
-        let Some(start) = acq.start_time else {continue};
-        let Some(end) = acq.end_time else {continue};
+        let Some(start) = acq.start_time else {
+            continue;
+        };
+        let Some(end) = acq.end_time else { continue };
         if end < start {
-            return Err(InvalidPlate::AcquisitionTime);
+            return Err(InvalidPlate::AcquisitionTime(acq.id));
         }
     }
     Ok(())
@@ -40,7 +273,7 @@ fn validate_acquisitions(acquisitions: &[Acquisition]) -> Result<(), InvalidPlat
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Index {
-    name: String,
+    name: Name,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +284,67 @@ pub struct PlateWell {
     column_index: usize,
 }
 
+impl Index {
+    pub fn new<S: Into<Name>>(name: S) -> Self {
+        Self { name: name.into() }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+impl PlateWell {
+    /// The well at the given row and column of a plate with the given rows and columns,
+    /// whose path is `"{row}/{column}"`.
+    pub fn new(
+        row_index: usize,
+        column_index: usize,
+        rows: &[Index],
+        columns: &[Index],
+    ) -> Result<Self, InvalidPlate> {
+        let indices = || format!("{row_index}/{column_index}");
+        let row = rows
+            .get(row_index)
+            .ok_or_else(|| InvalidPlate::NonexistentRow(indices(), row_index))?;
+        let column = columns
+            .get(column_index)
+            .ok_or_else(|| InvalidPlate::NonexistentColumn(indices(), column_index))?;
+        Ok(Self {
+            path: format!("{}/{}", row.name, column.name),
+            row_index,
+            column_index,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    pub fn row_index(&self) -> usize {
+        self.row_index
+    }
+
+    pub fn column_index(&self) -> usize {
+        self.column_index
+    }
+
+    /// The row and column names in the well's path.
+    pub fn split_path(&self) -> Result<(&str, &str), InvalidPlate> {
+        parse_well_path(&self.path)
+    }
+}
+
+/// Split a well path (e.g. `"A/1"`) into its row and column names,
+/// checking that both are non-empty and alphanumeric.
+pub fn parse_well_path(path: &str) -> Result<(&str, &str), InvalidPlate> {
+    let is_name = |s: &str| !s.is_empty() && s.chars().all(char::is_alphanumeric);
+    match path.split_once('/') {
+        Some((row, column)) if is_name(row) && is_name(column) => Ok((row, column)),
+        _ => Err(InvalidPlate::InvalidWellPath(path.to_owned())),
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Plate {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -61,252 +355,1230 @@ pub struct Plate {
     #[serde(skip_serializing_if = "Option::is_none")]
     name: Option<String>,
     rows: Vec<Index>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "super::serialize_version")]
     version: Option<String>,
     wells: Vec<PlateWell>,
 }
 
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum InvalidPlate {
-    #[error("Well indices are not consistent with their names")]
-    InconsistentWells,
-    #[error("No well at index {0}")]
-    NonexistentWell(usize),
-    #[error("Row or column indices are not unique")]
-    NonUniqueIndex,
-    #[error("Index names must be alphanumeric")]
-    InvalidIndex,
-    #[error("Acquisition IDs are not unique")]
-    NonUniqueAcquisitionId,
-    #[error("Acquisition ends before it starts")]
-    AcquisitionTime,
+    #[error("Well path {0:?} is not consistent with its row and column names")]
+    InconsistentWells(String),
+    #[error("Well path {0:?} is not of the form \"{{row}}/{{column}}\"")]
+    InvalidWellPath(String),
+    #[error("Well {0:?} refers to nonexistent row {1}")]
+    NonexistentRow(String, usize),
+    #[error("Well {0:?} refers to nonexistent column {1}")]
+    NonexistentColumn(String, usize),
+    #[error("No {0} at index {1}")]
+    NoIndex(&'static str, usize),
+    #[error("Well {0:?} is not unique")]
+    NonUniqueWell(String),
+    #[error("Row or column name {0:?} is not unique")]
+    NonUniqueIndex(String),
+    #[error("Row or column name {0:?} is not alphanumeric")]
+    InvalidIndex(String),
+    #[error("Acquisition ID {0} is not unique")]
+    NonUniqueAcquisitionId(AcquisitionId),
+    #[error("Acquisition {0} ends before it starts")]
+    AcquisitionTime(AcquisitionId),
+    #[error("Expected version {}, got {0}", super::VERSION)]
+    Version(String),
+}
+
+impl SpecError for InvalidPlate {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InconsistentWells(_) => "ngff::plate::inconsistent_well",
+            Self::InvalidWellPath(_) => "ngff::plate::invalid_well_path",
+            Self::NonexistentRow(..) => "ngff::plate::nonexistent_row",
+            Self::NonexistentColumn(..) => "ngff::plate::nonexistent_column",
+            Self::NoIndex(..) => "ngff::plate::no_index",
+            Self::NonUniqueWell(_) => "ngff::plate::non_unique_well",
+            Self::NonUniqueIndex(_) => "ngff::plate::non_unique_index",
+            Self::InvalidIndex(_) => "ngff::plate::invalid_index",
+            Self::NonUniqueAcquisitionId(_) => "ngff::plate::non_unique_acquisition",
+            Self::AcquisitionTime(_) => "ngff::plate::acquisition_time",
+            Self::Version(_) => "ngff::plate::version",
+        }
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        super::spec_url("plate-md")
+    }
+}
+
+impl_spec_diagnostic!(InvalidPlate);
+
+/// The standard label of the row at a 0-based index: `A` to `Z`, then `AA`, `AB`, ...
+pub fn row_label(index: usize) -> String {
+    let mut label = String::default();
+    let mut n = index + 1;
+    while n > 0 {
+        n -= 1;
+        label.insert(0, char::from(b'A' + (n % 26) as u8));
+        n /= 26;
+    }
+    label
+}
+
+/// The standard label of the column at a 0-based index, i.e. the 1-based number,
+/// zero-padded to at least `width` digits.
+pub fn column_label(index: usize, width: usize) -> String {
+    format!("{:0width$}", index + 1)
+}
+
+/// The 0-based index of a row label generated by [row_label] (case-insensitive).
+pub fn parse_row_label(label: &str) -> Option<usize> {
+    if label.is_empty() {
+        return None;
+    }
+    label
+        .chars()
+        .try_fold(0usize, |acc, c| {
+            let digit = c.to_ascii_uppercase();
+            if !digit.is_ascii_uppercase() {
+                return None;
+            }
+            acc.checked_mul(26)?
+                .checked_add(usize::from(digit as u8 - b'A') + 1)
+        })
+        .map(|n| n - 1)
+}
+
+/// The 0-based index of a column label generated by [column_label] (zero-padded or not).
+pub fn parse_column_label(label: &str) -> Option<usize> {
+    if !label.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    label.parse::<usize>().ok()?.checked_sub(1)
+}
+
+/// Whether a plate operation applies to rows or columns.
+#[derive(Debug, Clone, Copy)]
+enum Dim {
+    Row,
+    Column,
+}
+
+impl Dim {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Row => "row",
+            Self::Column => "column",
+        }
+    }
+
+    fn index(&self, well: &PlateWell) -> usize {
+        match self {
+            Self::Row => well.row_index,
+            Self::Column => well.column_index,
+        }
+    }
+
+    fn index_mut<'a>(&self, well: &'a mut PlateWell) -> &'a mut usize {
+        match self {
+            Self::Row => &mut well.row_index,
+            Self::Column => &mut well.column_index,
+        }
+    }
 }
 
 fn validate_index(idxs: &[Index]) -> Result<(), InvalidPlate> {
     let mut names = HashSet::with_capacity(idxs.len());
     for name in idxs.iter().map(|idx| idx.name.as_str()) {
         if !names.insert(name) {
-            return Err(InvalidPlate::NonUniqueIndex);
+            return Err(InvalidPlate::NonUniqueIndex(name.to_owned()));
         }
         if !name.chars().all(char::is_alphanumeric) {
-            return Err(InvalidPlate::InvalidIndex);
+            return Err(InvalidPlate::InvalidIndex(name.to_owned()));
         }
     }
     Ok(())
 }
 
 impl Plate {
+    /// A plate with the given rows and columns, and no wells or acquisitions.
+    pub fn new(rows: Vec<Index>, columns: Vec<Index>) -> Result<Self, InvalidPlate> {
+        validate_index(rows.as_slice())?;
+        validate_index(columns.as_slice())?;
+        Ok(Self {
+            acquisitions: None,
+            columns,
+            field_count: None,
+            name: None,
+            rows,
+            version: Some(super::VERSION.to_owned()),
+            wells: Vec::default(),
+        })
+    }
+
+    /// A plate with the standard row and column labels ([row_label] and unpadded [column_label]),
+    /// and no wells or acquisitions.
+    pub fn with_size(n_rows: usize, n_columns: usize) -> Self {
+        let rows = (0..n_rows).map(|r| Index::new(row_label(r))).collect();
+        let columns = (0..n_columns)
+            .map(|c| Index::new(column_label(c, 0)))
+            .collect();
+        Self::new(rows, columns).expect("standard labels are unique and alphanumeric")
+    }
+
+    /// Add a well at the given row and column, returning it.
+    ///
+    /// Fails if the row or column does not exist, or the plate already has that well.
+    pub fn add_well(
+        &mut self,
+        row_index: usize,
+        column_index: usize,
+    ) -> Result<&PlateWell, InvalidPlate> {
+        let well = PlateWell::new(row_index, column_index, &self.rows, &self.columns)?;
+        if self.wells.iter().any(|w| w.path == well.path) {
+            return Err(InvalidPlate::NonUniqueWell(well.path));
+        }
+        self.wells.push(well);
+        Ok(self.wells.last().expect("just pushed"))
+    }
+
+    /// Add an acquisition, failing if the plate already has one with the same ID.
+    pub fn add_acquisition(&mut self, acquisition: Acquisition) -> Result<(), InvalidPlate> {
+        let acqs = self.acquisitions.get_or_insert_with(Vec::default);
+        if acqs.iter().any(|a| a.id == acquisition.id) {
+            return Err(InvalidPlate::NonUniqueAcquisitionId(acquisition.id));
+        }
+        acqs.push(acquisition);
+        Ok(())
+    }
+
+    /// Append a row, returning its index.
+    pub fn add_row<S: Into<Name>>(&mut self, name: S) -> Result<usize, InvalidPlate> {
+        self.add_index(Dim::Row, name.into())
+    }
+
+    /// Append a column, returning its index.
+    pub fn add_column<S: Into<Name>>(&mut self, name: S) -> Result<usize, InvalidPlate> {
+        self.add_index(Dim::Column, name.into())
+    }
+
+    /// Rename a row, updating the paths of its wells.
+    pub fn rename_row<S: Into<Name>>(&mut self, index: usize, name: S) -> Result<(), InvalidPlate> {
+        self.rename_index(Dim::Row, index, name.into())
+    }
+
+    /// Rename a column, updating the paths of its wells.
+    pub fn rename_column<S: Into<Name>>(
+        &mut self,
+        index: usize,
+        name: S,
+    ) -> Result<(), InvalidPlate> {
+        self.rename_index(Dim::Column, index, name.into())
+    }
+
+    /// Remove a row and its wells, returning the removed wells.
+    ///
+    /// Later rows' indices are shifted down.
+    pub fn remove_row(&mut self, index: usize) -> Result<Vec<PlateWell>, InvalidPlate> {
+        self.remove_index(Dim::Row, index)
+    }
+
+    /// Remove a column and its wells, returning the removed wells.
+    ///
+    /// Later columns' indices are shifted down.
+    pub fn remove_column(&mut self, index: usize) -> Result<Vec<PlateWell>, InvalidPlate> {
+        self.remove_index(Dim::Column, index)
+    }
+
+    fn indices_mut(&mut self, dim: Dim) -> &mut Vec<Index> {
+        match dim {
+            Dim::Row => &mut self.rows,
+            Dim::Column => &mut self.columns,
+        }
+    }
+
+    fn add_index(&mut self, dim: Dim, name: Name) -> Result<usize, InvalidPlate> {
+        let indices = self.indices_mut(dim);
+        let mut updated = indices.clone();
+        updated.push(Index { name });
+        validate_index(&updated)?;
+        *indices = updated;
+        Ok(indices.len() - 1)
+    }
+
+    fn rename_index(&mut self, dim: Dim, index: usize, name: Name) -> Result<(), InvalidPlate> {
+        let indices = self.indices_mut(dim);
+        let mut updated = indices.clone();
+        updated
+            .get_mut(index)
+            .ok_or(InvalidPlate::NoIndex(dim.as_str(), index))?
+            .name = name;
+        validate_index(&updated)?;
+        *indices = updated;
+        for well in self.wells.iter_mut() {
+            if dim.index(well) == index {
+                well.path = format!(
+                    "{}/{}",
+                    self.rows[well.row_index].name, self.columns[well.column_index].name
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_index(&mut self, dim: Dim, index: usize) -> Result<Vec<PlateWell>, InvalidPlate> {
+        let indices = self.indices_mut(dim);
+        if index >= indices.len() {
+            return Err(InvalidPlate::NoIndex(dim.as_str(), index));
+        }
+        indices.remove(index);
+        let (removed, mut kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.wells)
+            .into_iter()
+            .partition(|w| dim.index(w) == index);
+        for well in kept.iter_mut() {
+            let idx = dim.index_mut(well);
+            if *idx > index {
+                *idx -= 1;
+            }
+        }
+        self.wells = kept;
+        Ok(removed)
+    }
+
+    /// Set the maximum number of fields of view in any well.
+    pub fn set_field_count(&mut self, field_count: NonZeroUsize) {
+        self.field_count = Some(field_count.get());
+    }
+
+    pub fn field_count(&self) -> Option<usize> {
+        self.field_count
+    }
+
+    pub fn acquisitions(&self) -> Option<&[Acquisition]> {
+        self.acquisitions.as_deref()
+    }
+
+    pub fn rows(&self) -> &[Index] {
+        self.rows.as_slice()
+    }
+
+    pub fn columns(&self) -> &[Index] {
+        self.columns.as_slice()
+    }
+
+    pub fn wells(&self) -> &[PlateWell] {
+        self.wells.as_slice()
+    }
+
     pub fn validate(&self) -> Result<(), InvalidPlate> {
+        if let Some(v) = &self.version {
+            if v != super::VERSION {
+                return Err(InvalidPlate::Version(v.clone()));
+            }
+        }
         validate_index(self.rows.as_slice())?;
         validate_index(self.columns.as_slice())?;
         if let Some(acqs) = self.acquisitions.as_ref() {
             validate_acquisitions(acqs.as_slice())?;
         }
-        for well in self.wells.iter() {
-            let row_name = self
-                .rows
-                .get(well.row_index)
-                .ok_or(InvalidPlate::NonexistentWell(well.row_index))?
-                .name
-                .as_str();
-            let col_name = self
-                .columns
-                .get(well.column_index)
-                .ok_or(InvalidPlate::NonexistentWell(well.column_index))?
-                .name
-                .as_str();
-
-            if well.path != format!("{row_name}/{col_name}") {
-                return Err(InvalidPlate::InconsistentWells);
-            }
+        try_for_each_item(self.wells.as_slice(), |well| self.validate_well(well))?;
+        let mut paths = HashSet::with_capacity(self.wells.len());
+        match self.wells.iter().find(|w| !paths.insert(w.path.as_str())) {
+            Some(well) => Err(InvalidPlate::NonUniqueWell(well.path.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_well(&self, well: &PlateWell) -> Result<(), InvalidPlate> {
+        let row_name = self
+            .rows
+            .get(well.row_index)
+            .ok_or_else(|| InvalidPlate::NonexistentRow(well.path.clone(), well.row_index))?
+            .name
+            .as_str();
+        let col_name = self
+            .columns
+            .get(well.column_index)
+            .ok_or_else(|| InvalidPlate::NonexistentColumn(well.path.clone(), well.column_index))?
+            .name
+            .as_str();
+
+        if well.path != format!("{row_name}/{col_name}") {
+            return Err(InvalidPlate::InconsistentWells(well.path.clone()));
         }
         Ok(())
     }
 
+    /// Apply canonical forms: sort wells by row then column,
+    /// sort acquisitions by ID, and drop an empty acquisition list.
+    pub fn normalize(&mut self) {
+        self.wells.sort_by_key(|w| (w.row_index, w.column_index));
+        if let Some(acqs) = self.acquisitions.as_mut() {
+            acqs.sort_by_key(|a| a.id);
+        }
+        if self.acquisitions.as_ref().is_some_and(Vec::is_empty) {
+            self.acquisitions = None;
+        }
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Set the version to [super::VERSION] if it is missing.
+    pub fn fill_version(&mut self) {
+        self.version
+            .get_or_insert_with(|| super::VERSION.to_owned());
+    }
+
+    /// Validate the plate's wells' metadata, including cross-checks against
+    /// the plate's acquisitions if it has any.
+    ///
+    /// Wells are validated in parallel with the `rayon` feature.
+    pub fn validate_wells(&self, wells: &[Well]) -> Result<(), InvalidWell> {
+        let ids = self.acquisitions.as_ref().map(|_| self.acquisition_ids());
+        try_for_each_item(wells, |w| w.validate_against(ids.as_ref()))
+    }
+
+    /// Key of a field of view's image group, relative to the plate group.
+    pub fn image_path(&self, well: &PlateWell, fov: &FieldOfView) -> ZPath {
+        join_key([well.path.as_str(), fov.path()])
+    }
+
+    /// Walk plate → wells → fields of view in a store, where the plate group is at `plate_group`.
+    ///
+    /// Yields each image group's path (relative to the store root) and its metadata.
+    /// A well which cannot be loaded yields a single error.
+    pub fn iter_images<'a, S: ReadableStore + ?Sized>(
+        &'a self,
+        store: &'a S,
+        plate_group: &'a str,
+    ) -> impl Iterator<Item = Result<(ZPath, NgffMetadata), LoadError>> + 'a {
+        self.wells.iter().flat_map(move |pw| {
+            let well_group = join_key([plate_group, pw.path.as_str()]);
+            let well = NgffMetadata::from_store(store, &well_group).and_then(|m| {
+                m.well
+                    .ok_or_else(|| LoadError::MissingMetadata(well_group.clone(), "well"))
+            });
+            let images: Vec<_> = match well {
+                Ok(w) => w
+                    .image_paths(&well_group)
+                    .into_iter()
+                    .map(|path| NgffMetadata::from_store(store, &path).map(|m| (path, m)))
+                    .collect(),
+                Err(e) => vec![Err(e)],
+            };
+            images
+        })
+    }
+
     pub fn acquisition_ids(&self) -> HashSet<AcquisitionId> {
         self.acquisitions
             .as_ref()
             .map(|acs| acs.iter().map(|a| a.id).collect())
             .unwrap_or(HashSet::with_capacity(0))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json;
+    /// Counts of the plate's wells, and of the fields of view in the given wells' metadata
+    /// (which may be empty if the wells have not been loaded).
+    pub fn summary(&self, wells: &[Well]) -> PlateSummary {
+        let mut fields_per_acquisition = BTreeMap::default();
+        for fov in wells.iter().flat_map(Well::images) {
+            *fields_per_acquisition.entry(fov.acquisition()).or_default() += 1;
+        }
+        PlateSummary {
+            rows: self.rows.len(),
+            columns: self.columns.len(),
+            wells: self.wells.len(),
+            acquisitions: self.acquisitions.as_ref().map_or(0, Vec::len),
+            fields_per_acquisition,
+        }
+    }
 
-    const EXAMPLE1: &str = r#"
-        {
-            "acquisitions": [
-                {
-                    "id": 1,
-                    "maximumfieldcount": 2,
-                    "name": "Meas_01(2012-07-31_10-41-12)",
-                    "starttime": 1343731272000
-                },
-                {
-                    "id": 2,
-                    "maximumfieldcount": 2,
-                    "name": "Meas_02(201207-31_11-56-41)",
-                    "starttime": 1343735801000
-                }
-            ],
-            "columns": [
-                {
-                    "name": "1"
-                },
-                {
-                    "name": "2"
-                },
-                {
-                    "name": "3"
-                }
-            ],
-            "field_count": 4,
-            "name": "test",
-            "rows": [
-                {
-                    "name": "A"
-                },
-                {
-                    "name": "B"
-                }
-            ],
-            "version": "0.4",
-            "wells": [
-                {
-                    "path": "A/1",
-                    "rowIndex": 0,
-                    "columnIndex": 0
-                },
-                {
-                    "path": "A/2",
-                    "rowIndex": 0,
-                    "columnIndex": 1
-                },
-                {
-                    "path": "A/3",
-                    "rowIndex": 0,
-                    "columnIndex": 2
-                },
-                {
-                    "path": "B/1",
-                    "rowIndex": 1,
-                    "columnIndex": 0
-                },
-                {
-                    "path": "B/2",
-                    "rowIndex": 1,
-                    "columnIndex": 1
-                },
-                {
-                    "path": "B/3",
-                    "rowIndex": 1,
-                    "columnIndex": 2
+    /// A text map of the plate, with a line per row and a mark for each occupied position.
+    ///
+    /// ```text
+    ///   1 2 3
+    /// A ● ● ●
+    /// B ● · ●
+    /// ```
+    pub fn render_grid(&self, charset: GridCharset) -> String {
+        self.render(charset, |_| None)
+    }
+
+    /// A text map of the plate as [Plate::render_grid], with each occupied position colored
+    /// by the acquisition its fields belong to (using ANSI escape codes).
+    ///
+    /// Positions for which `acquisition` returns `None` are not colored.
+    pub fn render_grid_by_acquisition<F>(&self, charset: GridCharset, acquisition: F) -> String
+    where
+        F: Fn(&PlateWell) -> Option<AcquisitionId>,
+    {
+        self.render(charset, acquisition)
+    }
+
+    fn render<F>(&self, charset: GridCharset, acquisition: F) -> String
+    where
+        F: Fn(&PlateWell) -> Option<AcquisitionId>,
+    {
+        let (occupied, empty) = match charset {
+            GridCharset::Ascii => ('#', '.'),
+            GridCharset::Unicode => ('●', '·'),
+        };
+        let wells: HashMap<_, _> = self
+            .wells
+            .iter()
+            .map(|w| ((w.row_index, w.column_index), w))
+            .collect();
+        // padding in `format!` counts characters, not bytes
+        let width = |idxs: &[Index]| idxs.iter().map(|i| i.name.chars().count()).max();
+        let row_width = width(&self.rows).unwrap_or(0);
+        let col_width = width(&self.columns).unwrap_or(0);
+
+        let mut lines = Vec::with_capacity(self.rows.len() + 1);
+        let mut header = " ".repeat(row_width);
+        for col in self.columns.iter() {
+            header.push_str(&format!(" {:>col_width$}", col.name.as_str()));
+        }
+        lines.push(header);
+        for (r, row) in self.rows.iter().enumerate() {
+            let mut line = format!("{:>row_width$}", row.name.as_str());
+            for c in 0..self.columns.len() {
+                line.push_str(&" ".repeat(col_width));
+                let Some(well) = wells.get(&(r, c)) else {
+                    line.push(empty);
+                    continue;
+                };
+                match acquisition(well) {
+                    // cycle through red, green, yellow, blue, magenta, and cyan
+                    Some(id) => line.push_str(&format!("\x1b[{}m{occupied}\x1b[0m", 31 + id % 6)),
+                    None => line.push(occupied),
                 }
-            ]
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+}
+
+/// The characters used by [Plate::render_grid].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GridCharset {
+    /// `#` for occupied positions and `.` for empty ones.
+    Ascii,
+    /// `●` for occupied positions and `·` for empty ones.
+    #[default]
+    Unicode,
+}
+
+/// Summary statistics of a plate, from [Plate::summary].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlateSummary {
+    pub rows: usize,
+    pub columns: usize,
+    /// The number of occupied positions.
+    pub wells: usize,
+    pub acquisitions: usize,
+    /// The number of fields of view in each acquisition,
+    /// under `None` for fields without an acquisition.
+    pub fields_per_acquisition: BTreeMap<Option<AcquisitionId>, usize>,
+}
+
+impl PlateSummary {
+    /// The number of positions on the plate.
+    pub fn positions(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// The fraction of positions which are occupied by wells, or 0 for a plate with no positions.
+    pub fn occupancy(&self) -> f64 {
+        match self.positions() {
+            0 => 0.0,
+            n => self.wells as f64 / n as f64,
         }
-    "#;
+    }
 
-    const EXAMPLE2: &str = r#"
-        {
-            "acquisitions": [
-                {
-                    "id": 1,
-                    "maximumfieldcount": 1,
-                    "name": "single acquisition",
-                    "starttime": 1343731272000
-                }
-            ],
-            "columns": [
-                {
-                    "name": "1"
-                },
-                {
-                    "name": "2"
-                },
-                {
-                    "name": "3"
-                },
-                {
-                    "name": "4"
-                },
-                {
-                    "name": "5"
-                },
-                {
-                    "name": "6"
-                },
-                {
-                    "name": "7"
-                },
-                {
-                    "name": "8"
-                },
-                {
-                    "name": "9"
-                },
-                {
-                    "name": "10"
-                },
-                {
-                    "name": "11"
-                },
-                {
-                    "name": "12"
+    /// The number of fields of view across all acquisitions.
+    pub fn fields(&self) -> usize {
+        self.fields_per_acquisition.values().sum()
+    }
+}
+
+impl fmt::Display for PlateSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{} plate with {}/{} wells ({:.1}%), {} acquisitions, {} fields",
+            self.rows,
+            self.columns,
+            self.wells,
+            self.positions(),
+            self.occupancy() * 100.0,
+            self.acquisitions,
+            self.fields(),
+        )
+    }
+}
+
+/// Incremental parser for plates with many wells.
+///
+/// Each [PlateWell] is handed to a callback as soon as it is parsed,
+/// rather than collecting them all before returning.
+pub struct PlateReader<R> {
+    reader: R,
+    wrapped: bool,
+}
+
+impl<R: Read> PlateReader<R> {
+    /// Read a bare plate object.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            wrapped: false,
+        }
+    }
+
+    /// Read the `plate` key of a group's attributes, ignoring other keys.
+    pub fn from_attributes(reader: R) -> Self {
+        Self {
+            reader,
+            wrapped: true,
+        }
+    }
+
+    /// Parse the plate, calling `f` on each well in document order.
+    ///
+    /// The returned plate has every field except `wells`, which is empty.
+    pub fn for_each_well<F: FnMut(PlateWell)>(self, f: F) -> Result<Plate, JsonError> {
+        let mut de = serde_json::Deserializer::from_reader(self.reader);
+        let seed = PlateSeed(f);
+        let plate = if self.wrapped {
+            AttributesSeed(seed).deserialize(&mut de)
+        } else {
+            seed.deserialize(&mut de)
+        }
+        .and_then(|p| de.end().map(|_| p))
+        .map_err(|e| JsonError::Read("Plate", e))?;
+        Ok(plate)
+    }
+}
+
+struct AttributesSeed<F>(PlateSeed<F>);
+
+impl<'de, F: FnMut(PlateWell)> DeserializeSeed<'de> for AttributesSeed<F> {
+    type Value = Plate;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Plate, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(PlateWell)> Visitor<'de> for AttributesSeed<F> {
+    type Value = Plate;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an attributes object with a plate key")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Plate, A::Error> {
+        let mut seed = Some(self.0);
+        let mut plate = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match (key.as_str(), seed.take()) {
+                ("plate", Some(s)) => plate = Some(map.next_value_seed(s)?),
+                (_, s) => {
+                    seed = s;
+                    map.next_value::<IgnoredAny>()?;
                 }
-            ],
-            "field_count": 1,
-            "name": "sparse test",
-            "rows": [
-                {
-                    "name": "A"
-                },
-                {
-                    "name": "B"
-                },
-                {
-                    "name": "C"
-                },
-                {
-                    "name": "D"
-                },
-                {
-                    "name": "E"
-                },
-                {
-                    "name": "F"
-                },
-                {
-                    "name": "G"
-                },
-                {
-                    "name": "H"
+            }
+        }
+        plate.ok_or_else(|| serde::de::Error::missing_field("plate"))
+    }
+}
+
+struct PlateSeed<F>(F);
+
+impl<'de, F: FnMut(PlateWell)> DeserializeSeed<'de> for PlateSeed<F> {
+    type Value = Plate;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Plate, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(PlateWell)> Visitor<'de> for PlateSeed<F> {
+    type Value = Plate;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a plate object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<Plate, A::Error> {
+        use serde::de::Error;
+
+        let mut acquisitions = None;
+        let mut columns = None;
+        let mut field_count = None;
+        let mut name = None;
+        let mut rows = None;
+        let mut version = None;
+        let mut has_wells = false;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "acquisitions" => next_once(&mut map, &mut acquisitions, "acquisitions")?,
+                "columns" => next_once(&mut map, &mut columns, "columns")?,
+                "field_count" => next_once(&mut map, &mut field_count, "field_count")?,
+                "name" => next_once(&mut map, &mut name, "name")?,
+                "rows" => next_once(&mut map, &mut rows, "rows")?,
+                "version" => next_once(&mut map, &mut version, "version")?,
+                "wells" => {
+                    if has_wells {
+                        return Err(A::Error::duplicate_field("wells"));
+                    }
+                    map.next_value_seed(WellsSeed(&mut self.0))?;
+                    has_wells = true;
                 }
-            ],
-            "version": "0.4",
-            "wells": [
-                {
-                    "path": "C/5",
-                    "rowIndex": 2,
-                    "columnIndex": 4
-                },
-                {
-                    "path": "D/7",
-                    "rowIndex": 3,
-                    "columnIndex": 6
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
                 }
-            ]
+            }
         }
-    "#;
+        if !has_wells {
+            return Err(A::Error::missing_field("wells"));
+        }
+        Ok(Plate {
+            acquisitions: acquisitions.flatten(),
+            columns: columns.ok_or_else(|| A::Error::missing_field("columns"))?,
+            field_count: field_count.flatten(),
+            name: name.flatten(),
+            rows: rows.ok_or_else(|| A::Error::missing_field("rows"))?,
+            version: version.flatten(),
+            wells: Vec::default(),
+        })
+    }
+}
+
+/// Read a field's value from the map, failing if the field has already been read.
+fn next_once<'de, A, T>(
+    map: &mut A,
+    value: &mut Option<T>,
+    field: &'static str,
+) -> Result<(), A::Error>
+where
+    A: MapAccess<'de>,
+    T: Deserialize<'de>,
+{
+    if value.is_some() {
+        return Err(serde::de::Error::duplicate_field(field));
+    }
+    *value = Some(map.next_value()?);
+    Ok(())
+}
+
+struct WellsSeed<'a, F>(&'a mut F);
+
+impl<'de, F: FnMut(PlateWell)> DeserializeSeed<'de> for WellsSeed<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(PlateWell)> Visitor<'de> for WellsSeed<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of wells")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(well) = seq.next_element::<PlateWell>()? {
+            (self.0)(well);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod arbitrary {
+    use super::*;
+    use crate::util::Validity;
+    use proptest::prelude::*;
+
+    fn arb_acquisitions(validity: Validity) -> BoxedStrategy<Vec<Acquisition>> {
+        let times = (0u64..u64::MAX / 2, 0u64..1_000_000);
+        match validity {
+            Validity::Valid => prop::collection::btree_map(0u64..100, times, 0..4)
+                .prop_map(|acqs| {
+                    acqs.into_iter()
+                        .map(|(id, (start, duration))| Acquisition {
+                            id,
+                            name: None,
+                            maximum_field_count: None,
+                            description: None,
+                            start_time: Some(start),
+                            end_time: Some(start + duration),
+                        })
+                        .collect()
+                })
+                .boxed(),
+            Validity::Any => prop::collection::vec(
+                (
+                    0u64..4,
+                    prop::option::of(any::<u64>()),
+                    prop::option::of(any::<u64>()),
+                ),
+                0..4,
+            )
+            .prop_map(|acqs| {
+                acqs.into_iter()
+                    .map(|(id, start_time, end_time)| Acquisition {
+                        id,
+                        name: None,
+                        maximum_field_count: None,
+                        description: None,
+                        start_time,
+                        end_time,
+                    })
+                    .collect()
+            })
+            .boxed(),
+        }
+    }
+
+    impl Arbitrary for Plate {
+        type Parameters = Validity;
+        type Strategy = BoxedStrategy<Self>;
+
+        /// Valid plates have up to 32 rows (A, B, ...) and 48 columns (1, 2, ...),
+        /// with a random subset of positions occupied by wells.
+        fn arbitrary_with(validity: Validity) -> Self::Strategy {
+            match validity {
+                Validity::Valid => (1usize..=32, 1usize..=48)
+                    .prop_flat_map(move |(n_rows, n_cols)| {
+                        (
+                            Just((n_rows, n_cols)),
+                            prop::collection::btree_set((0..n_rows, 0..n_cols), 0..=16),
+                            arb_acquisitions(validity),
+                        )
+                    })
+                    .prop_map(|((n_rows, n_cols), positions, acqs)| {
+                        let rows: Vec<Index> = (0..n_rows)
+                            .map(|r| Index {
+                                name: row_label(r).into(),
+                            })
+                            .collect();
+                        let columns: Vec<Index> = (0..n_cols)
+                            .map(|c| Index {
+                                name: column_label(c, 0).into(),
+                            })
+                            .collect();
+                        let wells = positions
+                            .into_iter()
+                            .map(|(r, c)| PlateWell {
+                                path: format!("{}/{}", rows[r].name, columns[c].name),
+                                row_index: r,
+                                column_index: c,
+                            })
+                            .collect();
+                        Plate {
+                            acquisitions: Some(acqs).filter(|a| !a.is_empty()),
+                            columns,
+                            field_count: None,
+                            name: None,
+                            rows,
+                            version: Some(crate::v0_4::VERSION.to_owned()),
+                            wells,
+                        }
+                    })
+                    .boxed(),
+                Validity::Any => (
+                    prop::collection::vec("[A-Za-z0-9_]{1,2}", 0..4),
+                    prop::collection::vec("[A-Za-z0-9_]{1,2}", 0..4),
+                    prop::collection::vec(("[A-Z]/[0-9]", 0usize..4, 0usize..4), 0..8),
+                    arb_acquisitions(validity),
+                )
+                    .prop_map(|(rows, columns, wells, acqs)| Plate {
+                        acquisitions: Some(acqs),
+                        columns: columns
+                            .into_iter()
+                            .map(|n| Index { name: n.into() })
+                            .collect(),
+                        field_count: None,
+                        name: None,
+                        rows: rows.into_iter().map(|n| Index { name: n.into() }).collect(),
+                        version: None,
+                        wells: wells
+                            .into_iter()
+                            .map(|(path, row_index, column_index)| PlateWell {
+                                path,
+                                row_index,
+                                column_index,
+                            })
+                            .collect(),
+                    })
+                    .boxed(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    /// The spec's 6-well plate example, from the corpus.
+    fn example1() -> Plate {
+        example("plate_6wells")
+    }
+
+    /// The spec's 2-well plate example, from the corpus.
+    fn example2() -> Plate {
+        example("plate_2wells")
+    }
+
+    fn example(name: &str) -> Plate {
+        let fixture = crate::v0_4::corpus::fixture(name).unwrap();
+        fixture.parse().unwrap().plate().unwrap().clone()
+    }
 
     #[test]
     fn examples() {
-        let p1: Plate = serde_json::from_str(EXAMPLE1).unwrap();
+        let p1: Plate = example1();
         p1.validate().unwrap();
 
-        let p2: Plate = serde_json::from_str(EXAMPLE2).unwrap();
+        let p2: Plate = example2();
         p2.validate().unwrap();
     }
+
+    #[test]
+    fn invalid_plate_context() {
+        let mut plate: Plate = example1();
+        plate.wells[4].path = "B/3".to_owned();
+        assert_eq!(
+            plate.validate(),
+            Err(InvalidPlate::InconsistentWells("B/3".to_owned()))
+        );
+        plate.rows[1].name = "A".into();
+        assert_eq!(
+            plate.validate(),
+            Err(InvalidPlate::NonUniqueIndex("A".to_owned()))
+        );
+    }
+
+    #[test]
+    fn cross_validate_wells() {
+        let plate: Plate = example1();
+        let good: Well =
+            serde_json::from_str(r#"{"images": [{"path": "0", "acquisition": 2}]}"#).unwrap();
+        let bad: Well =
+            serde_json::from_str(r#"{"images": [{"path": "0", "acquisition": 3}]}"#).unwrap();
+        plate.validate_wells(&[good.clone(), good.clone()]).unwrap();
+        assert_eq!(
+            plate.validate_wells(&[good, bad]).unwrap_err(),
+            InvalidWell::UnknownAcquisition(3)
+        );
+    }
+
+    #[test]
+    fn plate_well() {
+        let plate: Plate = example1();
+        let well = PlateWell::new(1, 2, plate.rows(), plate.columns()).unwrap();
+        assert_eq!(well.path(), "B/3");
+        assert_eq!(
+            PlateWell::new(2, 0, plate.rows(), plate.columns()).unwrap_err(),
+            InvalidPlate::NonexistentRow("2/0".to_owned(), 2)
+        );
+        assert_eq!(
+            PlateWell::new(0, 3, plate.rows(), plate.columns()).unwrap_err(),
+            InvalidPlate::NonexistentColumn("0/3".to_owned(), 3)
+        );
+    }
+
+    #[test]
+    fn labels() {
+        for (idx, label) in [
+            (0, "A"),
+            (25, "Z"),
+            (26, "AA"),
+            (27, "AB"),
+            (701, "ZZ"),
+            (702, "AAA"),
+        ] {
+            assert_eq!(row_label(idx), label);
+            assert_eq!(parse_row_label(label), Some(idx));
+        }
+        assert_eq!(parse_row_label("ab"), Some(27));
+        assert_eq!(parse_row_label("A1"), None);
+        assert_eq!(parse_row_label(""), None);
+
+        assert_eq!(column_label(0, 0), "1");
+        assert_eq!(column_label(8, 2), "09");
+        assert_eq!(column_label(99, 2), "100");
+        assert_eq!(parse_column_label("09"), Some(8));
+        assert_eq!(parse_column_label("0"), None);
+        assert_eq!(parse_column_label("+1"), None);
+
+        let plate = Plate::with_size(16, 24);
+        assert_eq!(plate.rows()[15].name(), "P");
+        assert_eq!(plate.columns()[23].name(), "24");
+    }
+
+    #[test]
+    fn well_paths() {
+        assert_eq!(parse_well_path("A/1"), Ok(("A", "1")));
+        assert_eq!(parse_well_path("AB/12"), Ok(("AB", "12")));
+        for path in ["A1", "A/", "/1", "A/1/2", "A-/1"] {
+            assert_eq!(
+                parse_well_path(path),
+                Err(InvalidPlate::InvalidWellPath(path.to_owned()))
+            );
+        }
+        let plate: Plate = example1();
+        assert_eq!(plate.wells()[4].split_path(), Ok(("B", "2")));
+        assert_eq!(
+            InvalidPlate::InvalidWellPath("A1".to_owned()).to_string(),
+            r#"Well path "A1" is not of the form "{row}/{column}""#
+        );
+    }
+
+    #[test]
+    fn summary() {
+        let plate: Plate = example1();
+        let wells: Vec<Well> = [
+            r#"{"images": [{"path": "0", "acquisition": 1}, {"path": "1", "acquisition": 2}]}"#,
+            r#"{"images": [{"path": "0", "acquisition": 1}, {"path": "1"}]}"#,
+        ]
+        .into_iter()
+        .map(|w| serde_json::from_str(w).unwrap())
+        .collect();
+        let summary = plate.summary(&wells);
+        assert_eq!(summary.positions(), 6);
+        assert_eq!(summary.occupancy(), 1.0);
+        assert_eq!(
+            summary.fields_per_acquisition,
+            BTreeMap::from([(None, 1), (Some(1), 2), (Some(2), 1)])
+        );
+        assert_eq!(
+            summary.to_string(),
+            "2x3 plate with 6/6 wells (100.0%), 2 acquisitions, 4 fields"
+        );
+        assert_eq!(Plate::with_size(0, 0).summary(&[]).occupancy(), 0.0);
+    }
+
+    #[test]
+    fn render_grid() {
+        let mut plate = Plate::with_size(2, 10);
+        plate.add_well(0, 0).unwrap();
+        plate.add_well(1, 9).unwrap();
+        assert_eq!(
+            plate.render_grid(GridCharset::Ascii),
+            [
+                "   1  2  3  4  5  6  7  8  9 10",
+                "A  #  .  .  .  .  .  .  .  .  .",
+                "B  .  .  .  .  .  .  .  .  .  #",
+            ]
+            .join("\n")
+        );
+        let colored =
+            plate.render_grid_by_acquisition(GridCharset::Unicode, |w| Some(w.row_index() as u64));
+        assert!(colored.contains("\x1b[31m●\x1b[0m"));
+        assert!(colored.ends_with("·  \x1b[32m●\x1b[0m"));
+
+        let rows = vec![Index::new("Ä"), Index::new("B")];
+        let columns = vec![Index::new("é1"), Index::new("2")];
+        let mut plate = Plate::new(rows, columns).unwrap();
+        plate.add_well(0, 1).unwrap();
+        assert_eq!(
+            plate.render_grid(GridCharset::Ascii),
+            ["  é1  2", "Ä  .  #", "B  .  ."].join("\n")
+        );
+    }
+
+    #[test]
+    fn grow_plate() {
+        let rows = vec![Index::new("A"), Index::new("B")];
+        let columns = vec![Index::new("1")];
+        let mut plate = Plate::new(rows, columns).unwrap();
+        assert_eq!(plate.add_well(1, 0).unwrap().path(), "B/1");
+        assert_eq!(
+            plate.add_well(1, 0).unwrap_err(),
+            InvalidPlate::NonUniqueWell("B/1".to_owned())
+        );
+        plate.add_well(0, 0).unwrap();
+        plate
+            .add_acquisition(Acquisition::builder(1).build().unwrap())
+            .unwrap();
+        assert_eq!(
+            plate
+                .add_acquisition(Acquisition::builder(1).build().unwrap())
+                .unwrap_err(),
+            InvalidPlate::NonUniqueAcquisitionId(1)
+        );
+        plate.set_field_count(NonZeroUsize::new(2).unwrap());
+        plate.validate().unwrap();
+        assert_eq!(plate.wells().len(), 2);
+
+        let mut duplicated = plate.clone();
+        duplicated.wells.push(plate.wells()[0].clone());
+        assert_eq!(
+            duplicated.validate().unwrap_err(),
+            InvalidPlate::NonUniqueWell(plate.wells()[0].path().to_owned())
+        );
+
+        assert_eq!(
+            Plate::new(vec![Index::new("A"), Index::new("A")], Vec::default()).unwrap_err(),
+            InvalidPlate::NonUniqueIndex("A".to_owned())
+        );
+    }
+
+    #[test]
+    fn reindex_plate() {
+        let mut plate: Plate = example1();
+        plate.rename_row(1, "C").unwrap();
+        assert_eq!(plate.wells()[4].path(), "C/2");
+        assert_eq!(
+            plate.rename_column(0, "3").unwrap_err(),
+            InvalidPlate::NonUniqueIndex("3".to_owned())
+        );
+        assert_eq!(plate.columns()[0].name(), "1");
+        assert_eq!(
+            plate.rename_row(2, "D").unwrap_err(),
+            InvalidPlate::NoIndex("row", 2)
+        );
+
+        let removed = plate.remove_column(1).unwrap();
+        let paths: Vec<_> = removed.iter().map(PlateWell::path).collect();
+        assert_eq!(paths, ["A/2", "C/2"]);
+        let paths: Vec<_> = plate.wells().iter().map(PlateWell::path).collect();
+        assert_eq!(paths, ["A/1", "A/3", "C/1", "C/3"]);
+        assert_eq!(plate.add_column("2").unwrap(), 2);
+        plate.add_well(0, 2).unwrap();
+        plate.validate().unwrap();
+    }
+
+    #[test]
+    fn acquisition_builder() {
+        let acq = Acquisition::builder(1)
+            .with_name("Meas_01")
+            .with_start_time(1343731272000)
+            .with_end_time(1343731273000)
+            .build()
+            .unwrap();
+        assert_eq!(acq.name(), Some("Meas_01"));
+        assert_eq!(
+            serde_json::to_value(&acq).unwrap(),
+            serde_json::json!({"id": 1, "name": "Meas_01", "starttime": 1343731272000u64, "endtime": 1343731273000u64})
+        );
+        assert_eq!(
+            Acquisition::builder(2)
+                .with_start_time(10)
+                .with_end_time(9)
+                .build()
+                .unwrap_err(),
+            InvalidPlate::AcquisitionTime(2)
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let mut paths = Vec::default();
+        let plate = serde_json::to_string(&example1()).unwrap();
+        let header = PlateReader::new(plate.as_bytes())
+            .for_each_well(|w| paths.push(w.path))
+            .unwrap();
+        assert_eq!(paths, ["A/1", "A/2", "A/3", "B/1", "B/2", "B/3"]);
+        assert_eq!(header.rows.len(), 2);
+        assert!(header.wells.is_empty());
+
+        let plate = serde_json::to_string(&example2()).unwrap();
+        let attrs = format!(r#"{{"other": [1, 2], "plate": {plate}}}"#);
+        let mut count = 0;
+        PlateReader::from_attributes(attrs.as_bytes())
+            .for_each_well(|_| count += 1)
+            .unwrap();
+        assert_eq!(count, 2);
+
+        for key in ["acquisitions", "field_count", "name", "version"] {
+            let plate = format!(
+                r#"{{"rows": [], "columns": [], "wells": [], "{key}": null, "{key}": null}}"#
+            );
+            let err = PlateReader::new(plate.as_bytes())
+                .for_each_well(|_| ())
+                .unwrap_err();
+            assert!(err.to_string().contains("duplicate field"), "{key}: {err}");
+        }
+        let err =
+            PlateReader::new(br#"{"rows": [], "rows": [], "columns": [], "wells": []}"#.as_slice())
+                .for_each_well(|_| ())
+                .unwrap_err();
+        assert!(err.to_string().contains("duplicate field `rows`"));
+    }
+
+    #[test]
+    fn iter_images() {
+        use crate::store::{MemoryStore, WritableStore};
+
+        let plate: Plate = example2();
+        let store = MemoryStore::new();
+        let image = r#"{"multiscales": []}"#;
+        store
+            .set(
+                "plate.zarr/C/5/.zattrs",
+                br#"{"well": {"images": [{"path": "0"}, {"path": "1"}]}}"#,
+            )
+            .unwrap();
+        store
+            .set("plate.zarr/C/5/0/.zattrs", image.as_bytes())
+            .unwrap();
+        store
+            .set("plate.zarr/C/5/1/.zattrs", image.as_bytes())
+            .unwrap();
+
+        let results: Vec<_> = plate.iter_images(&store, "plate.zarr").collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().0, "plate.zarr/C/5/0");
+        assert_eq!(results[1].as_ref().unwrap().0, "plate.zarr/C/5/1");
+        assert!(
+            matches!(&results[2], Err(LoadError::NotFound(k)) if k == "plate.zarr/D/7/.zattrs")
+        );
+        let well: Well = serde_json::from_str(r#"{"images": [{"path": "0"}]}"#).unwrap();
+        assert_eq!(
+            plate.image_path(&plate.wells[1], &well.images()[0]),
+            "D/7/0"
+        );
+    }
+
+    #[test]
+    fn iso8601() {
+        assert_eq!(parse_iso8601_millis("1970-01-01"), Some(0));
+        assert_eq!(
+            parse_iso8601_millis("2021-03-04T05:06:07.89Z"),
+            Some(1_614_834_367_890)
+        );
+        assert_eq!(
+            parse_iso8601_millis("2021-03-04T06:06:07.89+01:00"),
+            Some(1_614_834_367_890)
+        );
+        assert_eq!(
+            parse_iso8601_millis("2000-02-29 00:00"),
+            Some(951_782_400_000)
+        );
+        assert_eq!(parse_iso8601_millis("1969-12-31"), None);
+        assert_eq!(parse_iso8601_millis("2021-13-01"), None);
+        assert_eq!(parse_iso8601_millis("yesterday"), None);
+    }
+
+    #[test]
+    fn iso8601_invalid() {
+        assert_eq!(parse_iso8601_millis("2021-02-31"), None);
+        assert_eq!(parse_iso8601_millis("2021-02-29"), None);
+        assert_eq!(parse_iso8601_millis("1900-02-29"), None);
+        assert_eq!(parse_iso8601_millis("2021-04-31"), None);
+        assert_eq!(parse_iso8601_millis("2021-03-04T05:06:07.ééZ"), None);
+        assert_eq!(parse_iso8601_millis("2021-03-04T05:06:07+0é0"), None);
+        assert_eq!(parse_iso8601_millis("2021-03-04T05:06:07+25:00"), None);
+        assert_eq!(parse_iso8601_millis("99999999999999-01-01"), None);
+        assert_eq!(parse_iso8601_millis("9223372036854775807-12-31"), None);
+    }
 }
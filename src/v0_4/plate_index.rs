@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use super::plate::{Acquisition, AcquisitionId, Plate, PlateWell};
+
+/// Lookup tables built once from a [Plate] for repeated queries.
+///
+/// Where the plate has duplicate paths, positions, or acquisition IDs,
+/// the last occurrence wins; [Plate::validate] rejects such plates.
+#[derive(Debug, Clone)]
+pub struct PlateIndex<'a> {
+    plate: &'a Plate,
+    by_path: HashMap<&'a str, &'a PlateWell>,
+    by_position: HashMap<(usize, usize), &'a PlateWell>,
+    acquisitions: HashMap<AcquisitionId, &'a Acquisition>,
+}
+
+impl<'a> PlateIndex<'a> {
+    pub fn new(plate: &'a Plate) -> Self {
+        let wells = plate.wells();
+        Self {
+            plate,
+            by_path: wells.iter().map(|w| (w.path(), w)).collect(),
+            by_position: wells
+                .iter()
+                .map(|w| ((w.row_index(), w.column_index()), w))
+                .collect(),
+            acquisitions: plate
+                .acquisitions()
+                .unwrap_or_default()
+                .iter()
+                .map(|a| (a.id(), a))
+                .collect(),
+        }
+    }
+
+    pub fn plate(&self) -> &'a Plate {
+        self.plate
+    }
+
+    /// Look up a well by its path, e.g. `"B/7"`.
+    pub fn well_by_path(&self, path: &str) -> Option<&'a PlateWell> {
+        self.by_path.get(path).copied()
+    }
+
+    /// Look up a well by its row and column names.
+    pub fn well_by_names(&self, row: &str, column: &str) -> Option<&'a PlateWell> {
+        self.well_by_path(&format!("{row}/{column}"))
+    }
+
+    /// Look up a well by its row and column indices.
+    pub fn well_at(&self, row_index: usize, column_index: usize) -> Option<&'a PlateWell> {
+        self.by_position.get(&(row_index, column_index)).copied()
+    }
+
+    pub fn acquisition(&self, id: AcquisitionId) -> Option<&'a Acquisition> {
+        self.acquisitions.get(&id).copied()
+    }
+
+    pub fn has_acquisition(&self, id: AcquisitionId) -> bool {
+        self.acquisitions.contains_key(&id)
+    }
+}
+
+impl<'a> From<&'a Plate> for PlateIndex<'a> {
+    fn from(plate: &'a Plate) -> Self {
+        Self::new(plate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+        {
+            "acquisitions": [{"id": 1}, {"id": 5}],
+            "columns": [{"name": "1"}, {"name": "2"}],
+            "rows": [{"name": "A"}, {"name": "B"}],
+            "wells": [
+                {"path": "A/2", "rowIndex": 0, "columnIndex": 1},
+                {"path": "B/1", "rowIndex": 1, "columnIndex": 0}
+            ]
+        }
+    "#;
+
+    #[test]
+    fn lookups() {
+        let plate: Plate = serde_json::from_str(EXAMPLE).unwrap();
+        let idx = PlateIndex::new(&plate);
+        assert_eq!(idx.well_by_path("B/1").unwrap().row_index(), 1);
+        assert_eq!(idx.well_by_names("A", "2").unwrap().column_index(), 1);
+        assert_eq!(idx.well_at(0, 1).unwrap().path(), "A/2");
+        assert!(idx.well_at(0, 0).is_none());
+        assert_eq!(idx.acquisition(5).unwrap().id(), 5);
+        assert!(!idx.has_acquisition(2));
+    }
+}
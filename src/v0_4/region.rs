@@ -0,0 +1,167 @@
+use std::ops::Range;
+
+use super::multiscale::Multiscale;
+use crate::core::{Transform, TransformError};
+use crate::util::{InconsistentDimensionality, Ndim};
+
+/// A block of voxels in one dataset of a multiscale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoxelRegion {
+    pub level: usize,
+    /// Half-open voxel index range on each axis.
+    pub ranges: Vec<Range<u64>>,
+}
+
+impl VoxelRegion {
+    pub fn n_voxels(&self) -> u64 {
+        self.ranges.iter().map(|r| r.end - r.start).product()
+    }
+}
+
+impl Multiscale {
+    /// The voxels of the dataset at `level` (whose array has the given shape)
+    /// which intersect the world-space box between corners `world_min` and `world_max`.
+    ///
+    /// Ranges are clamped to the array, and so may be empty.
+    pub fn region_at_level(
+        &self,
+        world_min: &[f64],
+        world_max: &[f64],
+        level: usize,
+        shape: &[u64],
+    ) -> Result<VoxelRegion, TransformError> {
+        let ndim = self.ndim();
+        InconsistentDimensionality::check_dims(world_min.len(), ndim)?;
+        InconsistentDimensionality::check_dims(world_max.len(), ndim)?;
+        InconsistentDimensionality::check_dims(shape.len(), ndim)?;
+        let mut lo = world_min.to_vec();
+        let mut hi = world_max.to_vec();
+        (self, level).rev_transform(&mut lo)?;
+        (self, level).rev_transform(&mut hi)?;
+        let ranges = lo
+            .iter()
+            .zip(hi.iter())
+            .zip(shape.iter())
+            .map(|((a, b), len)| {
+                let clamp = |v: f64| v.clamp(0.0, *len as f64) as u64;
+                let start = clamp(a.min(*b).floor());
+                let end = clamp(a.max(*b).ceil());
+                start..end.max(start)
+            })
+            .collect();
+        Ok(VoxelRegion { level, ranges })
+    }
+
+    /// As [Multiscale::region_at_level], choosing the highest-resolution level
+    /// at which the region has at most `max_voxels` voxels (or the lowest resolution, if none do).
+    ///
+    /// `shapes` has one array shape per dataset, in dataset order.
+    pub fn region(
+        &self,
+        world_min: &[f64],
+        world_max: &[f64],
+        shapes: &[Vec<u64>],
+        max_voxels: u64,
+    ) -> Result<VoxelRegion, TransformError> {
+        let n_levels = self.datasets().len().min(shapes.len());
+        let mut region = None;
+        for (level, shape) in shapes.iter().enumerate().take(n_levels) {
+            let r = self.region_at_level(world_min, world_max, level, shape)?;
+            let fits = r.n_voxels() <= max_voxels;
+            region = Some(r);
+            if fits {
+                break;
+            }
+        }
+        region.ok_or(TransformError::NoDataset(0))
+    }
+
+    /// The level whose voxels best match the size of one screen pixel,
+    /// when the world-space extent `world_extent` is shown on a viewport `viewport_px` pixels across.
+    ///
+    /// `displayed` gives the indices of the axes shown on screen, in the same order as the extents.
+    /// Sizes are compared on a log2 scale, averaged over the displayed axes;
+    /// a positive `bias` prefers coarser levels (undersampling) and a negative one finer levels,
+    /// e.g. a bias of 1 targets voxels twice the size of a pixel.
+    pub fn level_for_screen(
+        &self,
+        displayed: &[usize],
+        viewport_px: &[u64],
+        world_extent: &[f64],
+        bias: f64,
+    ) -> Result<usize, TransformError> {
+        InconsistentDimensionality::check_dims(displayed.len(), viewport_px.len())?;
+        InconsistentDimensionality::check_dims(displayed.len(), world_extent.len())?;
+        if let Some(&bad) = displayed.iter().find(|&&ax| ax >= self.ndim()) {
+            return Err(InconsistentDimensionality::new(bad + 1, self.ndim()).into());
+        }
+        let pixel: Vec<f64> = world_extent
+            .iter()
+            .zip(viewport_px.iter())
+            .map(|(e, px)| e.abs() / (*px).max(1) as f64)
+            .collect();
+        let mut best = None;
+        for level in 0..self.datasets().len() {
+            let voxel = self.voxel_size(level)?;
+            let log_ratio = displayed
+                .iter()
+                .zip(pixel.iter())
+                .map(|(ax, p)| (voxel[*ax].abs() / p).log2())
+                .sum::<f64>()
+                / displayed.len().max(1) as f64;
+            let cost = (log_ratio - bias).abs();
+            if best.is_none_or(|(_, c)| cost < c) {
+                best = Some((level, cost));
+            }
+        }
+        best.map(|(level, _)| level)
+            .ok_or(TransformError::NoDataset(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"{
+        "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+        "datasets": [
+            {"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]},
+            {"path": "1", "coordinateTransformations": [{"type": "scale", "scale": [2, 2]}]}
+        ],
+        "coordinateTransformations": [{"type": "scale", "scale": [0.5, 0.5]}]
+    }"#;
+
+    #[test]
+    fn region() {
+        let ms: Multiscale = serde_json::from_str(EXAMPLE).unwrap();
+        let shapes = [vec![100, 100], vec![50, 50]];
+        let r = ms
+            .region_at_level(&[1.2, -3.0], &[10.0, 4.0], 0, &shapes[0])
+            .unwrap();
+        assert_eq!(r.ranges, vec![2..20, 0..8]);
+
+        let r = ms.region(&[0.0, 0.0], &[10.0, 10.0], &shapes, 100).unwrap();
+        assert_eq!(r.level, 1);
+        assert_eq!(r.ranges, vec![0..10, 0..10]);
+    }
+
+    #[test]
+    fn level_for_screen() {
+        let ms: Multiscale = serde_json::from_str(EXAMPLE).unwrap();
+        // voxels are 0.5 and 1 world units at levels 0 and 1
+        let level = |extent: f64, bias| {
+            ms.level_for_screen(&[0, 1], &[100, 100], &[extent, extent], bias)
+                .unwrap()
+        };
+        assert_eq!(level(50.0, 0.0), 0);
+        assert_eq!(level(100.0, 0.0), 1);
+        assert_eq!(level(70.0, 0.0), 0);
+        assert_eq!(level(70.0, 0.5), 1);
+
+        assert!(matches!(
+            ms.level_for_screen(&[0, 2], &[100, 100], &[50.0, 50.0], 0.0),
+            Err(TransformError::Dimensions(_))
+        ));
+    }
+}
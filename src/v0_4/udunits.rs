@@ -0,0 +1,358 @@
+//! Checking of free-form units against a UDUNITS-2 style grammar,
+//! which the spec recommends for units it does not list.
+//!
+//! Only syntax and names are checked: the unit is not evaluated,
+//! so e.g. a unit of time on a space axis is not caught.
+//! The registry covers the SI base and derived units, their prefixes,
+//! and the common non-SI units accepted by UDUNITS-2.
+use thiserror::Error;
+
+/// Unit names, which take name prefixes (e.g. "kilo") and may be plural.
+const NAMES: &[&str] = &[
+    "ampere",
+    "angstrom",
+    "astronomical_unit",
+    "becquerel",
+    "candela",
+    "celsius",
+    "coulomb",
+    "dalton",
+    "day",
+    "degree",
+    "degree_celsius",
+    "electronvolt",
+    "farad",
+    "foot",
+    "gram",
+    "gray",
+    "henry",
+    "hertz",
+    "hour",
+    "inch",
+    "joule",
+    "katal",
+    "kelvin",
+    "liter",
+    "litre",
+    "lumen",
+    "lux",
+    "meter",
+    "metre",
+    "mile",
+    "minute",
+    "mole",
+    "newton",
+    "ohm",
+    "parsec",
+    "pascal",
+    "percent",
+    "radian",
+    "second",
+    "siemens",
+    "sievert",
+    "steradian",
+    "tesla",
+    "volt",
+    "watt",
+    "weber",
+    "week",
+    "yard",
+    "year",
+];
+
+/// Unit symbols, which take symbol prefixes (e.g. "k") and are case-sensitive.
+const SYMBOLS: &[&str] = &[
+    "%", "A", "Bq", "C", "Da", "F", "Gy", "H", "Hz", "J", "K", "L", "N", "Pa", "S", "Sv", "T", "V",
+    "W", "Wb", "au", "cd", "d", "eV", "ft", "g", "h", "in", "kat", "l", "lm", "lx", "m", "mi",
+    "min", "mol", "pc", "rad", "s", "sr", "yd", "Å", "°", "°C", "Ω",
+];
+
+const NAME_PREFIXES: &[&str] = &[
+    "yotta", "zetta", "exa", "peta", "tera", "giga", "mega", "kilo", "hecto", "deka", "deca",
+    "deci", "centi", "milli", "micro", "nano", "pico", "femto", "atto", "zepto", "yocto",
+];
+
+const SYMBOL_PREFIXES: &[&str] = &[
+    "Y", "Z", "E", "P", "T", "G", "M", "k", "h", "da", "d", "c", "m", "u", "µ", "μ", "n", "p", "f",
+    "a", "z", "y",
+];
+
+/// Why a unit string is not a valid UDUNITS-2 unit.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidUnit {
+    #[error("Unit is empty")]
+    Empty,
+    #[error("Unknown unit {0:?}")]
+    Unknown(String),
+    #[error("Unexpected {0:?} at character {1}")]
+    Syntax(char, usize),
+    #[error("Unexpected end of unit")]
+    Incomplete,
+    #[error("Parentheses nested more than {} deep at character {0}", MAX_DEPTH)]
+    TooDeep(usize),
+}
+
+/// How deeply parentheses may be nested, bounding the parser's recursion.
+const MAX_DEPTH: usize = 32;
+
+/// Check that a unit is a product of powers of known (optionally prefixed) units and numbers,
+/// e.g. `"micrometer"`, `"um"`, `"m.s-1"`, `"kg m^2 / s**2"`, or `"(1/s) per mole"`.
+pub fn validate_unit(unit: &str) -> Result<(), InvalidUnit> {
+    let chars: Vec<char> = unit.chars().collect();
+    let mut parser = Parser {
+        chars,
+        pos: 0,
+        depth: 0,
+    };
+    parser.skip_space();
+    if parser.peek().is_none() {
+        return Err(InvalidUnit::Empty);
+    }
+    parser.product()?;
+    match parser.peek() {
+        None => Ok(()),
+        Some(c) => Err(InvalidUnit::Syntax(c, parser.pos)),
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphabetic() || matches!(c, '_' | '%' | '°' | 'Ω')
+}
+
+fn is_known(ident: &str) -> bool {
+    let is_symbol = |s: &str| SYMBOLS.contains(&s);
+    if is_symbol(ident)
+        || SYMBOL_PREFIXES
+            .iter()
+            .any(|p| ident.strip_prefix(p).is_some_and(is_symbol))
+    {
+        return true;
+    }
+    let lower = ident.to_lowercase();
+    let is_name =
+        |s: &str| NAMES.contains(&s) || s.strip_suffix('s').is_some_and(|s| NAMES.contains(&s));
+    is_name(&lower)
+        || NAME_PREFIXES
+            .iter()
+            .any(|p| lower.strip_prefix(p).is_some_and(is_name))
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Number of currently open parentheses.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_space(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume `s` if the input continues with it.
+    fn eat(&mut self, s: &str) -> bool {
+        let n = s.chars().count();
+        let matches = self.chars.len() >= self.pos + n
+            && self.chars[self.pos..self.pos + n]
+                .iter()
+                .copied()
+                .eq(s.chars());
+        if matches {
+            self.pos += n;
+        }
+        matches
+    }
+
+    /// `power (op power)*`, where `op` is whitespace, `.`, `*`, `·`, `/`, or `per`.
+    fn product(&mut self) -> Result<(), InvalidUnit> {
+        self.power()?;
+        loop {
+            let before = self.pos;
+            self.skip_space();
+            let spaced = self.pos > before;
+            let explicit = self.eat("/")
+                || self.eat("*")
+                || self.eat(".")
+                || self.eat("·")
+                || (spaced && self.peek_word("per") && self.eat("per"));
+            self.skip_space();
+            match self.peek() {
+                None | Some(')') if !explicit => return Ok(()),
+                None => return Err(InvalidUnit::Incomplete),
+                Some(_) if explicit || spaced => self.power()?,
+                Some(_) => return Ok(()),
+            }
+        }
+    }
+
+    /// Whether the input continues with the word `w`, not followed by more of an identifier.
+    fn peek_word(&self, w: &str) -> bool {
+        let n = w.chars().count();
+        self.chars.len() >= self.pos + n
+            && self.chars[self.pos..self.pos + n]
+                .iter()
+                .copied()
+                .eq(w.chars())
+            && !self
+                .chars
+                .get(self.pos + n)
+                .is_some_and(|c| is_ident_char(*c))
+    }
+
+    /// `basic exponent?`, where the exponent is an integer, optionally after `^` or `**`.
+    fn power(&mut self) -> Result<(), InvalidUnit> {
+        let is_number = self.basic()?;
+        let explicit = self.eat("^") || self.eat("**");
+        if explicit || !is_number {
+            let start = self.pos;
+            if !self.integer() && explicit {
+                return Err(self
+                    .peek()
+                    .map_or(InvalidUnit::Incomplete, |c| InvalidUnit::Syntax(c, start)));
+            }
+        }
+        Ok(())
+    }
+
+    /// A number, a unit, or a parenthesised product; returns whether it was a number.
+    fn basic(&mut self) -> Result<bool, InvalidUnit> {
+        match self.peek() {
+            None => Err(InvalidUnit::Incomplete),
+            Some('(') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(InvalidUnit::TooDeep(self.pos));
+                }
+                self.pos += 1;
+                self.depth += 1;
+                self.skip_space();
+                self.product()?;
+                self.depth -= 1;
+                self.skip_space();
+                if self.eat(")") {
+                    Ok(false)
+                } else {
+                    Err(self.peek().map_or(InvalidUnit::Incomplete, |c| {
+                        InvalidUnit::Syntax(c, self.pos)
+                    }))
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => {
+                self.number()?;
+                Ok(true)
+            }
+            Some(c) if is_ident_char(c) => {
+                let start = self.pos;
+                while self.peek().is_some_and(is_ident_char) {
+                    self.pos += 1;
+                }
+                let ident: String = self.chars[start..self.pos].iter().collect();
+                if is_known(&ident) {
+                    Ok(false)
+                } else {
+                    Err(InvalidUnit::Unknown(ident))
+                }
+            }
+            Some(c) => Err(InvalidUnit::Syntax(c, self.pos)),
+        }
+    }
+
+    /// An optionally signed sequence of digits.
+    fn integer(&mut self) -> bool {
+        let start = self.pos;
+        if matches!(self.peek(), Some('-' | '+')) {
+            self.pos += 1;
+        }
+        let digits = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos == digits {
+            self.pos = start;
+            return false;
+        }
+        true
+    }
+
+    /// A decimal number with optional fraction and exponent, e.g. `1e-3` or `2.54`.
+    fn number(&mut self) -> Result<(), InvalidUnit> {
+        let start = self.pos;
+        if !self.integer() {
+            return Err(self
+                .peek()
+                .map_or(InvalidUnit::Incomplete, |c| InvalidUnit::Syntax(c, start)));
+        }
+        if self.peek() == Some('.')
+            && self
+                .chars
+                .get(self.pos + 1)
+                .is_some_and(|c| c.is_ascii_digit())
+        {
+            self.pos += 1;
+            self.integer();
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            let before = self.pos;
+            self.pos += 1;
+            if !self.integer() {
+                self.pos = before;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        for unit in [
+            "micrometer",
+            "micrometers",
+            "um",
+            "µm",
+            "m.s-1",
+            "m s^-1",
+            "kg m**2 / s2",
+            "(1/s) per mole",
+            "1e-3 m",
+            "2.54 cm",
+            "degree_Celsius",
+            "Hz",
+            "nanometre s",
+        ] {
+            assert_eq!(validate_unit(unit), Ok(()), "{unit}");
+        }
+        assert_eq!(
+            validate_unit("micron s"),
+            Err(InvalidUnit::Unknown("micron".to_owned()))
+        );
+        assert_eq!(validate_unit("  "), Err(InvalidUnit::Empty));
+        assert_eq!(validate_unit("m /"), Err(InvalidUnit::Incomplete));
+        assert_eq!(validate_unit("m^"), Err(InvalidUnit::Incomplete));
+        assert_eq!(validate_unit("(m"), Err(InvalidUnit::Incomplete));
+        assert_eq!(validate_unit("m # s"), Err(InvalidUnit::Syntax('#', 2)));
+    }
+
+    #[test]
+    fn nesting() {
+        let nested = |n: usize| format!("{}m{}", "(".repeat(n), ")".repeat(n));
+        assert_eq!(validate_unit(&nested(MAX_DEPTH)), Ok(()));
+        assert_eq!(
+            validate_unit(&nested(MAX_DEPTH + 1)),
+            Err(InvalidUnit::TooDeep(MAX_DEPTH))
+        );
+        // would overflow the stack without the limit
+        assert_eq!(
+            validate_unit(&"(".repeat(1_000_000)),
+            Err(InvalidUnit::TooDeep(MAX_DEPTH))
+        );
+    }
+}
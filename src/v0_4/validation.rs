@@ -0,0 +1,115 @@
+use thiserror::Error;
+
+use crate::util::{impl_spec_diagnostic, SpecError};
+
+use crate::store::{InvalidHierarchy, LoadError};
+
+use super::plate::{AcquisitionId, Timestamp};
+use super::well::InvalidWell;
+use super::InvalidNgffMetadata;
+use crate::core::{AxisStrictness, InvalidAxes};
+
+/// Options controlling which checks validation runs, and how.
+///
+/// The default runs every check strictly.
+#[derive(Debug, Clone)]
+pub struct ValidatorConfig {
+    pub axes: AxisStrictness,
+    /// Warn about space and time units not known to the spec.
+    pub units: bool,
+    /// Require datasets to be ordered from highest to lowest resolution.
+    pub resolution_order: bool,
+    /// Relative tolerance when comparing scale factors, e.g. `1e-6`.
+    pub float_tolerance: f64,
+    /// Cross-check a plate's wells against its acquisitions
+    /// (only where wells are available, e.g. [super::NgffMetadata::validate_group]),
+    /// also validating the wells' images if `store_checks` is set.
+    pub hcs_cross_refs: bool,
+    /// Check that arrays and groups referred to by the metadata exist,
+    /// and that label arrays have integer data types
+    /// (only where a store is available, e.g. [super::NgffMetadata::validate_group]).
+    pub store_checks: bool,
+    /// When reading, accept fractional acquisition times (truncating them)
+    /// and negative ones (dropping them), with a warning, rather than failing
+    /// (only where the document is read, e.g. [super::NgffMetadata::from_slice_with]).
+    pub lenient_times: bool,
+    /// When reading, accept ISO 8601 acquisition times (converting them to epoch milliseconds),
+    /// with a warning, rather than failing.
+    pub iso_times: bool,
+}
+
+impl Default for ValidatorConfig {
+    fn default() -> Self {
+        Self {
+            axes: AxisStrictness::default(),
+            units: true,
+            resolution_order: true,
+            float_tolerance: 0.0,
+            hcs_cross_refs: true,
+            store_checks: true,
+            lenient_times: false,
+            iso_times: false,
+        }
+    }
+}
+
+/// Failure to validate a group read from a store.
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum InvalidGroup {
+    #[error(transparent)]
+    Load(#[from] LoadError),
+    #[error(transparent)]
+    Metadata(#[from] InvalidNgffMetadata),
+    #[error("Invalid well {0}: {1}")]
+    Well(String, InvalidWell),
+    #[error("Invalid image {0}: {1}")]
+    Image(String, Box<InvalidGroup>),
+    #[error(transparent)]
+    Hierarchy(#[from] InvalidHierarchy),
+}
+
+/// A spec violation which was not treated as an error under the [ValidatorConfig].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum Warning {
+    #[error(transparent)]
+    Axes(#[from] InvalidAxes),
+    #[error("Axis {axis:?} has unknown unit {unit:?}{}", suggestion.as_ref().map(|s| format!(" (did you mean {s:?}?)")).unwrap_or_default())]
+    UnknownUnit {
+        axis: String,
+        unit: String,
+        suggestion: Option<String>,
+    },
+    #[error("Acquisition {acquisition} has invalid {key} {value}, replaced with {replacement:?}")]
+    AcquisitionTime {
+        acquisition: AcquisitionId,
+        key: &'static str,
+        value: String,
+        replacement: Option<Timestamp>,
+    },
+    #[error("omero field {field} has unexpected value {value}, so was not read")]
+    OmeroField { field: String, value: String },
+}
+
+impl SpecError for Warning {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Axes(e) => e.code(),
+            Self::UnknownUnit { .. } => "ngff::axes::unknown_unit",
+            Self::AcquisitionTime { .. } => "ngff::plate::acquisition_time",
+            Self::OmeroField { .. } => "ngff::omero::unexpected_value",
+        }
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        match self {
+            Self::Axes(e) => e.spec_url(),
+            Self::UnknownUnit { .. } => super::spec_url("axes-md"),
+            Self::AcquisitionTime { .. } => super::spec_url("plate-md"),
+            Self::OmeroField { .. } => super::spec_url("omero-md"),
+        }
+    }
+}
+
+impl_spec_diagnostic!(Warning);
@@ -3,13 +3,14 @@ use std::collections::HashSet;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::util::ZPath;
+use crate::store::join_key;
+use crate::util::{impl_spec_diagnostic, SpecError, ZPath};
 
 use super::plate::AcquisitionId;
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 pub struct Well {
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "super::serialize_version")]
     version: Option<String>,
     images: Vec<FieldOfView>,
 }
@@ -21,98 +22,233 @@ pub struct FieldOfView {
     acquisition: Option<AcquisitionId>,
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Error)]
+#[derive(Debug, PartialEq, Eq, Clone, Error)]
 pub enum InvalidWell {
-    #[error("Field of view paths are not unique")]
-    NonUniquePaths,
+    #[error("Field of view path {0:?} is not unique")]
+    NonUniquePaths(String),
     #[error("Unknown acquisition ID {0}")]
     UnknownAcquisition(AcquisitionId),
-    #[error("Acquisition ID required but not present")]
-    NoAcquisition,
-    #[error("Path must be alphanumeric")]
-    InvalidPath,
+    #[error("Acquisition ID required but not present for field of view {0:?}")]
+    NoAcquisition(String),
+    #[error("Field of view path {0:?} must be alphanumeric")]
+    InvalidPath(String),
+    #[error("Expected version {}, got {0}", super::VERSION)]
+    Version(String),
+}
+
+impl SpecError for InvalidWell {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NonUniquePaths(_) => "ngff::well::non_unique_path",
+            Self::UnknownAcquisition(_) => "ngff::well::unknown_acquisition",
+            Self::NoAcquisition(_) => "ngff::well::no_acquisition",
+            Self::InvalidPath(_) => "ngff::well::invalid_path",
+            Self::Version(_) => "ngff::well::version",
+        }
+    }
+
+    fn spec_url(&self) -> Option<String> {
+        super::spec_url("well-md")
+    }
+}
+
+impl_spec_diagnostic!(InvalidWell);
+
+impl FieldOfView {
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    pub fn acquisition(&self) -> Option<AcquisitionId> {
+        self.acquisition
+    }
+}
+
+impl Default for Well {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Well {
+    /// A well with no fields of view.
+    pub fn new() -> Self {
+        Self {
+            version: Some(super::VERSION.to_owned()),
+            images: Vec::default(),
+        }
+    }
+
+    pub fn images(&self) -> &[FieldOfView] {
+        self.images.as_slice()
+    }
+
+    /// The distinct acquisitions of the fields of view, in order of first appearance.
+    pub fn acquisitions(&self) -> Vec<AcquisitionId> {
+        let mut seen = HashSet::with_capacity(self.images.len());
+        self.images
+            .iter()
+            .filter_map(|im| im.acquisition)
+            .filter(|acq| seen.insert(*acq))
+            .collect()
+    }
+
+    /// Add a field of view, returning it.
+    ///
+    /// Fails if the path is not alphanumeric or the well already has a field of view with that path.
+    pub fn add_image<S: Into<ZPath>>(
+        &mut self,
+        path: S,
+        acquisition: Option<AcquisitionId>,
+    ) -> Result<&FieldOfView, InvalidWell> {
+        let path = path.into();
+        if !path.chars().all(char::is_alphanumeric) {
+            return Err(InvalidWell::InvalidPath(path));
+        }
+        if self.images.iter().any(|im| im.path == path) {
+            return Err(InvalidWell::NonUniquePaths(path));
+        }
+        self.images.push(FieldOfView { path, acquisition });
+        Ok(self.images.last().expect("just pushed"))
+    }
+
+    /// Store keys of every field of view's image group, given the well group's key.
+    pub fn image_paths(&self, well_group_path: &str) -> Vec<ZPath> {
+        self.images
+            .iter()
+            .map(|fov| join_key([well_group_path, fov.path.as_str()]))
+            .collect()
+    }
+
     pub fn validate(
         &self,
         acquisitions: Option<HashSet<AcquisitionId>>,
     ) -> Result<(), InvalidWell> {
+        self.validate_against(acquisitions.as_ref())
+    }
+
+    /// As [Well::validate], borrowing the acquisition IDs so that many wells can share them.
+    pub(crate) fn validate_against(
+        &self,
+        acquisitions: Option<&HashSet<AcquisitionId>>,
+    ) -> Result<(), InvalidWell> {
+        if let Some(v) = &self.version {
+            if v != super::VERSION {
+                return Err(InvalidWell::Version(v.clone()));
+            }
+        }
         let mut paths = HashSet::with_capacity(self.images.len());
         for im in self.images.iter() {
             if !im.path.chars().all(char::is_alphanumeric) {
-                return Err(InvalidWell::InvalidPath);
+                return Err(InvalidWell::InvalidPath(im.path.clone()));
             }
 
             if !paths.insert(im.path.as_str()) {
-                return Err(InvalidWell::NonUniquePaths);
+                return Err(InvalidWell::NonUniquePaths(im.path.clone()));
             }
 
-            if let Some(acqs) = acquisitions.as_ref() {
+            if let Some(acqs) = acquisitions {
                 if let Some(acq) = im.acquisition.as_ref() {
                     if !acqs.contains(acq) {
                         return Err(InvalidWell::UnknownAcquisition(*acq));
                     }
                 } else {
-                    return Err(InvalidWell::NoAcquisition);
+                    return Err(InvalidWell::NoAcquisition(im.path.clone()));
                 }
             }
         }
         Ok(())
     }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Set the version to [super::VERSION] if it is missing.
+    pub fn fill_version(&mut self) {
+        self.version
+            .get_or_insert_with(|| super::VERSION.to_owned());
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod arbitrary {
+    use super::*;
+    use crate::util::Validity;
+    use proptest::prelude::*;
+
+    impl Arbitrary for Well {
+        type Parameters = Validity;
+        type Strategy = BoxedStrategy<Self>;
+
+        /// Valid wells have uniquely-numbered fields of view.
+        fn arbitrary_with(validity: Validity) -> Self::Strategy {
+            let path = match validity {
+                Validity::Valid => "[0-9]{1,2}",
+                Validity::Any => "[0-9a-z_/]{0,2}",
+            };
+            prop::collection::vec((path, prop::option::of(0u64..4)), 0..8)
+                .prop_map(move |images| {
+                    let mut seen = HashSet::new();
+                    let images = images
+                        .into_iter()
+                        .filter(|(p, _)| validity == Validity::Any || seen.insert(p.clone()))
+                        .map(|(path, acquisition)| FieldOfView { path, acquisition })
+                        .collect();
+                    Well {
+                        version: Some(crate::v0_4::VERSION.to_owned()),
+                        images,
+                    }
+                })
+                .boxed()
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json;
-
-    const EXAMPLE1: &str = r#"
-    {
-        "images": [
-            {
-                "acquisition": 1,
-                "path": "0"
-            },
-            {
-                "acquisition": 1,
-                "path": "1"
-            },
-            {
-                "acquisition": 2,
-                "path": "2"
-            },
-            {
-                "acquisition": 2,
-                "path": "3"
-            }
-        ],
-        "version": "0.4"
-    }
-    "#;
-
-    const EXAMPLE2: &str = r#"
-    {
-        "images": [
-            {
-                "acquisition": 0,
-                "path": "0"
-            },
-            {
-                "acquisition": 3,
-                "path": "1"
-            }
-        ],
-        "version": "0.4"
+
+    /// The spec's 4-field well example, from the corpus.
+    fn example1() -> Well {
+        example("well_4fields")
+    }
+
+    /// The spec's 2-field well example, from the corpus.
+    fn example2() -> Well {
+        example("well_2fields")
+    }
+
+    fn example(name: &str) -> Well {
+        let fixture = crate::v0_4::corpus::fixture(name).unwrap();
+        fixture.parse().unwrap().well().unwrap().clone()
     }
-    "#;
 
     #[test]
     fn examples() {
-        let w1: Well = serde_json::from_str(EXAMPLE1).unwrap();
+        let w1: Well = example1();
         w1.validate(None).unwrap();
 
-        let w2: Well = serde_json::from_str(EXAMPLE2).unwrap();
+        let w2: Well = example2();
         w2.validate(None).unwrap();
     }
+
+    #[test]
+    fn add_image() {
+        let mut well = Well::new();
+        well.add_image("0", Some(2)).unwrap();
+        well.add_image("1", Some(1)).unwrap();
+        well.add_image("2", Some(2)).unwrap();
+        assert_eq!(
+            well.add_image("1", None).unwrap_err(),
+            InvalidWell::NonUniquePaths("1".to_owned())
+        );
+        assert_eq!(
+            well.add_image("a/b", None).unwrap_err(),
+            InvalidWell::InvalidPath("a/b".to_owned())
+        );
+        assert_eq!(well.images().len(), 3);
+        assert_eq!(well.acquisitions(), [2, 1]);
+        well.validate(Some(HashSet::from([1, 2]))).unwrap();
+    }
 }
@@ -0,0 +1,561 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::RwLock;
+
+use crate::store::{join_key, ReadableStore, StoreError, WritableStore};
+use crate::util::{Validate, Validated, ZPath};
+
+use super::{
+    InvalidGroup, InvalidNgffMetadata, InvalidWell, NgffMetadata, Plate, ValidatorConfig,
+    VersionPolicy, Well, ARRAY_KEY, ATTRS_KEY, GROUP_KEY,
+};
+
+/// Failure to write metadata to a store.
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum WriteError {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error("Could not serialize metadata: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Metadata(#[from] InvalidNgffMetadata),
+    #[error("Invalid well {0}: {1}")]
+    Well(String, InvalidWell),
+    #[error("Well {0} is not listed in the plate")]
+    UnlistedWell(String),
+    #[error("Well {0} is listed in the plate but was not given")]
+    MissingWell(String),
+    #[error("Invalid group {0}: {1}")]
+    Group(String, Box<InvalidGroup>),
+}
+
+/// The zarr v2 metadata of an uncompressed array to create for a dataset,
+/// so that its pixel data can be written later with any zarr implementation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrayPlaceholder {
+    pub shape: Vec<u64>,
+    pub chunks: Vec<u64>,
+    /// A zarr v2 data type, e.g. `"<u2"`.
+    pub dtype: String,
+}
+
+impl ArrayPlaceholder {
+    fn to_zarray(&self) -> Value {
+        json!({
+            "zarr_format": 2,
+            "shape": self.shape,
+            "chunks": self.chunks,
+            "dtype": self.dtype,
+            "compressor": null,
+            "fill_value": 0,
+            "order": "C",
+            "filters": null,
+        })
+    }
+}
+
+pub(crate) fn write_json<S: WritableStore + ?Sized, T: Serialize + ?Sized>(
+    store: &S,
+    key: &str,
+    value: &T,
+) -> Result<(), WriteError> {
+    store.set(key, &serde_json::to_vec_pretty(value)?)?;
+    Ok(())
+}
+
+/// Write group metadata for `path` under `root`, and for every group in between.
+pub(crate) fn write_groups<S: WritableStore + ?Sized>(
+    store: &S,
+    root: &str,
+    path: &str,
+) -> Result<(), WriteError> {
+    let mut group = join_key([root]);
+    for seg in join_key([path]).split('/').filter(|s| !s.is_empty()) {
+        group = join_key([group.as_str(), seg]);
+        write_json(
+            store,
+            &join_key([group.as_str(), GROUP_KEY]),
+            &json!({"zarr_format": 2}),
+        )?;
+    }
+    Ok(())
+}
+
+impl Validated<NgffMetadata> {
+    /// Lay down the document's zarr hierarchy at `group`:
+    /// the group's metadata and attributes, a group for every listed label,
+    /// and the intermediate groups of every multiscale dataset.
+    ///
+    /// `arrays` gives the placeholder array to create at each dataset path (relative to `group`),
+    /// if any.
+    pub fn write_skeleton<S, F>(&self, store: &S, group: &str, arrays: F) -> Result<(), WriteError>
+    where
+        S: WritableStore + ?Sized,
+        F: Fn(&str) -> Option<ArrayPlaceholder>,
+    {
+        write_groups(store, "", group)?;
+        write_json(
+            store,
+            &join_key([group, ATTRS_KEY]),
+            &self.to_value(VersionPolicy::Inject)?,
+        )?;
+        for label in self.labels.iter().flatten() {
+            write_groups(store, group, label)?;
+        }
+        for ds in self
+            .multiscales
+            .iter()
+            .flatten()
+            .flat_map(|ms| ms.datasets())
+        {
+            let path = join_key([ds.path()]);
+            if let Some((parent, _)) = path.rsplit_once('/') {
+                write_groups(store, group, parent)?;
+            }
+            if let Some(array) = arrays(&path) {
+                write_json(
+                    store,
+                    &join_key([group, path.as_str(), ARRAY_KEY]),
+                    &array.to_zarray(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write a plate's attributes at `group`, and each well's attributes at its path under `group`.
+///
+/// Nothing is written unless the wells given are exactly those listed in the plate,
+/// and every well's images refer to the plate's acquisitions.
+pub fn write_plate<S, I>(
+    store: &S,
+    group: &str,
+    plate: &Validated<Plate>,
+    wells: I,
+) -> Result<(), WriteError>
+where
+    S: WritableStore + ?Sized,
+    I: IntoIterator<Item = (ZPath, Validated<Well>)>,
+{
+    let plate_meta = NgffMetadata::builder()
+        .with_plate(Plate::clone(plate))
+        .build()?
+        .into_validated()?;
+    let mut wells: HashMap<ZPath, Validated<Well>> = wells
+        .into_iter()
+        .map(|(path, well)| (join_key([path.as_str()]), well))
+        .collect();
+    let ids = plate.acquisitions().map(|_| plate.acquisition_ids());
+    let mut well_metas = Vec::with_capacity(plate.wells().len());
+    for pw in plate.wells() {
+        let path = join_key([pw.path()]);
+        let well = wells
+            .remove(&path)
+            .ok_or_else(|| WriteError::MissingWell(path.clone()))?;
+        well.validate_against(ids.as_ref())
+            .map_err(|e| WriteError::Well(path.clone(), e))?;
+        let meta = NgffMetadata::builder()
+            .with_well(well.into_inner())
+            .build()?
+            .into_validated()?;
+        well_metas.push((path, meta));
+    }
+    if let Some(path) = wells.into_keys().next() {
+        return Err(WriteError::UnlistedWell(path));
+    }
+
+    plate_meta.write_skeleton(store, group, |_| None)?;
+    for (path, meta) in well_metas {
+        meta.write_skeleton(store, &join_key([group, path.as_str()]), |_| None)?;
+    }
+    Ok(())
+}
+
+/// Stages writes to a store, so that a set of metadata documents is applied all together or not at all.
+///
+/// Writes and deletions are held in memory, and reads see them over the underlying store.
+/// [Transaction::commit] validates every group whose attributes were staged,
+/// and only then writes to the store,
+/// restoring the previous values if any write fails part-way through.
+#[derive(Debug)]
+pub struct Transaction<'a, S: ?Sized> {
+    store: &'a S,
+    /// Values to write, or `None` to delete.
+    staged: RwLock<BTreeMap<String, Option<Vec<u8>>>>,
+}
+
+impl<'a, S: ReadableStore + WritableStore + ?Sized> Transaction<'a, S> {
+    pub fn new(store: &'a S) -> Self {
+        Self {
+            store,
+            staged: RwLock::default(),
+        }
+    }
+
+    /// The keys written or deleted so far, sorted.
+    pub fn staged_keys(&self) -> Vec<String> {
+        self.staged.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Validate every group with staged attributes according to the config
+    /// (see [NgffMetadata::validate_group]), then apply the staged writes.
+    pub fn commit(self, config: &ValidatorConfig) -> Result<(), WriteError> {
+        for (key, value) in self.staged.read().unwrap().iter() {
+            let Some(group) = key.strip_suffix(ATTRS_KEY) else {
+                continue;
+            };
+            if value.is_none() {
+                continue;
+            }
+            let group = join_key([group]);
+            NgffMetadata::validate_group(&self, &group, config)
+                .map_err(|e| WriteError::Group(group, Box::new(e)))?;
+        }
+        self.apply()
+    }
+
+    /// Apply the staged writes without validating them.
+    pub fn apply(self) -> Result<(), WriteError> {
+        let staged = self.staged.into_inner().unwrap();
+        let mut previous = Vec::with_capacity(staged.len());
+        for (key, value) in staged.iter() {
+            let result = self.store.get(key).and_then(|old| {
+                previous.push((key, old));
+                match value {
+                    Some(v) => self.store.set(key, v),
+                    None => self.store.delete(key),
+                }
+            });
+            if let Err(e) = result {
+                for (key, old) in previous.into_iter().rev() {
+                    let _ = match old {
+                        Some(v) => self.store.set(key, &v),
+                        None => self.store.delete(key),
+                    };
+                }
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: ReadableStore + ?Sized> ReadableStore for Transaction<'_, S> {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        if let Some(v) = self.staged.read().unwrap().get(key) {
+            return Ok(v.clone());
+        }
+        self.store.get(key)
+    }
+}
+
+impl<S: ?Sized> WritableStore for Transaction<'_, S> {
+    fn set(&self, key: &str, value: &[u8]) -> Result<(), StoreError> {
+        self.staged
+            .write()
+            .unwrap()
+            .insert(key.to_owned(), Some(value.to_vec()));
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), StoreError> {
+        self.staged.write().unwrap().insert(key.to_owned(), None);
+        Ok(())
+    }
+}
+
+/// The keys and documents which a write or migration would make, recorded by [DryRun].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WritePlan {
+    writes: BTreeMap<String, Vec<u8>>,
+    deletes: BTreeSet<String>,
+}
+
+impl WritePlan {
+    /// Whether nothing would be written or deleted.
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty() && self.deletes.is_empty()
+    }
+
+    /// The keys which would be written, sorted.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.writes.keys().map(String::as_str)
+    }
+
+    /// The bytes which would be written at a key.
+    pub fn get(&self, key: &str) -> Option<&[u8]> {
+        self.writes.get(key).map(Vec::as_slice)
+    }
+
+    /// The JSON document which would be written at a key, if it is JSON.
+    pub fn document(&self, key: &str) -> Option<Value> {
+        serde_json::from_slice(self.get(key)?).ok()
+    }
+
+    /// The keys which would be deleted, sorted.
+    pub fn deleted(&self) -> impl Iterator<Item = &str> {
+        self.deletes.iter().map(String::as_str)
+    }
+}
+
+/// Records writes to a store instead of making them,
+/// so that any writing API can be previewed by passing a `DryRun` in place of its store.
+///
+/// Reads see the recorded writes over the underlying store,
+/// so operations which read back what they wrote (e.g. [Transaction::commit]) behave as they would for real.
+/// The store itself is never written to.
+#[derive(Debug)]
+pub struct DryRun<'a, S: ?Sized> {
+    store: &'a S,
+    plan: RwLock<WritePlan>,
+}
+
+impl<'a, S: ?Sized> DryRun<'a, S> {
+    pub fn new(store: &'a S) -> Self {
+        Self {
+            store,
+            plan: RwLock::default(),
+        }
+    }
+
+    /// The writes and deletions recorded so far.
+    pub fn plan(&self) -> WritePlan {
+        self.plan.read().unwrap().clone()
+    }
+
+    pub fn into_plan(self) -> WritePlan {
+        self.plan.into_inner().unwrap()
+    }
+}
+
+impl<S: ReadableStore + ?Sized> ReadableStore for DryRun<'_, S> {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        let plan = self.plan.read().unwrap();
+        if let Some(v) = plan.writes.get(key) {
+            return Ok(Some(v.clone()));
+        }
+        if plan.deletes.contains(key) {
+            return Ok(None);
+        }
+        self.store.get(key)
+    }
+}
+
+impl<S: ?Sized> WritableStore for DryRun<'_, S> {
+    fn set(&self, key: &str, value: &[u8]) -> Result<(), StoreError> {
+        let mut plan = self.plan.write().unwrap();
+        plan.deletes.remove(key);
+        plan.writes.insert(key.to_owned(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), StoreError> {
+        let mut plan = self.plan.write().unwrap();
+        plan.writes.remove(key);
+        plan.deletes.insert(key.to_owned());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use crate::v0_4::{Multiscale, ValidatorConfig};
+
+    const PLATE: &str = r#"{
+        "acquisitions": [{"id": 1}],
+        "rows": [{"name": "A"}],
+        "columns": [{"name": "1"}, {"name": "2"}],
+        "wells": [
+            {"path": "A/1", "rowIndex": 0, "columnIndex": 0},
+            {"path": "A/2", "rowIndex": 0, "columnIndex": 1}
+        ]
+    }"#;
+
+    fn well(acquisition: u64) -> Validated<Well> {
+        serde_json::from_value(json!({"images": [{"path": "0", "acquisition": acquisition}]}))
+            .unwrap()
+    }
+
+    #[test]
+    fn skeleton() {
+        let ms: Multiscale = serde_json::from_str(
+            r#"{
+                "axes": [{"name": "y", "type": "space"}, {"name": "x", "type": "space"}],
+                "datasets": [
+                    {"path": "0", "coordinateTransformations": [{"type": "scale", "scale": [1, 1]}]},
+                    {"path": "pyramid/1", "coordinateTransformations": [{"type": "scale", "scale": [2, 2]}]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let meta = NgffMetadata::builder()
+            .with_multiscale(ms)
+            .build()
+            .unwrap()
+            .into_validated()
+            .unwrap();
+        let store = MemoryStore::new();
+        meta.write_skeleton(&store, "out/img", |path| {
+            let size = if path == "0" { 64 } else { 32 };
+            Some(ArrayPlaceholder {
+                shape: vec![size, size],
+                chunks: vec![32, 32],
+                dtype: "<u2".to_owned(),
+            })
+        })
+        .unwrap();
+
+        assert_eq!(
+            store.keys(),
+            [
+                "out/.zgroup",
+                "out/img/.zattrs",
+                "out/img/.zgroup",
+                "out/img/0/.zarray",
+                "out/img/pyramid/.zgroup",
+                "out/img/pyramid/1/.zarray",
+            ]
+        );
+        let (read, _) =
+            NgffMetadata::validate_group(&store, "out/img", &ValidatorConfig::default()).unwrap();
+        assert_eq!(read.multiscales().unwrap()[0].datasets().len(), 2);
+    }
+
+    #[test]
+    fn plate() {
+        let plate: Validated<Plate> = serde_json::from_str(PLATE).unwrap();
+        let store = MemoryStore::new();
+
+        let err = write_plate(
+            &store,
+            "p",
+            &plate,
+            [("A/1".to_owned(), well(1)), ("A/2".to_owned(), well(2))],
+        );
+        assert!(matches!(
+            err,
+            Err(WriteError::Well(p, InvalidWell::UnknownAcquisition(2))) if p == "A/2"
+        ));
+        let err = write_plate(&store, "p", &plate, [("A/1".to_owned(), well(1))]);
+        assert!(matches!(err, Err(WriteError::MissingWell(p)) if p == "A/2"));
+        assert!(store.keys().is_empty());
+
+        write_plate(
+            &store,
+            "p",
+            &plate,
+            [("A/1".to_owned(), well(1)), ("A/2".to_owned(), well(1))],
+        )
+        .unwrap();
+        let config = ValidatorConfig {
+            store_checks: false,
+            ..Default::default()
+        };
+        NgffMetadata::validate_group(&store, "p", &config).unwrap();
+        assert!(store.keys().contains(&"p/A/.zgroup".to_owned()));
+    }
+
+    #[test]
+    fn transaction() {
+        /// Fails to write any key containing "fail".
+        struct Failing(MemoryStore);
+
+        impl ReadableStore for Failing {
+            fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+                self.0.get(key)
+            }
+        }
+
+        impl WritableStore for Failing {
+            fn set(&self, key: &str, value: &[u8]) -> Result<(), StoreError> {
+                if key.contains("fail") {
+                    return Err(StoreError::Other("failed".to_owned()));
+                }
+                self.0.set(key, value)
+            }
+
+            fn delete(&self, key: &str) -> Result<(), StoreError> {
+                self.0.delete(key)
+            }
+        }
+
+        let plate: Validated<Plate> = serde_json::from_str(PLATE).unwrap();
+        let store = Failing(MemoryStore::new());
+        let config = ValidatorConfig {
+            store_checks: false,
+            ..Default::default()
+        };
+
+        // a well which doesn't match the plate is never written
+        let tx = Transaction::new(&store);
+        let wells = [("A/1".to_owned(), well(1)), ("A/2".to_owned(), well(1))];
+        write_plate(&tx, "p", &plate, wells).unwrap();
+        tx.set(
+            "p/A/2/.zattrs",
+            br#"{"well": {"images": [{"path": "0", "acquisition": 2}]}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            tx.commit(&config),
+            Err(WriteError::Group(g, _)) if g == "p"
+        ));
+        assert!(store.0.keys().is_empty());
+
+        // a failed write restores the store
+        store.0.set("p/.zattrs", b"{}").unwrap();
+        let tx = Transaction::new(&store);
+        tx.set("p/.zattrs", br#"{"labels": []}"#).unwrap();
+        tx.set("p/zfail/.zgroup", b"{}").unwrap();
+        assert!(matches!(tx.commit(&config), Err(WriteError::Store(_))));
+        assert_eq!(store.0.keys(), ["p/.zattrs"]);
+        assert_eq!(store.0.get("p/.zattrs").unwrap().unwrap(), b"{}");
+    }
+
+    #[test]
+    fn dry_run() {
+        let plate: Validated<Plate> = serde_json::from_str(PLATE).unwrap();
+        let store = MemoryStore::new();
+        store.set("p/.zattrs", b"{}").unwrap();
+        let dry_run = DryRun::new(&store);
+        let tx = Transaction::new(&dry_run);
+        let wells = [("A/1".to_owned(), well(1)), ("A/2".to_owned(), well(1))];
+        write_plate(&tx, "p", &plate, wells).unwrap();
+        let config = ValidatorConfig {
+            store_checks: false,
+            ..Default::default()
+        };
+        tx.commit(&config).unwrap();
+
+        let plan = dry_run.into_plan();
+        assert_eq!(store.keys(), ["p/.zattrs"]);
+        assert_eq!(
+            plan.keys().collect::<Vec<_>>(),
+            [
+                "p/.zattrs",
+                "p/.zgroup",
+                "p/A/.zgroup",
+                "p/A/1/.zattrs",
+                "p/A/1/.zgroup",
+                "p/A/2/.zattrs",
+                "p/A/2/.zgroup",
+            ]
+        );
+        assert_eq!(
+            plan.document("p/A/1/.zattrs").unwrap()["well"]["images"][0]["acquisition"],
+            1
+        );
+
+        let dry_run = DryRun::new(&store);
+        dry_run.delete("p/.zattrs").unwrap();
+        assert_eq!(dry_run.get("p/.zattrs").unwrap(), None);
+        assert_eq!(dry_run.plan().deleted().collect::<Vec<_>>(), ["p/.zattrs"]);
+        assert!(store.contains("p/.zattrs").unwrap());
+    }
+}
@@ -0,0 +1,86 @@
+//! Example documents from the OME-NGFF v0.5 specification.
+//!
+//! Only multiscales are implemented for this version, so only their examples are included.
+
+use serde::Deserialize;
+
+use crate::util::JsonError;
+
+use super::Multiscale;
+
+/// A spec example document: the attributes of a zarr v3 group, under the `ome` key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixture {
+    pub name: &'static str,
+    pub json: &'static str,
+}
+
+macro_rules! fixture {
+    ($name:literal) => {
+        Fixture {
+            name: $name,
+            json: include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/corpus/v0_5/",
+                $name,
+                ".json"
+            )),
+        }
+    };
+}
+
+pub const FIXTURES: &[Fixture] = &[
+    fixture!("multiscales_example"),
+    fixture!("multiscales_transformations"),
+];
+
+pub fn fixtures() -> impl Iterator<Item = &'static Fixture> {
+    FIXTURES.iter()
+}
+
+/// The fixture with the given name, if there is one.
+pub fn fixture(name: &str) -> Option<&'static Fixture> {
+    fixtures().find(|fix| fix.name == name)
+}
+
+#[derive(Deserialize)]
+struct Attributes {
+    ome: Ome,
+}
+
+#[derive(Deserialize)]
+struct Ome {
+    version: String,
+    multiscales: Vec<Multiscale>,
+}
+
+impl Fixture {
+    /// The `ome` attribute's version and multiscales.
+    pub fn parse(&self) -> Result<(String, Vec<Multiscale>), JsonError> {
+        let attrs: Attributes =
+            serde_json::from_str(self.json).map_err(|e| JsonError::Read("Multiscale", e))?;
+        Ok((attrs.ome.version, attrs.ome.multiscales))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corpus_is_valid() {
+        for fix in fixtures() {
+            let (version, multiscales) = fix.parse().unwrap();
+            assert_eq!(version, "0.5", "{}", fix.name);
+            assert!(!multiscales.is_empty(), "{}", fix.name);
+            for ms in multiscales.iter() {
+                let ndim = ms.axes().len();
+                for ds in ms.datasets() {
+                    ds.validate(Some(ndim))
+                        .unwrap_or_else(|e| panic!("{}: {e}", fix.name));
+                }
+            }
+        }
+        assert!(fixture("multiscales_example").is_some());
+    }
+}
@@ -1 +1,17 @@
+//! OME-NGFF v0.5.
+//!
+//! Only multiscales are implemented for this version so far.
+//! Axes, units, scale/translation transforms, and datasets are unchanged from v0.4,
+//! and are the same types as in the `v0_4` module.
+#[cfg(feature = "corpus")]
+pub mod corpus;
+mod multiscale;
 
+#[cfg(feature = "proptest")]
+pub use crate::core::{arb_axes, arb_transforms};
+pub use crate::core::{
+    simplify, Axis, AxisStrictness, AxisType, AxisUnit, CoordinateTransformation, CoreAxis,
+    Dimension, InvalidAxes, InvalidCoordinateTransforms, MultiscaleDataset, Rounding, ScaleOrPath,
+    SpaceUnit, TimeUnit, Transform, TransformError, TranslationOrPath, Unit,
+};
+pub use multiscale::Multiscale;
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::{Axis, CoordinateTransformation, MultiscaleDataset};
+
+/// A multiscale image.
+///
+/// Unlike in v0.4, the version is not given here,
+/// but once for the whole `ome` attribute.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Multiscale {
+    pub(crate) axes: Vec<Axis>,
+    pub(crate) datasets: Vec<MultiscaleDataset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) coordinate_transformations: Option<Vec<CoordinateTransformation>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<Value>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub(crate) multiscale_type: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) metadata: Option<HashMap<String, Value>>,
+}
+
+impl Multiscale {
+    pub fn axes(&self) -> &[Axis] {
+        self.axes.as_slice()
+    }
+
+    pub fn datasets(&self) -> &[MultiscaleDataset] {
+        self.datasets.as_slice()
+    }
+
+    /// Transforms applied to every dataset, after the dataset's own.
+    pub fn coordinate_transformations(&self) -> Option<&[CoordinateTransformation]> {
+        self.coordinate_transformations.as_deref()
+    }
+
+    pub fn metadata(&self) -> Option<&HashMap<String, Value>> {
+        self.metadata.as_ref()
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+/// A version of the OME-NGFF specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NgffVersion {
+    V0_4,
+    V0_5,
+}
+
+impl NgffVersion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V0_4 => "0.4",
+            Self::V0_5 => "0.5",
+        }
+    }
+
+    /// Parse a `version` field value, returning `None` for unsupported versions.
+    pub fn from_version_str(s: &str) -> Option<Self> {
+        match s {
+            "0.4" => Some(Self::V0_4),
+            "0.5" => Some(Self::V0_5),
+            _ => None,
+        }
+    }
+}
+
+impl Display for NgffVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+pub(crate) mod sealed {
+    pub trait Sealed {}
+}
+
+/// A version of the specification as a type, so that code can be generic over versions.
+///
+/// Implemented by a marker type in each version's module, e.g. [crate::v0_4::V0_4].
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+pub trait Spec: sealed::Sealed {
+    const VERSION: NgffVersion;
+
+    /// The attributes of a group.
+    type Metadata: SpecType;
+    type Multiscale: SpecType;
+    type ImageLabel: SpecType;
+    type Omero: SpecType;
+    type Plate: SpecType;
+    type Well: SpecType;
+}
+
+/// Behaviour common to the metadata types of every version.
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+pub trait SpecType:
+    std::fmt::Debug
+    + Clone
+    + serde::Serialize
+    + serde::de::DeserializeOwned
+    + crate::util::Validate
+    + crate::util::ToCanonicalJson
+{
+}
+
+#[cfg(any(feature = "v0_4", feature = "v0_5"))]
+impl<T> SpecType for T where
+    T: std::fmt::Debug
+        + Clone
+        + serde::Serialize
+        + serde::de::DeserializeOwned
+        + crate::util::Validate
+        + crate::util::ToCanonicalJson
+{
+}
+
+#[derive(Deserialize)]
+struct VersionOnly {
+    /// Some writers give the version as a number, e.g. `0.4`.
+    version: Option<serde_json::Value>,
+}
+
+impl VersionOnly {
+    fn version(self) -> Option<NgffVersion> {
+        match self.version? {
+            serde_json::Value::String(s) => NgffVersion::from_version_str(&s),
+            serde_json::Value::Number(n) => NgffVersion::from_version_str(&n.to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Sniff {
+    ome: Option<VersionOnly>,
+    multiscales: Option<Vec<VersionOnly>>,
+    #[serde(rename = "image-label")]
+    image_label: Option<VersionOnly>,
+    plate: Option<VersionOnly>,
+    well: Option<VersionOnly>,
+}
+
+/// Determine the spec version of a group's attributes without fully parsing them.
+///
+/// Only `version` fields are captured; serde skips all other keys without building them.
+/// A v0.5-style `ome` wrapper takes precedence over top-level keys.
+/// Returns `None` if the bytes are not a JSON object,
+/// no version is found, or the version is not supported.
+pub fn detect_version(bytes: &[u8]) -> Option<NgffVersion> {
+    let sniff: Sniff = serde_json::from_slice(bytes).ok()?;
+    if let Some(ome) = sniff.ome {
+        return ome.version();
+    }
+    sniff
+        .multiscales
+        .into_iter()
+        .flatten()
+        .chain(sniff.image_label)
+        .chain(sniff.plate)
+        .chain(sniff.well)
+        .find(|v| v.version.is_some())
+        .and_then(VersionOnly::version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff() {
+        assert_eq!(
+            detect_version(br#"{"ome": {"version": "0.5", "multiscales": []}}"#),
+            Some(NgffVersion::V0_5)
+        );
+        assert_eq!(
+            detect_version(br#"{"plate": {"wells": [{"path": "A/1"}], "version": "0.4"}}"#),
+            Some(NgffVersion::V0_4)
+        );
+        assert_eq!(
+            detect_version(br#"{"multiscales": [{"axes": []}, {"version": "0.4"}]}"#),
+            Some(NgffVersion::V0_4)
+        );
+        assert_eq!(detect_version(br#"{"labels": ["a"]}"#), None);
+        assert_eq!(detect_version(br#"{"well": {"version": "0.1"}}"#), None);
+        assert_eq!(detect_version(b"[1, 2]"), None);
+        assert_eq!(
+            detect_version(br#"{"multiscales": [{"version": 0.4}]}"#),
+            Some(NgffVersion::V0_4)
+        );
+        assert_eq!(
+            detect_version(br#"{"ome": {"version": 0.5}}"#),
+            Some(NgffVersion::V0_5)
+        );
+        assert_eq!(detect_version(br#"{"well": {"version": 4}}"#), None);
+        assert_eq!(detect_version(br#"{"well": {"version": [0, 4]}}"#), None);
+    }
+}
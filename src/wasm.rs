@@ -0,0 +1,27 @@
+//! JavaScript bindings, exchanging metadata as JSON strings.
+use wasm_bindgen::prelude::*;
+
+use crate::util::ToCanonicalJson;
+use crate::{parse_any, AnyNgffMetadata};
+
+/// The spec version declared by a group's attributes, if supported.
+#[wasm_bindgen(js_name = detectVersion)]
+pub fn detect_version(json: &str) -> Option<String> {
+    crate::detect_version(json.as_bytes()).map(|v| v.to_string())
+}
+
+/// Parse a group's attributes, returning them as canonical JSON.
+#[wasm_bindgen(js_name = parseMetadata)]
+pub fn parse_metadata(json: &str) -> Result<String, JsError> {
+    match parse_any(json.as_bytes())? {
+        AnyNgffMetadata::V0_4(meta) => Ok(meta.to_canonical_json()?),
+    }
+}
+
+/// Parse and validate a group's attributes, throwing an error describing the first problem.
+#[wasm_bindgen(js_name = validateMetadata)]
+pub fn validate_metadata(json: &str) -> Result<(), JsError> {
+    match parse_any(json.as_bytes())? {
+        AnyNgffMetadata::V0_4(meta) => Ok(meta.validate()?),
+    }
+}